@@ -1097,6 +1097,12 @@ class JniScanningCallbacks : ScanningCallbacks {
                                  method_onSyncTransferredCallback, pa_source,
                                  status, addr.get());
   }
+
+  void OnMsftAdvMonitorDeviceFound(uint8_t monitor_handle,
+                                   RawAddress address) override {}
+
+  void OnMsftAdvMonitorDeviceLost(uint8_t monitor_handle,
+                                  RawAddress address) override {}
 };
 
 /**
@@ -1335,8 +1341,10 @@ static void gattClientConnectNative(JNIEnv* env, jobject object, jint clientif,
                                     jint initiating_phys) {
   if (!sGattIf) return;
 
-  sGattIf->client->connect(clientif, str2addr(env, address), isDirect,
-                           transport, opportunistic, initiating_phys);
+  // A public address type uses the type stored for the device.
+  sGattIf->client->connect(clientif, str2addr(env, address),
+                           0 /* BLE_ADDR_PUBLIC */, isDirect, transport,
+                           opportunistic, initiating_phys);
 }
 
 static void gattClientDisconnectNative(JNIEnv* env, jobject object,
@@ -1415,7 +1423,8 @@ static void gattClientReadCharacteristicNative(JNIEnv* env, jobject object,
                                                jint authReq) {
   if (!sGattIf) return;
 
-  sGattIf->client->read_characteristic(conn_id, handle, authReq);
+  sGattIf->client->read_characteristic(conn_id, handle, authReq,
+                                       0 /* automatic bearer */);
 }
 
 static void gattClientReadUsingCharacteristicUuidNative(
@@ -1453,7 +1462,7 @@ static void gattClientWriteCharacteristicNative(JNIEnv* env, jobject object,
 
   sGattIf->client->write_characteristic(conn_id, handle, write_type, auth_req,
                                         reinterpret_cast<uint8_t*>(p_value),
-                                        len);
+                                        len, 0 /* automatic bearer */);
 
   env->ReleaseByteArrayElements(value, p_value, 0);
 }
@@ -1516,7 +1525,7 @@ static void gattSetScanParametersNative(JNIEnv* env, jobject object,
                                         jint scan_window_unit) {
   if (!sGattIf) return;
   sGattIf->scanner->SetScanParameters(
-      client_if, scan_interval_unit, scan_window_unit,
+      client_if, scan_interval_unit, scan_window_unit, 1 /* LE 1M */,
       base::Bind(&set_scan_params_cmpl_cb, client_if));
 }
 
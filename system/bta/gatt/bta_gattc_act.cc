@@ -73,6 +73,12 @@ static void bta_gattc_phy_update_cback(tGATT_IF gatt_if, uint16_t conn_id,
 static void bta_gattc_conn_update_cback(tGATT_IF gatt_if, uint16_t conn_id,
                                         uint16_t interval, uint16_t latency,
                                         uint16_t timeout, tGATT_STATUS status);
+static void bta_gattc_subrate_chg_cback(tGATT_IF gatt_if, uint16_t conn_id,
+                                        uint16_t subrate_factor,
+                                        uint16_t latency, uint16_t cont_num,
+                                        uint16_t timeout, tGATT_STATUS status);
+static void bta_gattc_req_cback(uint16_t conn_id, uint32_t trans_id,
+                                tGATTS_REQ_TYPE type, tGATTS_DATA* p_data);
 static void bta_gattc_init_bk_conn(const tBTA_GATTC_API_OPEN* p_data,
                                    tBTA_GATTC_RCB* p_clreg);
 
@@ -81,11 +87,12 @@ static tGATT_CBACK bta_gattc_cl_cback = {
     .p_cmpl_cb = bta_gattc_cmpl_cback,
     .p_disc_res_cb = bta_gattc_disc_res_cback,
     .p_disc_cmpl_cb = bta_gattc_disc_cmpl_cback,
-    .p_req_cb = nullptr,
+    .p_req_cb = bta_gattc_req_cback,
     .p_enc_cmpl_cb = bta_gattc_enc_cmpl_cback,
     .p_congestion_cb = bta_gattc_cong_cback,
     .p_phy_update_cb = bta_gattc_phy_update_cback,
-    .p_conn_update_cb = bta_gattc_conn_update_cback};
+    .p_conn_update_cb = bta_gattc_conn_update_cback,
+    .p_subrate_chg_cb = bta_gattc_subrate_chg_cback};
 
 /* opcode(tGATTC_OPTYPE) order has to be comply with internal event order */
 static uint16_t bta_gattc_opcode_to_int_evt[] = {
@@ -903,7 +910,8 @@ void bta_gattc_read(tBTA_GATTC_CLCB* p_clcb, const tBTA_GATTC_DATA* p_data) {
     memset(&read_param, 0, sizeof(tGATT_READ_PARAM));
     read_param.by_handle.handle = p_data->api_read.handle;
     read_param.by_handle.auth_req = p_data->api_read.auth_req;
-    status = GATTC_Read(p_clcb->bta_conn_id, GATT_READ_BY_HANDLE, &read_param);
+    status = GATTC_Read(p_clcb->bta_conn_id, GATT_READ_BY_HANDLE, &read_param,
+                        p_data->api_read.bearer);
   } else {
     tGATT_READ_PARAM read_param;
     memset(&read_param, 0, sizeof(tGATT_READ_BY_TYPE));
@@ -912,7 +920,8 @@ void bta_gattc_read(tBTA_GATTC_CLCB* p_clcb, const tBTA_GATTC_DATA* p_data) {
     read_param.char_type.e_handle = p_data->api_read.e_handle;
     read_param.char_type.uuid = p_data->api_read.uuid;
     read_param.char_type.auth_req = p_data->api_read.auth_req;
-    status = GATTC_Read(p_clcb->bta_conn_id, GATT_READ_BY_TYPE, &read_param);
+    status = GATTC_Read(p_clcb->bta_conn_id, GATT_READ_BY_TYPE, &read_param,
+                        p_data->api_read.bearer);
   }
 
   /* read fail */
@@ -972,8 +981,8 @@ void bta_gattc_write(tBTA_GATTC_CLCB* p_clcb, const tBTA_GATTC_DATA* p_data) {
     if (p_data->api_write.p_value)
       memcpy(attr.value, p_data->api_write.p_value, p_data->api_write.len);
 
-    status =
-        GATTC_Write(p_clcb->bta_conn_id, p_data->api_write.write_type, &attr);
+    status = GATTC_Write(p_clcb->bta_conn_id, p_data->api_write.write_type,
+                         &attr, p_data->api_write.bearer);
   }
 
   /* write fail */
@@ -1022,6 +1031,19 @@ void bta_gattc_confirm(tBTA_GATTC_CLCB* p_clcb, const tBTA_GATTC_DATA* p_data) {
 /** read complete */
 static void bta_gattc_read_cmpl(tBTA_GATTC_CLCB* p_clcb,
                                 const tBTA_GATTC_OP_CMPL* p_data) {
+  if (p_clcb->p_q_cmd->hdr.event == BTA_GATTC_API_READ_MULTI_EVT) {
+    GATT_READ_MULTI_OP_CB cb = p_clcb->p_q_cmd->api_read_multi.read_cb;
+    void* my_cb_data = p_clcb->p_q_cmd->api_read_multi.read_cb_data;
+
+    osi_free_and_reset((void**)&p_clcb->p_q_cmd);
+
+    if (cb) {
+      cb(p_clcb->bta_conn_id, p_data->status, p_data->p_cmpl->att_value.len,
+         p_data->p_cmpl->att_value.value, my_cb_data);
+    }
+    return;
+  }
+
   GATT_READ_OP_CB cb = p_clcb->p_q_cmd->api_read.read_cb;
   void* my_cb_data = p_clcb->p_q_cmd->api_read.read_cb_data;
 
@@ -1134,8 +1156,13 @@ void bta_gattc_op_cmpl(tBTA_GATTC_CLCB* p_clcb, const tBTA_GATTC_DATA* p_data) {
       return;
   }
 
-  if (p_clcb->p_q_cmd->hdr.event !=
-      bta_gattc_opcode_to_int_evt[op - GATTC_OPTYPE_READ]) {
+  /* read multiple completes as a regular read */
+  bool is_read_multi =
+      op == GATTC_OPTYPE_READ &&
+      p_clcb->p_q_cmd->hdr.event == BTA_GATTC_API_READ_MULTI_EVT;
+  if (!is_read_multi &&
+      p_clcb->p_q_cmd->hdr.event !=
+          bta_gattc_opcode_to_int_evt[op - GATTC_OPTYPE_READ]) {
     uint8_t mapped_op =
         p_clcb->p_q_cmd->hdr.event - BTA_GATTC_API_READ_EVT + GATTC_OPTYPE_READ;
     if (mapped_op > GATTC_OPTYPE_INDICATION) mapped_op = 0;
@@ -1595,3 +1622,43 @@ static void bta_gattc_conn_update_cback(tGATT_IF gatt_if, uint16_t conn_id,
   cb_data.conn_update.status = status;
   (*p_clreg->p_cback)(BTA_GATTC_CONN_UPDATE_EVT, &cb_data);
 }
+
+static void bta_gattc_subrate_chg_cback(tGATT_IF gatt_if, uint16_t conn_id,
+                                        uint16_t subrate_factor,
+                                        uint16_t latency, uint16_t cont_num,
+                                        uint16_t timeout, tGATT_STATUS status) {
+  tBTA_GATTC_RCB* p_clreg = bta_gattc_cl_get_regcb(gatt_if);
+
+  if (!p_clreg || !p_clreg->p_cback) {
+    LOG(ERROR) << __func__ << ": client_if=" << gatt_if << " not found";
+    return;
+  }
+
+  tBTA_GATTC cb_data;
+  cb_data.subrate_chg.conn_id = conn_id;
+  cb_data.subrate_chg.subrate_factor = subrate_factor;
+  cb_data.subrate_chg.latency = latency;
+  cb_data.subrate_chg.cont_num = cont_num;
+  cb_data.subrate_chg.timeout = timeout;
+  cb_data.subrate_chg.status = status;
+  (*p_clreg->p_cback)(BTA_GATTC_SUBRATE_CHG_EVT, &cb_data);
+}
+
+/** Only MTU exchanges initiated by the remote device are reported to clients */
+static void bta_gattc_req_cback(uint16_t conn_id, uint32_t trans_id,
+                                tGATTS_REQ_TYPE type, tGATTS_DATA* p_data) {
+  if (type != GATTS_REQ_TYPE_MTU) return;
+
+  tGATT_IF gatt_if = GATT_GET_GATT_IF(conn_id);
+  tBTA_GATTC_RCB* p_clreg = bta_gattc_cl_get_regcb(gatt_if);
+
+  if (!p_clreg || !p_clreg->p_cback) {
+    LOG(ERROR) << __func__ << ": client_if=" << +gatt_if << " not found";
+    return;
+  }
+
+  tBTA_GATTC cb_data;
+  cb_data.mtu_chg.conn_id = conn_id;
+  cb_data.mtu_chg.mtu = p_data->mtu;
+  (*p_clreg->p_cback)(BTA_GATTC_MTU_CHG_EVT, &cb_data);
+}
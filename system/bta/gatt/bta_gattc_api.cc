@@ -371,13 +371,15 @@ void BTA_GATTC_GetGattDb(uint16_t conn_id, uint16_t start_handle,
  *
  * Parameters       conn_id - connection ID.
  *                  handle - characteritic handle to read.
+ *                  bearer - bearer to send the request on.
  *
  * Returns          None
  *
  ******************************************************************************/
 void BTA_GATTC_ReadCharacteristic(uint16_t conn_id, uint16_t handle,
                                   tGATT_AUTH_REQ auth_req,
-                                  GATT_READ_OP_CB callback, void* cb_data) {
+                                  GATT_READ_OP_CB callback, void* cb_data,
+                                  tGATT_BEARER bearer) {
   tBTA_GATTC_API_READ* p_buf =
       (tBTA_GATTC_API_READ*)osi_calloc(sizeof(tBTA_GATTC_API_READ));
 
@@ -387,6 +389,7 @@ void BTA_GATTC_ReadCharacteristic(uint16_t conn_id, uint16_t handle,
   p_buf->handle = handle;
   p_buf->read_cb = callback;
   p_buf->read_cb_data = cb_data;
+  p_buf->bearer = bearer;
 
   bta_sys_sendmsg(p_buf);
 }
@@ -452,12 +455,14 @@ void BTA_GATTC_ReadCharDescr(uint16_t conn_id, uint16_t handle,
  *
  * Parameters       conn_id - connectino ID.
  *                    p_read_multi - pointer to the read multiple parameter.
+ *                    callback - called with the concatenated values.
  *
  * Returns          None
  *
  ******************************************************************************/
 void BTA_GATTC_ReadMultiple(uint16_t conn_id, tBTA_GATTC_MULTI* p_read_multi,
-                            tGATT_AUTH_REQ auth_req) {
+                            tGATT_AUTH_REQ auth_req,
+                            GATT_READ_MULTI_OP_CB callback, void* cb_data) {
   tBTA_GATTC_API_READ_MULTI* p_buf =
       (tBTA_GATTC_API_READ_MULTI*)osi_calloc(sizeof(tBTA_GATTC_API_READ_MULTI));
 
@@ -465,6 +470,8 @@ void BTA_GATTC_ReadMultiple(uint16_t conn_id, tBTA_GATTC_MULTI* p_read_multi,
   p_buf->hdr.layer_specific = conn_id;
   p_buf->auth_req = auth_req;
   p_buf->num_attr = p_read_multi->num_attr;
+  p_buf->read_cb = callback;
+  p_buf->read_cb_data = cb_data;
 
   if (p_buf->num_attr > 0)
     memcpy(p_buf->handles, p_read_multi->handles,
@@ -483,6 +490,7 @@ void BTA_GATTC_ReadMultiple(uint16_t conn_id, tBTA_GATTC_MULTI* p_read_multi,
  *                  handle - characteristic handle to write.
  *                  write_type - type of write.
  *                  value - the value to be written.
+ *                  bearer - bearer to send the request on.
  *
  * Returns          None
  *
@@ -491,7 +499,8 @@ void BTA_GATTC_WriteCharValue(uint16_t conn_id, uint16_t handle,
                               tGATT_WRITE_TYPE write_type,
                               std::vector<uint8_t> value,
                               tGATT_AUTH_REQ auth_req,
-                              GATT_WRITE_OP_CB callback, void* cb_data) {
+                              GATT_WRITE_OP_CB callback, void* cb_data,
+                              tGATT_BEARER bearer) {
   tBTA_GATTC_API_WRITE* p_buf = (tBTA_GATTC_API_WRITE*)osi_calloc(
       sizeof(tBTA_GATTC_API_WRITE) + value.size());
 
@@ -503,6 +512,7 @@ void BTA_GATTC_WriteCharValue(uint16_t conn_id, uint16_t handle,
   p_buf->len = value.size();
   p_buf->write_cb = callback;
   p_buf->write_cb_data = cb_data;
+  p_buf->bearer = bearer;
 
   if (value.size() > 0) {
     p_buf->p_value = (uint8_t*)(p_buf + 1);
@@ -120,6 +120,7 @@ typedef struct {
   tBTA_GATTC_EVT cmpl_evt;
   GATT_READ_OP_CB read_cb;
   void* read_cb_data;
+  tGATT_BEARER bearer;
 } tBTA_GATTC_API_READ;
 
 typedef struct {
@@ -132,6 +133,7 @@ typedef struct {
   uint8_t* p_value;
   GATT_WRITE_OP_CB write_cb;
   void* write_cb_data;
+  tGATT_BEARER bearer;
 } tBTA_GATTC_API_WRITE;
 
 typedef struct {
@@ -161,6 +163,8 @@ typedef struct {
   tGATT_AUTH_REQ auth_req;
   uint8_t num_attr;
   uint16_t handles[GATT_MAX_READ_MULTI_HANDLES];
+  GATT_READ_MULTI_OP_CB read_cb;
+  void* read_cb_data;
 } tBTA_GATTC_API_READ_MULTI;
 
 typedef struct {
@@ -71,6 +71,8 @@ typedef enum : uint8_t {
   BTA_GATTC_CONGEST_EVT = 24,       /* Congestion event */
   BTA_GATTC_PHY_UPDATE_EVT = 25,    /* PHY change event */
   BTA_GATTC_CONN_UPDATE_EVT = 26,   /* Connection parameters update event */
+  BTA_GATTC_MTU_CHG_EVT = 27,       /* MTU exchanged by the remote device */
+  BTA_GATTC_SUBRATE_CHG_EVT = 28,   /* LE connection subrate change event */
 } tBTA_GATTC_EVT;
 
 #define CASE_RETURN_TEXT(code) \
@@ -95,6 +97,8 @@ inline std::string gatt_client_event_text(const tBTA_GATTC_EVT& event) {
     CASE_RETURN_TEXT(BTA_GATTC_CONGEST_EVT);
     CASE_RETURN_TEXT(BTA_GATTC_PHY_UPDATE_EVT);
     CASE_RETURN_TEXT(BTA_GATTC_CONN_UPDATE_EVT);
+    CASE_RETURN_TEXT(BTA_GATTC_MTU_CHG_EVT);
+    CASE_RETURN_TEXT(BTA_GATTC_SUBRATE_CHG_EVT);
     default:
       return base::StringPrintf("UNKNOWN[%hhu]", event);
   }
@@ -229,11 +233,26 @@ typedef struct {
   tGATT_STATUS status;
 } tBTA_GATTC_CONN_UPDATE;
 
+typedef struct {
+  tGATT_IF server_if;
+  uint16_t conn_id;
+  uint16_t subrate_factor;
+  uint16_t latency;
+  uint16_t cont_num;
+  uint16_t timeout;
+  tGATT_STATUS status;
+} tBTA_GATTC_SUBRATE_CHG;
+
 typedef struct {
   RawAddress remote_bda;
   uint16_t conn_id;
 } tBTA_GATTC_SERVICE_CHANGED;
 
+typedef struct {
+  uint16_t conn_id;
+  uint16_t mtu;
+} tBTA_GATTC_MTU_CHG;
+
 typedef union {
   tGATT_STATUS status;
 
@@ -253,6 +272,8 @@ typedef union {
   tBTA_GATTC_PHY_UPDATE phy_update;
   tBTA_GATTC_CONN_UPDATE conn_update;
   tBTA_GATTC_SERVICE_CHANGED service_changed;
+  tBTA_GATTC_MTU_CHG mtu_chg;
+  tBTA_GATTC_SUBRATE_CHG subrate_chg;
 } tBTA_GATTC;
 
 /* GATTC enable callback function */
@@ -588,6 +609,9 @@ extern void BTA_GATTC_GetGattDb(uint16_t conn_id, uint16_t start_handle,
 typedef void (*GATT_READ_OP_CB)(uint16_t conn_id, tGATT_STATUS status,
                                 uint16_t handle, uint16_t len, uint8_t* value,
                                 void* data);
+typedef void (*GATT_READ_MULTI_OP_CB)(uint16_t conn_id, tGATT_STATUS status,
+                                      uint16_t len, uint8_t* value,
+                                      void* data);
 typedef void (*GATT_WRITE_OP_CB)(uint16_t conn_id, tGATT_STATUS status,
                                  uint16_t handle, uint16_t len,
                                  const uint8_t* value, void* data);
@@ -602,13 +626,15 @@ typedef void (*GATT_CONFIGURE_MTU_OP_CB)(uint16_t conn_id, tGATT_STATUS status,
  *
  * Parameters       conn_id - connectino ID.
  *                  handle - characteritic handle to read.
+ *                  bearer - bearer to send the request on.
  *
  * Returns          None
  *
  ******************************************************************************/
 void BTA_GATTC_ReadCharacteristic(uint16_t conn_id, uint16_t handle,
                                   tGATT_AUTH_REQ auth_req,
-                                  GATT_READ_OP_CB callback, void* cb_data);
+                                  GATT_READ_OP_CB callback, void* cb_data,
+                                  tGATT_BEARER bearer = GATT_BEARER_AUTO);
 
 /**
  * This function is called to read a value of characteristic with uuid equal to
@@ -645,6 +671,7 @@ void BTA_GATTC_ReadCharDescr(uint16_t conn_id, uint16_t handle,
  *                  handle - characteristic handle to write.
  *                  write_type - type of write.
  *                  value - the value to be written.
+ *                  bearer - bearer to send the request on.
  *
  * Returns          None
  *
@@ -653,7 +680,8 @@ void BTA_GATTC_WriteCharValue(uint16_t conn_id, uint16_t handle,
                               tGATT_WRITE_TYPE write_type,
                               std::vector<uint8_t> value,
                               tGATT_AUTH_REQ auth_req,
-                              GATT_WRITE_OP_CB callback, void* cb_data);
+                              GATT_WRITE_OP_CB callback, void* cb_data,
+                              tGATT_BEARER bearer = GATT_BEARER_AUTO);
 
 /*******************************************************************************
  *
@@ -765,13 +793,16 @@ extern void BTA_GATTC_ExecuteWrite(uint16_t conn_id, bool is_execute);
  *
  * Parameters       conn_id - connectino ID.
  *                    p_read_multi - read multiple parameters.
+ *                    callback - called with the concatenated values.
  *
  * Returns          None
  *
  ******************************************************************************/
 extern void BTA_GATTC_ReadMultiple(uint16_t conn_id,
                                    tBTA_GATTC_MULTI* p_read_multi,
-                                   tGATT_AUTH_REQ auth_req);
+                                   tGATT_AUTH_REQ auth_req,
+                                   GATT_READ_MULTI_OP_CB callback,
+                                   void* cb_data);
 
 /*******************************************************************************
  *
@@ -45,6 +45,7 @@
 #include "osi/include/log.h"
 #include "stack/include/btm_ble_api.h"
 #include "stack/include/btu.h"
+#include "stack/include/hci_error_code.h"
 #include "types/bluetooth/uuid.h"
 #include "types/raw_address.h"
 #include "vendor_api.h"
@@ -304,7 +305,14 @@ class BleScannerInterfaceImpl : public BleScannerInterface {
   }
 
   void SetScanParameters(int scanner_id, int scan_interval, int scan_window,
-                         Callback cb) override {
+                         int scan_phy, Callback cb) override {
+    // BTM only scans on the LE 1M PHY.
+    if (scan_phy != PHY_LE_1M) {
+      LOG_WARN("Unsupported scan PHYs 0x%02x", scan_phy);
+      do_in_jni_thread(FROM_HERE, base::Bind(cb, BTM_MODE_UNSUPPORTED));
+      return;
+    }
+
     do_in_main_thread(
         FROM_HERE, base::Bind(&BTM_BleSetScanParams, scan_interval, scan_window,
                               BTM_BLE_SCAN_MODE_ACTI,
@@ -455,6 +463,26 @@ class BleScannerInterfaceImpl : public BleScannerInterface {
                    jni_thread_wrapper(FROM_HERE, std::move(start_sync_cb))));
   }
 
+  // BTM has no support for the Microsoft HCI extension.
+  static constexpr uint8_t kMsftUnsupported = HCI_ERR_ILLEGAL_COMMAND;
+
+  bool IsMsftSupported() override { return false; }
+
+  void MsftAdvMonitorAdd(MsftAdvMonitor monitor,
+                         MsftAdvMonitorAddCallback cb) override {
+    do_in_jni_thread(FROM_HERE, base::Bind(cb, 0, kMsftUnsupported));
+  }
+
+  void MsftAdvMonitorRemove(uint8_t monitor_handle,
+                            MsftAdvMonitorRemoveCallback cb) override {
+    do_in_jni_thread(FROM_HERE, base::Bind(cb, kMsftUnsupported));
+  }
+
+  void MsftAdvMonitorEnable(bool enable,
+                            MsftAdvMonitorEnableCallback cb) override {
+    do_in_jni_thread(FROM_HERE, base::Bind(cb, kMsftUnsupported));
+  }
+
   ScanningCallbacks* callbacks_ = nullptr;
 };
 
@@ -140,6 +140,10 @@ std::string bta_gattc_event_text(const tBTA_GATTC_EVT& event) {
       return std::string("PHY change");
     case BTA_GATTC_CONN_UPDATE_EVT:
       return std::string("connection parameters update");
+    case BTA_GATTC_MTU_CHG_EVT:
+      return std::string("MTU changed");
+    case BTA_GATTC_SUBRATE_CHG_EVT:
+      return std::string("subrate change");
   }
 }
 
@@ -239,11 +243,23 @@ static void btif_gattc_upstreams_evt(uint16_t event, char* p_param) {
                 p_data->conn_update.status);
       break;
 
+    case BTA_GATTC_SUBRATE_CHG_EVT:
+      HAL_CBACK(bt_gatt_callbacks, client->subrate_chg_cb,
+                p_data->subrate_chg.conn_id, p_data->subrate_chg.subrate_factor,
+                p_data->subrate_chg.latency, p_data->subrate_chg.cont_num,
+                p_data->subrate_chg.timeout, p_data->subrate_chg.status);
+      break;
+
     case BTA_GATTC_SRVC_CHG_EVT:
       HAL_CBACK(bt_gatt_callbacks, client->service_changed_cb,
                 p_data->service_changed.conn_id);
       break;
 
+    case BTA_GATTC_MTU_CHG_EVT:
+      HAL_CBACK(bt_gatt_callbacks, client->mtu_changed_cb,
+                p_data->mtu_chg.conn_id, p_data->mtu_chg.mtu);
+      break;
+
     default:
       LOG_ERROR("Unhandled event (%d)!", event);
       break;
@@ -304,17 +320,22 @@ static bt_status_t btif_gattc_unregister_app(int client_if) {
   return do_in_jni_thread(Bind(&btif_gattc_unregister_app_impl, client_if));
 }
 
-void btif_gattc_open_impl(int client_if, RawAddress address, bool is_direct,
+void btif_gattc_open_impl(int client_if, RawAddress address,
+                          tBLE_ADDR_TYPE addr_type, bool is_direct,
                           int transport_p, bool opportunistic,
                           int initiating_phys) {
-  // Ensure device is in inquiry database
-  tBLE_ADDR_TYPE addr_type = BLE_ADDR_PUBLIC;
   int device_type = 0;
   tBT_TRANSPORT transport = (tBT_TRANSPORT)BT_TRANSPORT_LE;
 
-  if (btif_get_address_type(address, &addr_type) &&
-      btif_get_device_type(address, &device_type) &&
-      device_type != BT_DEVICE_TYPE_BREDR) {
+  if (addr_type != BLE_ADDR_PUBLIC) {
+    // Only LE devices have non-public addresses. The caller knows the type,
+    // possibly of a device that was never seen, so don't look it up.
+    device_type = BT_DEVICE_TYPE_BLE;
+    BTA_DmAddBleDevice(address, addr_type, device_type);
+  } else if (btif_get_address_type(address, &addr_type) &&
+             btif_get_device_type(address, &device_type) &&
+             device_type != BT_DEVICE_TYPE_BREDR) {
+    // Ensure device is in inquiry database
     BTA_DmAddBleDevice(address, addr_type, device_type);
   }
 
@@ -370,12 +391,13 @@ void btif_gattc_open_impl(int client_if, RawAddress address, bool is_direct,
 }
 
 static bt_status_t btif_gattc_open(int client_if, const RawAddress& bd_addr,
-                                   bool is_direct, int transport,
-                                   bool opportunistic, int initiating_phys) {
+                                   uint8_t addr_type, bool is_direct,
+                                   int transport, bool opportunistic,
+                                   int initiating_phys) {
   CHECK_BTGATT_INIT();
   // Closure will own this value and free it.
   return do_in_jni_thread(Bind(&btif_gattc_open_impl, client_if, bd_addr,
-                               is_direct, transport, opportunistic,
+                               addr_type, is_direct, transport, opportunistic,
                                initiating_phys));
 }
 
@@ -455,10 +477,46 @@ void read_char_cb(uint16_t conn_id, tGATT_STATUS status, uint16_t handle,
 }
 
 static bt_status_t btif_gattc_read_char(int conn_id, uint16_t handle,
-                                        int auth_req) {
+                                        int auth_req, int bearer) {
   CHECK_BTGATT_INIT();
   return do_in_jni_thread(Bind(&BTA_GATTC_ReadCharacteristic, conn_id, handle,
-                               auth_req, read_char_cb, nullptr));
+                               auth_req, read_char_cb, nullptr,
+                               static_cast<tGATT_BEARER>(bearer)));
+}
+
+void read_multi_cb(uint16_t conn_id, tGATT_STATUS status, uint16_t len,
+                   uint8_t* value, void* data) {
+  std::vector<uint8_t> val(value, value + len);
+
+  CLI_CBACK_WRAP_IN_JNI(
+      read_multiple_cb,
+      base::BindOnce(
+          [](read_multiple_callback cb, uint16_t conn_id, tGATT_STATUS status,
+             std::vector<uint8_t> moved_value) {
+            cb(conn_id, status, moved_value.data(), moved_value.size());
+          },
+          bt_gatt_callbacks->client->read_multiple_cb, conn_id, status,
+          std::move(val)));
+}
+
+static bt_status_t btif_gattc_read_multiple(int conn_id,
+                                            const uint16_t* handles,
+                                            size_t num_handles, int auth_req) {
+  CHECK_BTGATT_INIT();
+
+  if (num_handles == 0 || num_handles > GATT_MAX_READ_MULTI_HANDLES)
+    return BT_STATUS_PARM_INVALID;
+
+  tBTA_GATTC_MULTI read_multi;
+  read_multi.num_attr = num_handles;
+  memcpy(read_multi.handles, handles, sizeof(uint16_t) * num_handles);
+
+  return do_in_jni_thread(Bind(
+      [](int conn_id, tBTA_GATTC_MULTI read_multi, int auth_req) {
+        BTA_GATTC_ReadMultiple(conn_id, &read_multi, auth_req, read_multi_cb,
+                               nullptr);
+      },
+      conn_id, read_multi, auth_req));
 }
 
 void read_using_char_uuid_cb(uint16_t conn_id, tGATT_STATUS status,
@@ -526,7 +584,8 @@ void write_char_cb(uint16_t conn_id, tGATT_STATUS status, uint16_t handle,
 
 static bt_status_t btif_gattc_write_char(int conn_id, uint16_t handle,
                                          int write_type, int auth_req,
-                                         const uint8_t* val, size_t len) {
+                                         const uint8_t* val, size_t len,
+                                         int bearer) {
   CHECK_BTGATT_INIT();
 
   std::vector<uint8_t> value(val, val + len);
@@ -535,7 +594,8 @@ static bt_status_t btif_gattc_write_char(int conn_id, uint16_t handle,
 
   return do_in_jni_thread(Bind(&BTA_GATTC_WriteCharValue, conn_id, handle,
                                write_type, std::move(value), auth_req,
-                               write_char_cb, nullptr));
+                               write_char_cb, nullptr,
+                               static_cast<tGATT_BEARER>(bearer)));
 }
 
 void write_descr_cb(uint16_t conn_id, tGATT_STATUS status, uint16_t handle,
@@ -655,6 +715,17 @@ bt_status_t btif_gattc_conn_parameter_update(const RawAddress& bd_addr,
       min_interval, max_interval, latency, timeout, min_ce_len, max_ce_len));
 }
 
+static bt_status_t btif_gattc_subrate_request(const RawAddress& bd_addr,
+                                              int subrate_min, int subrate_max,
+                                              int max_latency, int cont_num,
+                                              int sup_timeout) {
+  CHECK_BTGATT_INIT();
+  do_in_main_thread(FROM_HERE, Bind(&BTM_BleSubrateRequest, bd_addr,
+                                    subrate_min, subrate_max, max_latency,
+                                    cont_num, sup_timeout));
+  return BT_STATUS_SUCCESS;
+}
+
 static bt_status_t btif_gattc_set_preferred_phy(const RawAddress& bd_addr,
                                                 uint8_t tx_phy, uint8_t rx_phy,
                                                 uint16_t phy_options) {
@@ -711,4 +782,6 @@ const btgatt_client_interface_t btgattClientInterface = {
     btif_gattc_set_preferred_phy,
     btif_gattc_read_phy,
     btif_gattc_test_command,
-    btif_gattc_get_gatt_db};
+    btif_gattc_get_gatt_db,
+    btif_gattc_read_multiple,
+    btif_gattc_subrate_request};
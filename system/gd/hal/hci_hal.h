@@ -97,6 +97,13 @@ class HciHal : public ::bluetooth::Module {
   // V5.2, Vol 4, Part E, Section 5.4.5) to the Bluetooth controller.
   // Packets must be processed in order.
   virtual void sendIsoData(HciPacket data) = 0;
+
+  // Get the opcode of the Microsoft-defined Bluetooth HCI extension commands,
+  // which is chosen by the controller vendor. Returns 0 if the controller
+  // doesn't support the extension or the HAL can't tell.
+  virtual uint16_t getMsftOpcode() {
+    return 0;
+  }
 };
 // LINT.ThenChange(fuzz/fuzz_hci_hal.h)
 
@@ -60,6 +60,10 @@ constexpr uint16_t HCI_DEV_NONE = 0xffff;
 #define MGMT_OP_INDEX_LIST 0x0003
 #define MGMT_EV_INDEX_ADDED 0x0004
 #define MGMT_EV_COMMAND_COMP 0x0001
+#define MGMT_EV_COMMAND_STATUS 0x0002
+/* Only available on kernels carrying the ChromeOS vendor extensions */
+#define MGMT_OP_GET_VS_OPCODE 0x0102
+#define MGMT_VS_OPCODE_MSFT 0x0001
 #define MGMT_EV_SIZE_MAX 1024
 #define WRITE_NO_INTR(fn) \
   do {                    \
@@ -85,6 +89,23 @@ struct mgmt_event_read_index {
   uint16_t index[0];
 } __attribute__((packed));
 
+struct mgmt_cp_get_vs_opcode {
+  uint16_t hci_id;
+  uint16_t vendor_specification;
+} __attribute__((packed));
+
+struct mgmt_event_get_vs_opcode {
+  uint16_t cc_opcode;
+  uint8_t status;
+  uint16_t hci_id;
+  uint16_t opcode;
+} __attribute__((packed));
+
+struct mgmt_event_command_status {
+  uint16_t cs_opcode;
+  uint8_t status;
+} __attribute__((packed));
+
 int waitHciDev(int hci_interface) {
   struct sockaddr_hci addr;
   struct pollfd fds[1];
@@ -169,6 +190,84 @@ int waitHciDev(int hci_interface) {
   return -1;
 }
 
+// Ask the kernel for the opcode of the MSFT extension of |hci_interface|.
+// Returns 0 if the controller or the kernel doesn't support it.
+uint16_t GetMsftOpcode(int hci_interface) {
+  constexpr int kMgmtTimeoutMs = 2000;
+  struct sockaddr_hci addr;
+  struct pollfd fds[1];
+  struct mgmt_pkt ev;
+  uint16_t opcode = 0;
+
+  int fd = socket(PF_BLUETOOTH, SOCK_RAW, BTPROTO_HCI);
+  if (fd < 0) {
+    LOG_ERROR("Bluetooth socket error: %s", strerror(errno));
+    return 0;
+  }
+  memset(&addr, 0, sizeof(addr));
+  addr.hci_family = AF_BLUETOOTH;
+  addr.hci_dev = HCI_DEV_NONE;
+  addr.hci_channel = HCI_CHANNEL_CONTROL;
+  if (bind(fd, (struct sockaddr*)&addr, sizeof(addr)) < 0) {
+    LOG_ERROR("HCI Channel Control: %s", strerror(errno));
+    close(fd);
+    return 0;
+  }
+
+  fds[0].fd = fd;
+  fds[0].events = POLLIN;
+
+  struct mgmt_cp_get_vs_opcode cp = {
+      .hci_id = static_cast<uint16_t>(hci_interface),
+      .vendor_specification = MGMT_VS_OPCODE_MSFT,
+  };
+  ev.opcode = MGMT_OP_GET_VS_OPCODE;
+  ev.index = HCI_DEV_NONE;
+  ev.len = sizeof(cp);
+  memcpy(ev.data, &cp, sizeof(cp));
+
+  ssize_t wrote;
+  WRITE_NO_INTR(wrote = write(fd, &ev, 6 + sizeof(cp)));
+  if (wrote != static_cast<ssize_t>(6 + sizeof(cp))) {
+    LOG_ERROR("Unable to write mgmt command: %s", strerror(errno));
+    close(fd);
+    return 0;
+  }
+
+  while (1) {
+    int n;
+    WRITE_NO_INTR(n = poll(fds, 1, kMgmtTimeoutMs));
+    if (n <= 0) {
+      LOG_WARN("No reply to get MSFT opcode: %s", n == 0 ? "timeout" : strerror(errno));
+      break;
+    }
+
+    if (fds[0].revents & POLLIN) {
+      WRITE_NO_INTR(n = read(fd, &ev, sizeof(struct mgmt_pkt)));
+      if (n < 0) {
+        LOG_ERROR("Error reading control channel: %s", strerror(errno));
+        break;
+      }
+
+      if (ev.opcode == MGMT_EV_COMMAND_COMP) {
+        struct mgmt_event_get_vs_opcode* cc = (struct mgmt_event_get_vs_opcode*)ev.data;
+        if (cc->cc_opcode != MGMT_OP_GET_VS_OPCODE) continue;
+        if (cc->status == 0 && cc->hci_id == hci_interface) opcode = cc->opcode;
+        break;
+      } else if (ev.opcode == MGMT_EV_COMMAND_STATUS) {
+        struct mgmt_event_command_status* cs = (struct mgmt_event_command_status*)ev.data;
+        if (cs->cs_opcode != MGMT_OP_GET_VS_OPCODE) continue;
+        // Kernels without the vendor extensions reject the command.
+        LOG_INFO("Get MSFT opcode rejected with status 0x%02hhx", cs->status);
+        break;
+      }
+    }
+  }
+
+  close(fd);
+  return opcode;
+}
+
 // Connect to Linux HCI socket
 int ConnectToSocket() {
   int socket_fd = socket(AF_BLUETOOTH, SOCK_RAW, BTPROTO_HCI);
@@ -261,6 +360,11 @@ class HciHalHost : public HciHal {
     write_to_fd(packet);
   }
 
+  uint16_t getMsftOpcode() override {
+    int hci_interface = 0;  // Assume we only have HCI 0
+    return GetMsftOpcode(hci_interface);
+  }
+
  protected:
   void ListDependencies(ModuleList* list) const {
     list->add<metrics::CounterMetrics>();
@@ -74,6 +74,15 @@ class LeAclConnectionTracker : public LeConnectionManagementCallbacks {
   void OnLocalAddressUpdate(AddressWithType address_with_type) override {
     SAVE_OR_CALL(OnLocalAddressUpdate, address_with_type);
   }
+  void OnLeSubrateChange(
+      hci::ErrorCode hci_status,
+      uint16_t subrate_factor,
+      uint16_t peripheral_latency,
+      uint16_t continuation_number,
+      uint16_t supervision_timeout) override {
+    SAVE_OR_CALL(
+        OnLeSubrateChange, hci_status, subrate_factor, peripheral_latency, continuation_number, supervision_timeout);
+  }
 
   void OnDisconnection(ErrorCode reason) override {
     SAVE_OR_CALL(OnDisconnection, reason);
@@ -186,6 +195,23 @@ bool LeAclConnection::LeReadRemoteFeatures() {
   return true;
 }
 
+void LeAclConnection::LeSubrateRequest(
+    uint16_t subrate_min, uint16_t subrate_max, uint16_t max_latency, uint16_t cont_num, uint16_t sup_tout) {
+  pimpl_->tracker.le_acl_connection_interface_->EnqueueCommand(
+      LeSubrateRequestBuilder::Create(handle_, subrate_min, subrate_max, max_latency, cont_num, sup_tout),
+      pimpl_->tracker.client_handler_->BindOnce(
+          [](LeConnectionManagementCallbacks* callbacks, CommandStatusView status) {
+            ASSERT(status.IsValid());
+            ASSERT(status.GetCommandOpCode() == OpCode::LE_SUBRATE_REQUEST);
+            // A rejected request produces no LE Subrate Change event, so report
+            // the failure through the same callback.
+            if (status.GetStatus() != ErrorCode::SUCCESS) {
+              callbacks->OnLeSubrateChange(status.GetStatus(), 0, 0, 0, 0);
+            }
+          },
+          common::Unretained(pimpl_->tracker.client_callbacks_)));
+}
+
 bool LeAclConnection::check_connection_parameters(
     uint16_t conn_interval_min, uint16_t conn_interval_max, uint16_t conn_latency, uint16_t supervision_timeout) {
   if (conn_interval_min < 0x0006 || conn_interval_min > 0x0C80 || conn_interval_max < 0x0006 ||
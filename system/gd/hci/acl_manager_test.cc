@@ -674,6 +674,14 @@ class AclManagerWithLeConnectionTest : public AclManagerTest {
     MOCK_METHOD2(OnLeReadRemoteFeaturesComplete, void(hci::ErrorCode hci_status, uint64_t features));
     MOCK_METHOD3(OnPhyUpdate, void(hci::ErrorCode hci_status, uint8_t tx_phy, uint8_t rx_phy));
     MOCK_METHOD1(OnLocalAddressUpdate, void(AddressWithType address_with_type));
+    MOCK_METHOD5(
+        OnLeSubrateChange,
+        void(
+            hci::ErrorCode hci_status,
+            uint16_t subrate_factor,
+            uint16_t peripheral_latency,
+            uint16_t continuation_number,
+            uint16_t supervision_timeout));
   } mock_le_connection_management_callbacks_;
 };
 
@@ -113,6 +113,8 @@ class Controller : public Module {
   virtual bool SupportsBlePowerControlRequest() const;
   virtual bool SupportsBlePowerChangeIndication() const;
   virtual bool SupportsBlePathLossMonitoring() const;
+  virtual bool SupportsBleConnectionSubrating() const;
+  virtual bool SupportsBleConnectionSubratingHost() const;
 
   virtual uint16_t GetAclPacketLength() const;
 
@@ -184,6 +186,8 @@ class Controller : public Module {
 
   static constexpr uint64_t kDefaultEventMask = 0x3dbfffffffffffff;
   static constexpr uint64_t kDefaultLeEventMask = 0x000000004d02fe7f;
+  // LE Subrate Change event, only set when the controller supports subrating.
+  static constexpr uint64_t kLeSubrateChangeEventMask = 0x0000000200000000;
 
  protected:
   void ListDependencies(ModuleList* list) const override;
@@ -29,6 +29,7 @@ constexpr SubeventCode LeConnectionManagementEvents[] = {
     SubeventCode::PHY_UPDATE_COMPLETE,
     SubeventCode::DATA_LENGTH_CHANGE,
     SubeventCode::REMOTE_CONNECTION_PARAMETER_REQUEST,
+    SubeventCode::LE_SUBRATE_CHANGE,
     // TODO implement callback for READ_REMOTE_FEATURES_COMPLETE
     // SubeventCode::READ_REMOTE_FEATURES_COMPLETE
 };
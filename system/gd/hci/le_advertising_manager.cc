@@ -239,6 +239,8 @@ struct LeAdvertisingManager::impl : public bluetooth::hci::LeAddressManagerCallb
         }
       } else {
         advertising_callbacks_->OnAdvertisingEnabled(advertiser_id, false, (uint8_t)status);
+        advertising_callbacks_->OnAdvertisingSetTerminated(
+            advertiser_id, (uint8_t)status, event_view.GetNumCompletedExtendedAdvertisingEvents());
       }
       return;
     }
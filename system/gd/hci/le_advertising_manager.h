@@ -92,6 +92,10 @@ class AdvertisingCallback {
   virtual void OnPeriodicAdvertisingDataSet(uint8_t advertiser_id, uint8_t status) = 0;
   virtual void OnPeriodicAdvertisingEnabled(uint8_t advertiser_id, bool enable, uint8_t status) = 0;
   virtual void OnOwnAddressRead(uint8_t advertiser_id, uint8_t address_type, Address address) = 0;
+  // Reported along with OnAdvertisingEnabled(advertiser_id, false, status) when the controller stops a set that
+  // reached its duration or maximum number of extended advertising events.
+  virtual void OnAdvertisingSetTerminated(
+      uint8_t advertiser_id, uint8_t status, uint8_t num_completed_extended_adv_events) {}
 };
 
 class LeAdvertisingManager : public bluetooth::Module {
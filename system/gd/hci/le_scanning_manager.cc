@@ -541,8 +541,11 @@ struct LeScanningManager::impl : public LeAddressManagerCallback {
     phy_scan_parameters.le_scan_window_ = window_ms_;
     phy_scan_parameters.le_scan_interval_ = interval_ms_;
     phy_scan_parameters.le_scan_type_ = le_scan_type_;
-    parameter_vector.push_back(phy_scan_parameters);
-    uint8_t phys_in_use = 1;
+    // One set of parameters per PHY in use, in bit order.
+    uint8_t phys_in_use = scan_phys_;
+    for (uint8_t phy : {kScanPhyLe1M, kScanPhyLeCoded}) {
+      if (phys_in_use & phy) parameter_vector.push_back(phy_scan_parameters);
+    }
 
     // The Host shall not issue set scan parameter command when scanning is enabled
     stop_scan();
@@ -677,7 +680,8 @@ struct LeScanningManager::impl : public LeAddressManagerCallback {
     }
   }
 
-  void set_scan_parameters(ScannerId scanner_id, LeScanType scan_type, uint16_t scan_interval, uint16_t scan_window) {
+  void set_scan_parameters(
+      ScannerId scanner_id, LeScanType scan_type, uint16_t scan_interval, uint16_t scan_window, uint8_t scan_phy) {
     uint32_t max_scan_interval = kLeScanIntervalMax;
     uint32_t max_scan_window = kLeScanWindowMax;
     if (api_type_ == ScanApiType::EXTENDED) {
@@ -703,9 +707,21 @@ struct LeScanningManager::impl : public LeAddressManagerCallback {
           scanner_id, ScanningCallback::ScanningStatus::ILLEGAL_PARAMETER);
       return;
     }
+    // Only the extended scanning commands can scan on the Coded PHY.
+    uint8_t supported_phys = kScanPhyLe1M;
+    if (api_type_ == ScanApiType::EXTENDED && controller_->SupportsBleCodedPhy()) {
+      supported_phys |= kScanPhyLeCoded;
+    }
+    if (scan_phy == 0 || (scan_phy & ~supported_phys) != 0) {
+      LOG_ERROR("Invalid scan_phy %d", scan_phy);
+      scanning_callbacks_->OnSetScannerParameterComplete(
+          scanner_id, ScanningCallback::ScanningStatus::ILLEGAL_PARAMETER);
+      return;
+    }
     le_scan_type_ = scan_type;
     interval_ms_ = scan_interval;
     window_ms_ = scan_window;
+    scan_phys_ = scan_phy;
     scanning_callbacks_->OnSetScannerParameterComplete(scanner_id, ScanningCallback::SUCCESS);
   }
 
@@ -1606,6 +1622,7 @@ struct LeScanningManager::impl : public LeAddressManagerCallback {
   LeScanType le_scan_type_ = LeScanType::ACTIVE;
   uint32_t interval_ms_{1000};
   uint16_t window_ms_{1000};
+  uint8_t scan_phys_{kScanPhyLe1M};
   OwnAddressType own_address_type_{OwnAddressType::PUBLIC_DEVICE_ADDRESS};
   LeScanningFilterPolicy filter_policy_{LeScanningFilterPolicy::ACCEPT_ALL};
   BatchScanConfig batch_scan_config_;
@@ -1681,8 +1698,8 @@ void LeScanningManager::Scan(bool start) {
 }
 
 void LeScanningManager::SetScanParameters(
-    ScannerId scanner_id, LeScanType scan_type, uint16_t scan_interval, uint16_t scan_window) {
-  CallOn(pimpl_.get(), &impl::set_scan_parameters, scanner_id, scan_type, scan_interval, scan_window);
+    ScannerId scanner_id, LeScanType scan_type, uint16_t scan_interval, uint16_t scan_window, uint8_t scan_phy) {
+  CallOn(pimpl_.get(), &impl::set_scan_parameters, scanner_id, scan_type, scan_interval, scan_window, scan_phy);
 }
 
 void LeScanningManager::ScanFilterEnable(bool enable) {
@@ -41,6 +41,8 @@ class LeScanningManager : public bluetooth::Module {
   static constexpr uint8_t kTxPowerInformationNotPresent = 0x7f;
   static constexpr uint8_t kNotPeriodicAdvertisement = 0x00;
   static constexpr ScannerId kInvalidScannerId = 0xFF;
+  static constexpr uint8_t kScanPhyLe1M = 0x01;
+  static constexpr uint8_t kScanPhyLeCoded = 0x04;
   LeScanningManager();
   LeScanningManager(const LeScanningManager&) = delete;
   LeScanningManager& operator=(const LeScanningManager&) = delete;
@@ -51,8 +53,10 @@ class LeScanningManager : public bluetooth::Module {
 
   virtual void Scan(bool start);
 
+  // |scan_phy| is a mask of the PHYs to scan on, as in the scanning_phys of
+  // LE Set Extended Scan Parameters.
   virtual void SetScanParameters(
-      ScannerId scanner_id, LeScanType scan_type, uint16_t scan_interval, uint16_t scan_window);
+      ScannerId scanner_id, LeScanType scan_type, uint16_t scan_interval, uint16_t scan_window, uint8_t scan_phy);
 
   /* Scan filter */
   virtual void ScanFilterEnable(bool enable);
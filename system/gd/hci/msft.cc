@@ -0,0 +1,247 @@
+/*
+ * Copyright 2022 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+#include "hci/msft.h"
+
+#include <algorithm>
+#include <future>
+
+#include "hal/hci_hal.h"
+#include "hci/hci_layer.h"
+#include "hci/vendor_specific_event_manager.h"
+#include "os/log.h"
+
+namespace bluetooth {
+namespace hci {
+
+// Supported feature telling that the controller can monitor LE advertisements.
+constexpr uint64_t kMsftFeatureLeAdvMonitor = 1 << 3;
+
+const ModuleFactory MsftExtensionManager::Factory = ModuleFactory([]() { return new MsftExtensionManager(); });
+
+struct MsftExtensionManager::impl {
+  impl(Module* module) : module_(module){};
+
+  ~impl() {}
+
+  void start(
+      os::Handler* handler,
+      hal::HciHal* hal,
+      hci::HciLayer* hci_layer,
+      hci::VendorSpecificEventManager* vendor_specific_event_manager) {
+    module_handler_ = handler;
+    hci_layer_ = hci_layer;
+    vendor_specific_event_manager_ = vendor_specific_event_manager;
+
+    opcode_ = static_cast<OpCode>(hal->getMsftOpcode());
+    if (opcode_ == OpCode::NONE) {
+      LOG_INFO("MSFT extension is not supported");
+      return;
+    }
+
+    std::promise<void> promise;
+    auto future = promise.get_future();
+    hci_layer_->EnqueueCommand(
+        MsftReadSupportedFeaturesBuilder::Create(opcode_),
+        handler->BindOnceOn(this, &impl::on_read_supported_features, std::move(promise)));
+    future.wait();
+
+    if (supports_adv_monitor()) {
+      vendor_specific_event_manager_->RegisterDefaultEventHandler(
+          handler->BindOn(this, &impl::on_vendor_specific_event));
+      event_handler_registered_ = true;
+    }
+  }
+
+  void stop() {
+    if (event_handler_registered_) {
+      vendor_specific_event_manager_->UnregisterDefaultEventHandler();
+    }
+  }
+
+  bool supports_adv_monitor() {
+    return (supported_features_ & kMsftFeatureLeAdvMonitor) != 0;
+  }
+
+  void adv_monitor_add(MsftAdvMonitor monitor, MsftAdvMonitorAddCallback cb) {
+    if (!supports_adv_monitor()) {
+      cb.Invoke(0, ErrorCode::UNKNOWN_HCI_COMMAND);
+      return;
+    }
+
+    std::vector<MsftLeMonitorAdvConditionPattern> patterns;
+    for (const auto& monitor_pattern : monitor.patterns) {
+      MsftLeMonitorAdvConditionPattern pattern;
+      pattern.ad_type_ = monitor_pattern.ad_type;
+      pattern.start_byte_ = monitor_pattern.start_byte;
+      pattern.pattern_ = monitor_pattern.pattern;
+      patterns.push_back(pattern);
+    }
+
+    hci_layer_->EnqueueCommand(
+        MsftLeMonitorAdvPatternsBuilder::Create(
+            opcode_,
+            static_cast<uint8_t>(monitor.rssi_threshold_high),
+            static_cast<uint8_t>(monitor.rssi_threshold_low),
+            monitor.rssi_threshold_low_time_interval,
+            monitor.rssi_sampling_period,
+            patterns),
+        module_handler_->BindOnceOn(this, &impl::on_adv_monitor_add_complete, std::move(cb)));
+  }
+
+  void adv_monitor_remove(uint8_t monitor_handle, MsftAdvMonitorRemoveCallback cb) {
+    if (!supports_adv_monitor()) {
+      cb.Invoke(ErrorCode::UNKNOWN_HCI_COMMAND);
+      return;
+    }
+
+    hci_layer_->EnqueueCommand(
+        MsftLeCancelMonitorAdvBuilder::Create(opcode_, monitor_handle),
+        module_handler_->BindOnceOn(this, &impl::on_status_complete, std::move(cb)));
+  }
+
+  void adv_monitor_enable(bool enable, MsftAdvMonitorEnableCallback cb) {
+    if (!supports_adv_monitor()) {
+      cb.Invoke(ErrorCode::UNKNOWN_HCI_COMMAND);
+      return;
+    }
+
+    hci_layer_->EnqueueCommand(
+        MsftLeSetAdvFilterEnableBuilder::Create(opcode_, enable ? Enable::ENABLED : Enable::DISABLED),
+        module_handler_->BindOnceOn(this, &impl::on_status_complete, std::move(cb)));
+  }
+
+  void register_device_callback(MsftAdvMonitorDeviceCallback cb) {
+    device_callback_ = cb;
+  }
+
+  // A failed command may complete with nothing but its status.
+  static ErrorCode status_of(CommandCompleteView view) {
+    auto payload = view.GetPayload();
+    if (payload.size() < 1) {
+      return ErrorCode::UNSPECIFIED_ERROR;
+    }
+    return static_cast<ErrorCode>(payload[0]);
+  }
+
+  void on_read_supported_features(std::promise<void> promise, CommandCompleteView view) {
+    auto complete_view = MsftReadSupportedFeaturesCompleteView::Create(MsftCommandCompleteView::Create(view));
+    if (!complete_view.IsValid() || complete_view.GetStatus() != ErrorCode::SUCCESS) {
+      LOG_WARN("Failed to read MSFT supported features: %s", ErrorCodeText(status_of(view)).c_str());
+    } else {
+      supported_features_ = complete_view.GetSupportedFeatures();
+      event_prefix_ = complete_view.GetMsftEventPrefix();
+      LOG_INFO("MSFT supported features 0x%016llx", static_cast<unsigned long long>(supported_features_));
+    }
+    promise.set_value();
+  }
+
+  void on_adv_monitor_add_complete(MsftAdvMonitorAddCallback cb, CommandCompleteView view) {
+    auto complete_view = MsftLeMonitorAdvCompleteView::Create(MsftCommandCompleteView::Create(view));
+    if (!complete_view.IsValid()) {
+      cb.Invoke(0, status_of(view) == ErrorCode::SUCCESS ? ErrorCode::UNSPECIFIED_ERROR : status_of(view));
+      return;
+    }
+    cb.Invoke(complete_view.GetMonitorHandle(), complete_view.GetStatus());
+  }
+
+  void on_status_complete(common::ContextualOnceCallback<void(ErrorCode)> cb, CommandCompleteView view) {
+    cb.Invoke(status_of(view));
+  }
+
+  void on_vendor_specific_event(VendorSpecificEventView view) {
+    // The subevent code is the first byte of the MSFT event prefix.
+    std::vector<uint8_t> params = {static_cast<uint8_t>(view.GetSubeventCode())};
+    auto payload = view.GetPayload();
+    params.insert(params.end(), payload.begin(), payload.end());
+    if (params.size() <= event_prefix_.size() ||
+        !std::equal(event_prefix_.begin(), event_prefix_.end(), params.begin())) {
+      LOG_WARN("Unhandled vendor specific event of type 0x%02hhx", view.GetSubeventCode());
+      return;
+    }
+
+    auto event = MsftEventView::Create(PacketView<kLittleEndian>(
+        std::make_shared<std::vector<uint8_t>>(params.begin() + event_prefix_.size(), params.end())));
+    auto monitor_view = MsftLeMonitorDeviceEventView::Create(event);
+    if (!monitor_view.IsValid()) {
+      LOG_WARN("Unhandled MSFT event");
+      return;
+    }
+
+    AddressWithType address(monitor_view.GetBdAddr(), static_cast<AddressType>(monitor_view.GetAddressType()));
+    device_callback_.InvokeIfNotEmpty(
+        monitor_view.GetMonitorHandle(), address, monitor_view.GetMonitorState() == MsftMonitorState::STARTED);
+  }
+
+  Module* module_;
+  os::Handler* module_handler_;
+  hci::HciLayer* hci_layer_;
+  hci::VendorSpecificEventManager* vendor_specific_event_manager_;
+  OpCode opcode_{OpCode::NONE};
+  uint64_t supported_features_{0};
+  std::vector<uint8_t> event_prefix_;
+  bool event_handler_registered_{false};
+  MsftAdvMonitorDeviceCallback device_callback_;
+};
+
+MsftExtensionManager::MsftExtensionManager() {
+  pimpl_ = std::make_unique<impl>(this);
+}
+
+void MsftExtensionManager::ListDependencies(ModuleList* list) const {
+  list->add<hal::HciHal>();
+  list->add<hci::HciLayer>();
+  list->add<hci::VendorSpecificEventManager>();
+}
+
+void MsftExtensionManager::Start() {
+  pimpl_->start(
+      GetHandler(),
+      GetDependency<hal::HciHal>(),
+      GetDependency<hci::HciLayer>(),
+      GetDependency<hci::VendorSpecificEventManager>());
+}
+
+void MsftExtensionManager::Stop() {
+  pimpl_->stop();
+  pimpl_.reset();
+}
+
+std::string MsftExtensionManager::ToString() const {
+  return "Microsoft Extension Manager";
+}
+
+bool MsftExtensionManager::SupportsMsftExtensions() {
+  return pimpl_->supports_adv_monitor();
+}
+
+void MsftExtensionManager::MsftAdvMonitorAdd(MsftAdvMonitor monitor, MsftAdvMonitorAddCallback cb) {
+  CallOn(pimpl_.get(), &impl::adv_monitor_add, monitor, std::move(cb));
+}
+
+void MsftExtensionManager::MsftAdvMonitorRemove(uint8_t monitor_handle, MsftAdvMonitorRemoveCallback cb) {
+  CallOn(pimpl_.get(), &impl::adv_monitor_remove, monitor_handle, std::move(cb));
+}
+
+void MsftExtensionManager::MsftAdvMonitorEnable(bool enable, MsftAdvMonitorEnableCallback cb) {
+  CallOn(pimpl_.get(), &impl::adv_monitor_enable, enable, std::move(cb));
+}
+
+void MsftExtensionManager::RegisterDeviceCallback(MsftAdvMonitorDeviceCallback cb) {
+  CallOn(pimpl_.get(), &impl::register_device_callback, cb);
+}
+
+}  // namespace hci
+}  // namespace bluetooth
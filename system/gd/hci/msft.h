@@ -0,0 +1,95 @@
+/*
+ * Copyright 2022 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+#pragma once
+
+#include <memory>
+#include <vector>
+
+#include "common/contextual_callback.h"
+#include "hci/address_with_type.h"
+#include "hci/hci_packets.h"
+#include "module.h"
+
+namespace bluetooth {
+namespace hci {
+
+// An advertising data pattern matched by an advertisement monitor.
+struct MsftAdvMonitorPattern {
+  uint8_t ad_type;
+  // Offset of |pattern| within the data of the AD structure.
+  uint8_t start_byte;
+  std::vector<uint8_t> pattern;
+};
+
+// A device is reported found once its RSSI reaches |rssi_threshold_high| and
+// lost once it stays below |rssi_threshold_low| for
+// |rssi_threshold_low_time_interval| seconds.
+struct MsftAdvMonitor {
+  int8_t rssi_threshold_high;
+  int8_t rssi_threshold_low;
+  uint8_t rssi_threshold_low_time_interval;
+  // In units of 100ms.
+  uint8_t rssi_sampling_period;
+  std::vector<MsftAdvMonitorPattern> patterns;
+};
+
+// Controller-offloaded advertisement monitoring through the
+// Microsoft-defined Bluetooth HCI extension.
+class MsftExtensionManager : public bluetooth::Module {
+ public:
+  using MsftAdvMonitorAddCallback = common::ContextualOnceCallback<void(uint8_t /* monitor_handle */, ErrorCode)>;
+  using MsftAdvMonitorRemoveCallback = common::ContextualOnceCallback<void(ErrorCode)>;
+  using MsftAdvMonitorEnableCallback = common::ContextualOnceCallback<void(ErrorCode)>;
+  using MsftAdvMonitorDeviceCallback =
+      common::ContextualCallback<void(uint8_t /* monitor_handle */, AddressWithType, bool /* found */)>;
+
+  MsftExtensionManager();
+  MsftExtensionManager(const MsftExtensionManager&) = delete;
+  MsftExtensionManager& operator=(const MsftExtensionManager&) = delete;
+
+  // Whether the controller can monitor advertisements through the extension.
+  // Read from the controller when the module starts.
+  bool SupportsMsftExtensions();
+
+  // Adds |monitor| and returns the handle allocated by the controller to |cb|.
+  void MsftAdvMonitorAdd(MsftAdvMonitor monitor, MsftAdvMonitorAddCallback cb);
+
+  void MsftAdvMonitorRemove(uint8_t monitor_handle, MsftAdvMonitorRemoveCallback cb);
+
+  // Enables or disables all the monitors at once.
+  void MsftAdvMonitorEnable(bool enable, MsftAdvMonitorEnableCallback cb);
+
+  // Devices starting or stopping to match a monitor are reported to |cb|.
+  void RegisterDeviceCallback(MsftAdvMonitorDeviceCallback cb);
+
+  static const ModuleFactory Factory;
+
+ protected:
+  void ListDependencies(ModuleList* list) const override;
+
+  void Start() override;
+
+  void Stop() override;
+
+  std::string ToString() const override;
+
+ private:
+  struct impl;
+  std::unique_ptr<impl> pimpl_;
+};
+
+}  // namespace hci
+}  // namespace bluetooth
@@ -54,6 +54,15 @@ struct VendorSpecificEventManager::impl {
     subevent_handlers_.erase(subevent_handlers_.find(event));
   }
 
+  void register_default_event(common::ContextualCallback<void(VendorSpecificEventView)> handler) {
+    ASSERT_LOG(default_handler_.IsEmpty(), "Can not register a second default handler");
+    default_handler_ = handler;
+  }
+
+  void unregister_default_event() {
+    default_handler_ = {};
+  }
+
   bool check_event_supported(VseSubeventCode event) {
     switch (event) {
       case (VseSubeventCode::BLE_THRESHOLD): {
@@ -83,6 +92,10 @@ struct VendorSpecificEventManager::impl {
     ASSERT(vendor_specific_event_view.IsValid());
     VseSubeventCode vse_subevent_code = vendor_specific_event_view.GetSubeventCode();
     if (subevent_handlers_.find(vse_subevent_code) == subevent_handlers_.end()) {
+      if (!default_handler_.IsEmpty()) {
+        default_handler_.Invoke(vendor_specific_event_view);
+        return;
+      }
       LOG_WARN("Unhandled vendor specific event of type 0x%02hhx", vse_subevent_code);
       return;
     }
@@ -95,6 +108,7 @@ struct VendorSpecificEventManager::impl {
   hci::Controller* controller_;
   VendorCapabilities vendor_capabilities_;
   std::map<VseSubeventCode, common::ContextualCallback<void(VendorSpecificEventView)>> subevent_handlers_;
+  common::ContextualCallback<void(VendorSpecificEventView)> default_handler_;
 };
 
 VendorSpecificEventManager::VendorSpecificEventManager() {
@@ -128,5 +142,14 @@ void VendorSpecificEventManager::UnregisterEventHandler(VseSubeventCode event) {
   CallOn(pimpl_.get(), &impl::unregister_event, event);
 }
 
+void VendorSpecificEventManager::RegisterDefaultEventHandler(
+    common::ContextualCallback<void(VendorSpecificEventView)> handler) {
+  CallOn(pimpl_.get(), &impl::register_default_event, handler);
+}
+
+void VendorSpecificEventManager::UnregisterDefaultEventHandler() {
+  CallOn(pimpl_.get(), &impl::unregister_default_event);
+}
+
 }  // namespace hci
 }  // namespace bluetooth
@@ -31,6 +31,13 @@ class VendorSpecificEventManager : public bluetooth::Module {
 
   void UnregisterEventHandler(VseSubeventCode event);
 
+  // Events with no handler registered for their subevent code are passed to
+  // |handler|. This is for extensions whose events start with a prefix chosen
+  // by the controller vendor, like the Microsoft one.
+  void RegisterDefaultEventHandler(common::ContextualCallback<void(VendorSpecificEventView)> handler);
+
+  void UnregisterDefaultEventHandler();
+
   static const ModuleFactory Factory;
 
  protected:
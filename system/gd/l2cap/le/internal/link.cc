@@ -106,6 +106,13 @@ void Link::OnLocalAddressUpdate(hci::AddressWithType address_with_type) {
   acl_connection_->UpdateLocalAddress(address_with_type);
 }
 
+void Link::OnLeSubrateChange(
+    hci::ErrorCode hci_status,
+    uint16_t subrate_factor,
+    uint16_t peripheral_latency,
+    uint16_t continuation_number,
+    uint16_t supervision_timeout) {}
+
 void Link::Disconnect() {
   acl_connection_->Disconnect(hci::DisconnectReason::REMOTE_USER_TERMINATED_CONNECTION);
 }
@@ -148,7 +148,9 @@ fn main() -> Result<(), Box<dyn Error>> {
         // Hold locks and initialize all interfaces. This must be done AFTER DBus is
         // initialized so DBus can properly enforce user policies.
         {
-            intf.lock().unwrap().initialize(get_bt_dispatcher(tx.clone()), args);
+            if !intf.lock().unwrap().initialize(get_bt_dispatcher(tx.clone()), args) {
+                return Err("Failed to initialize the Bluetooth interface".into());
+            }
 
             bluetooth_media.lock().unwrap().set_adapter(bluetooth.clone());
 
@@ -347,8 +347,8 @@ impl Bluetooth {
 
     pub fn init_profiles(&mut self) {
         let hhtx = self.tx.clone();
-        self.hh = Some(HidHost::new(&self.intf.lock().unwrap()));
-        self.hh.as_mut().unwrap().initialize(HHCallbacksDispatcher {
+        let mut hh = HidHost::new(&self.intf.lock().unwrap());
+        let hh_initialized = hh.initialize(HHCallbacksDispatcher {
             dispatch: Box::new(move |cb| {
                 let txl = hhtx.clone();
                 topstack::get_runtime().spawn(async move {
@@ -358,8 +358,8 @@ impl Bluetooth {
         });
 
         let sdptx = self.tx.clone();
-        self.sdp = Some(Sdp::new(&self.intf.lock().unwrap()));
-        self.sdp.as_mut().unwrap().initialize(SdpCallbacksDispatcher {
+        let mut sdp = Sdp::new(&self.intf.lock().unwrap());
+        let sdp_initialized = sdp.initialize(SdpCallbacksDispatcher {
             dispatch: Box::new(move |cb| {
                 let txl = sdptx.clone();
                 topstack::get_runtime().spawn(async move {
@@ -368,6 +368,19 @@ impl Bluetooth {
             }),
         });
 
+        self.hh = if hh_initialized {
+            Some(hh)
+        } else {
+            warn!("Failed to initialize HidHost");
+            None
+        };
+        self.sdp = if sdp_initialized {
+            Some(sdp)
+        } else {
+            warn!("Failed to initialize SDP");
+            None
+        };
+
         // Mark profiles as ready
         self.profiles_ready = true;
     }
@@ -613,7 +626,9 @@ impl BtifBluetoothCallbacks for Bluetooth {
         }
 
         if self.state == BtState::On {
-            self.bluetooth_media.lock().unwrap().initialize();
+            if !self.bluetooth_media.lock().unwrap().initialize() {
+                warn!("Some media profiles failed to initialize");
+            }
         }
 
         if self.state == BtState::Off {
@@ -1362,9 +1377,12 @@ impl IBluetooth for Bluetooth {
                 Some(p) => {
                     if self.uuid_helper.is_profile_enabled(&p) {
                         match p {
-                            Profile::Hid | Profile::Hogp => {
-                                self.hh.as_ref().unwrap().connect(&mut addr.unwrap());
-                            }
+                            Profile::Hid | Profile::Hogp => match self.hh.as_ref() {
+                                Some(hh) => {
+                                    hh.connect(&mut addr.unwrap());
+                                }
+                                None => warn!("HidHost is not initialized. Can't connect."),
+                            },
 
                             Profile::A2dpSink | Profile::A2dpSource => {
                                 let txl = self.tx.clone();
@@ -1411,9 +1429,12 @@ impl IBluetooth for Bluetooth {
                 Some(p) => {
                     if self.uuid_helper.is_profile_enabled(&p) {
                         match p {
-                            Profile::Hid | Profile::Hogp => {
-                                self.hh.as_ref().unwrap().disconnect(&mut addr.unwrap());
-                            }
+                            Profile::Hid | Profile::Hogp => match self.hh.as_ref() {
+                                Some(hh) => {
+                                    hh.disconnect(&mut addr.unwrap());
+                                }
+                                None => warn!("HidHost is not initialized. Can't disconnect."),
+                            },
 
                             Profile::A2dpSink | Profile::A2dpSource => {
                                 let txl = self.tx.clone();
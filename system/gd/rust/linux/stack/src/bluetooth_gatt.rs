@@ -3,15 +3,18 @@
 use btif_macros::{btif_callback, btif_callbacks_dispatcher};
 
 use bt_topshim::bindings::root::bluetooth::Uuid;
-use bt_topshim::btif::{BluetoothInterface, RawAddress, Uuid128Bit};
+use bt_topshim::btif::{BluetoothInterface, BtStatus, BtTransport, RawAddress, Uuid128Bit};
 use bt_topshim::profiles::gatt::{
-    BtGattDbElement, BtGattNotifyParams, BtGattReadParams, Gatt, GattClientCallbacks,
-    GattClientCallbacksDispatcher, GattScannerCallbacksDispatcher, GattServerCallbacksDispatcher,
-    GattStatus,
+    BtGattDbElement, ConnectionParameters, Gatt, GattAdvCallbacksDispatcher,
+    GattAdvInbandCallbacksDispatcher, GattAuthReq, GattClientCallbacks,
+    GattClientCallbacksDispatcher, GattNotification, GattReadResult,
+    GattScannerCallbacksDispatcher, GattScannerInbandCallbacksDispatcher,
+    GattServerCallbacksDispatcher, GattStatus, GattWriteType as BtGattWriteType, LePhy as BtLePhy,
+    PhyOptions,
 };
 use bt_topshim::topstack;
 
-use log::{debug, warn};
+use log::{debug, error, warn};
 use num_traits::cast::{FromPrimitive, ToPrimitive};
 use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
@@ -443,6 +446,15 @@ pub enum LePhy {
     PhyCoded = 3,
 }
 
+impl From<Result<BtLePhy, u8>> for LePhy {
+    fn from(phy: Result<BtLePhy, u8>) -> Self {
+        match phy {
+            Ok(phy) => LePhy::from_u8(phy as u8).unwrap_or(LePhy::Invalid),
+            Err(_) => LePhy::Invalid,
+        }
+    }
+}
+
 #[derive(Debug, FromPrimitive, ToPrimitive)]
 #[repr(u32)]
 /// Scan type configuration.
@@ -500,7 +512,7 @@ impl BluetoothGatt {
 
     pub fn init_profiles(&mut self, tx: Sender<Message>) {
         self.gatt = Gatt::new(&self.intf.lock().unwrap());
-        self.gatt.as_mut().unwrap().initialize(
+        let result = self.gatt.as_mut().unwrap().initialize(
             GattClientCallbacksDispatcher {
                 dispatch: Box::new(move |cb| {
                     let tx_clone = tx.clone();
@@ -520,7 +532,25 @@ impl BluetoothGatt {
                     debug!("received Gatt scanner callback: {:?}", cb);
                 }),
             },
+            GattScannerInbandCallbacksDispatcher {
+                dispatch: Box::new(move |cb| {
+                    debug!("received Gatt scanner inband callback: {:?}", cb);
+                }),
+            },
+            GattAdvInbandCallbacksDispatcher {
+                dispatch: Box::new(move |cb| {
+                    debug!("received Gatt advertiser inband callback: {:?}", cb);
+                }),
+            },
+            GattAdvCallbacksDispatcher {
+                dispatch: Box::new(move |cb| {
+                    debug!("received Gatt advertising callback: {:?}", cb);
+                }),
+            },
         );
+        if let Err(e) = result {
+            error!("Failed to initialize GATT: {:?}", e);
+        }
     }
 }
 
@@ -602,6 +632,11 @@ impl IBluetoothGatt for BluetoothGatt {
             Some(addr) => addr,
         };
 
+        let transport = match BtTransport::from_i32(transport) {
+            None => return,
+            Some(transport) => transport,
+        };
+
         self.gatt.as_ref().unwrap().client.connect(
             client_id,
             &address,
@@ -638,11 +673,24 @@ impl IBluetoothGatt for BluetoothGatt {
             return;
         }
 
+        let (tx_phy, rx_phy) = match (
+            BtLePhy::from_u8(tx_phy.to_u8().unwrap()),
+            BtLePhy::from_u8(rx_phy.to_u8().unwrap()),
+        ) {
+            (Some(tx_phy), Some(rx_phy)) => (tx_phy, rx_phy),
+            _ => return,
+        };
+
+        let phy_options = match PhyOptions::from_bits(phy_options as u16) {
+            None => return,
+            Some(phy_options) => phy_options,
+        };
+
         self.gatt.as_ref().unwrap().client.set_preferred_phy(
             &RawAddress::from_string(address).unwrap(),
-            tx_phy.to_u8().unwrap(),
-            rx_phy.to_u8().unwrap(),
-            phy_options as u16,
+            tx_phy,
+            rx_phy,
+            phy_options,
         );
     }
 
@@ -698,6 +746,7 @@ impl IBluetoothGatt for BluetoothGatt {
             conn_id.unwrap(),
             handle as u16,
             auth_req,
+            None,
         );
     }
 
@@ -749,18 +798,33 @@ impl IBluetoothGatt for BluetoothGatt {
             write_type = GattWriteType::WritePrepare;
         }
 
+        let write_type = match BtGattWriteType::from_i32(write_type.to_i32().unwrap()) {
+            None => return GattWriteRequestStatus::Fail,
+            Some(write_type) => write_type,
+        };
+
+        let auth_req = match GattAuthReq::from_i32(auth_req) {
+            None => return GattWriteRequestStatus::Fail,
+            Some(auth_req) => auth_req,
+        };
+
         // TODO(b/200065274): Perform check on restricted handles.
 
         // TODO(b/200070162): Handle concurrent write characteristic.
 
-        self.gatt.as_ref().unwrap().client.write_characteristic(
+        let status = self.gatt.as_ref().unwrap().client.write_characteristic(
             conn_id.unwrap(),
             handle as u16,
-            write_type.to_i32().unwrap(),
+            write_type,
             auth_req,
             &value,
+            None,
         );
 
+        if status != BtStatus::Success {
+            return GattWriteRequestStatus::Fail;
+        }
+
         return GattWriteRequestStatus::Success;
     }
 
@@ -792,6 +856,11 @@ impl IBluetoothGatt for BluetoothGatt {
             return;
         }
 
+        let auth_req = match GattAuthReq::from_i32(auth_req) {
+            None => return,
+            Some(auth_req) => auth_req,
+        };
+
         // TODO(b/200065274): Perform check on restricted handles.
 
         self.gatt.as_ref().unwrap().client.write_descriptor(
@@ -908,23 +977,16 @@ pub(crate) trait BtifGattClientCallbacks {
     );
 
     #[btif_callback(Notify)]
-    fn notify_cb(&mut self, conn_id: i32, data: BtGattNotifyParams);
+    fn notify_cb(&mut self, conn_id: i32, data: GattNotification);
 
     #[btif_callback(ReadCharacteristic)]
-    fn read_characteristic_cb(&mut self, conn_id: i32, status: i32, data: BtGattReadParams);
+    fn read_characteristic_cb(&mut self, conn_id: i32, status: i32, data: GattReadResult);
 
     #[btif_callback(WriteCharacteristic)]
-    fn write_characteristic_cb(
-        &mut self,
-        conn_id: i32,
-        status: i32,
-        handle: u16,
-        len: u16,
-        value: *const u8,
-    );
+    fn write_characteristic_cb(&mut self, conn_id: i32, status: i32, handle: u16, value: Vec<u8>);
 
     #[btif_callback(ReadDescriptor)]
-    fn read_descriptor_cb(&mut self, conn_id: i32, status: i32, data: BtGattReadParams);
+    fn read_descriptor_cb(&mut self, conn_id: i32, status: i32, data: GattReadResult);
 
     #[btif_callback(WriteDescriptor)]
     fn write_descriptor_cb(
@@ -940,7 +1002,7 @@ pub(crate) trait BtifGattClientCallbacks {
     fn execute_write_cb(&mut self, conn_id: i32, status: i32);
 
     #[btif_callback(ReadRemoteRssi)]
-    fn read_remote_rssi_cb(&mut self, client_id: i32, addr: RawAddress, rssi: i32, status: i32);
+    fn read_remote_rssi_cb(&mut self, client_id: i32, addr: RawAddress, rssi: i8, status: i32);
 
     #[btif_callback(ConfigureMtu)]
     fn configure_mtu_cb(&mut self, conn_id: i32, status: i32, mtu: i32);
@@ -952,23 +1014,29 @@ pub(crate) trait BtifGattClientCallbacks {
     fn get_gatt_db_cb(&mut self, conn_id: i32, elements: Vec<BtGattDbElement>, count: i32);
 
     #[btif_callback(PhyUpdated)]
-    fn phy_updated_cb(&mut self, conn_id: i32, tx_phy: u8, rx_phy: u8, status: u8);
-
-    #[btif_callback(ConnUpdated)]
-    fn conn_updated_cb(
+    fn phy_updated_cb(
         &mut self,
         conn_id: i32,
-        interval: u16,
-        latency: u16,
-        timeout: u16,
+        tx_phy: Result<BtLePhy, u8>,
+        rx_phy: Result<BtLePhy, u8>,
         status: u8,
     );
 
+    #[btif_callback(ConnUpdated)]
+    fn conn_updated_cb(&mut self, conn_id: i32, params: ConnectionParameters, status: GattStatus);
+
     #[btif_callback(ServiceChanged)]
     fn service_changed_cb(&self, conn_id: i32);
 
     #[btif_callback(ReadPhy)]
-    fn read_phy_cb(&mut self, client_id: i32, addr: RawAddress, tx_phy: u8, rx_phy: u8, status: u8);
+    fn read_phy_cb(
+        &mut self,
+        client_id: i32,
+        addr: RawAddress,
+        tx_phy: Result<BtLePhy, u8>,
+        rx_phy: Result<BtLePhy, u8>,
+        status: u8,
+    );
 }
 
 impl BtifGattClientCallbacks for BluetoothGatt {
@@ -1039,20 +1107,16 @@ impl BtifGattClientCallbacks for BluetoothGatt {
         // No-op.
     }
 
-    fn notify_cb(&mut self, conn_id: i32, data: BtGattNotifyParams) {
+    fn notify_cb(&mut self, conn_id: i32, data: GattNotification) {
         let client = self.context_map.get_client_by_conn_id(conn_id);
         if client.is_none() {
             return;
         }
 
-        client.unwrap().callback.on_notify(
-            RawAddress { val: data.bda.address }.to_string(),
-            data.handle as i32,
-            data.value[0..data.len as usize].to_vec(),
-        );
+        client.unwrap().callback.on_notify(data.addr.to_string(), data.handle as i32, data.value);
     }
 
-    fn read_characteristic_cb(&mut self, conn_id: i32, status: i32, data: BtGattReadParams) {
+    fn read_characteristic_cb(&mut self, conn_id: i32, status: i32, data: GattReadResult) {
         let address = self.context_map.get_address_by_conn_id(conn_id);
         if address.is_none() {
             return;
@@ -1067,7 +1131,7 @@ impl BtifGattClientCallbacks for BluetoothGatt {
             address.unwrap().to_string(),
             status,
             data.handle as i32,
-            data.value.value[0..data.value.len as usize].to_vec(),
+            data.value,
         );
     }
 
@@ -1076,8 +1140,7 @@ impl BtifGattClientCallbacks for BluetoothGatt {
         conn_id: i32,
         mut status: i32,
         handle: u16,
-        _len: u16,
-        _value: *const u8,
+        _value: Vec<u8>,
     ) {
         let address = self.context_map.get_address_by_conn_id(conn_id);
         if address.is_none() {
@@ -1110,7 +1173,7 @@ impl BtifGattClientCallbacks for BluetoothGatt {
         );
     }
 
-    fn read_descriptor_cb(&mut self, conn_id: i32, status: i32, data: BtGattReadParams) {
+    fn read_descriptor_cb(&mut self, conn_id: i32, status: i32, data: GattReadResult) {
         let address = self.context_map.get_address_by_conn_id(conn_id);
         if address.is_none() {
             return;
@@ -1125,7 +1188,7 @@ impl BtifGattClientCallbacks for BluetoothGatt {
             address.unwrap().to_string(),
             status,
             data.handle as i32,
-            data.value.value[0..data.value.len as usize].to_vec(),
+            data.value,
         );
     }
 
@@ -1168,13 +1231,13 @@ impl BtifGattClientCallbacks for BluetoothGatt {
         client.unwrap().callback.on_execute_write(address.unwrap().to_string(), status);
     }
 
-    fn read_remote_rssi_cb(&mut self, client_id: i32, addr: RawAddress, rssi: i32, status: i32) {
+    fn read_remote_rssi_cb(&mut self, client_id: i32, addr: RawAddress, rssi: i8, status: i32) {
         let client = self.context_map.get_by_client_id(client_id);
         if client.is_none() {
             return;
         }
 
-        client.unwrap().callback.on_read_remote_rssi(addr.to_string(), rssi, status);
+        client.unwrap().callback.on_read_remote_rssi(addr.to_string(), rssi.into(), status);
     }
 
     fn configure_mtu_cb(&mut self, conn_id: i32, status: i32, mtu: i32) {
@@ -1286,7 +1349,13 @@ impl BtifGattClientCallbacks for BluetoothGatt {
         client.unwrap().callback.on_search_complete(address.unwrap().to_string(), db_out, 0);
     }
 
-    fn phy_updated_cb(&mut self, conn_id: i32, tx_phy: u8, rx_phy: u8, status: u8) {
+    fn phy_updated_cb(
+        &mut self,
+        conn_id: i32,
+        tx_phy: Result<BtLePhy, u8>,
+        rx_phy: Result<BtLePhy, u8>,
+        status: u8,
+    ) {
         let client = self.context_map.get_client_by_conn_id(conn_id);
         if client.is_none() {
             return;
@@ -1299,9 +1368,9 @@ impl BtifGattClientCallbacks for BluetoothGatt {
 
         client.unwrap().callback.on_phy_update(
             address.unwrap(),
-            LePhy::from_u8(tx_phy).unwrap(),
-            LePhy::from_u8(rx_phy).unwrap(),
-            GattStatus::from_u8(status).unwrap(),
+            LePhy::from(tx_phy),
+            LePhy::from(rx_phy),
+            GattStatus::from(status),
         );
     }
 
@@ -1309,8 +1378,8 @@ impl BtifGattClientCallbacks for BluetoothGatt {
         &mut self,
         client_id: i32,
         addr: RawAddress,
-        tx_phy: u8,
-        rx_phy: u8,
+        tx_phy: Result<BtLePhy, u8>,
+        rx_phy: Result<BtLePhy, u8>,
         status: u8,
     ) {
         let client = self.context_map.get_by_client_id(client_id);
@@ -1320,20 +1389,13 @@ impl BtifGattClientCallbacks for BluetoothGatt {
 
         client.unwrap().callback.on_phy_read(
             addr.to_string(),
-            LePhy::from_u8(tx_phy).unwrap(),
-            LePhy::from_u8(rx_phy).unwrap(),
-            GattStatus::from_u8(status).unwrap(),
+            LePhy::from(tx_phy),
+            LePhy::from(rx_phy),
+            GattStatus::from(status),
         );
     }
 
-    fn conn_updated_cb(
-        &mut self,
-        conn_id: i32,
-        interval: u16,
-        latency: u16,
-        timeout: u16,
-        status: u8,
-    ) {
+    fn conn_updated_cb(&mut self, conn_id: i32, params: ConnectionParameters, status: GattStatus) {
         let client = self.context_map.get_client_by_conn_id(conn_id);
         if client.is_none() {
             return;
@@ -1344,11 +1406,12 @@ impl BtifGattClientCallbacks for BluetoothGatt {
             return;
         }
 
+        // The callback reports the parameters in controller units.
         client.unwrap().callback.on_connection_updated(
             address.unwrap(),
-            interval as i32,
-            latency as i32,
-            timeout as i32,
+            (params.interval_ms / 1.25).round() as i32,
+            params.latency as i32,
+            (params.supervision_timeout_ms / 10) as i32,
             status as i32,
         );
     }
@@ -103,7 +103,6 @@ pub enum MediaActions {
 
 pub struct BluetoothMedia {
     intf: Arc<Mutex<BluetoothInterface>>,
-    initialized: bool,
     callbacks: Arc<Mutex<Vec<(u32, Box<dyn IBluetoothMediaCallback + Send>)>>>,
     callback_last_id: u32,
     tx: Sender<Message>,
@@ -123,7 +122,6 @@ impl BluetoothMedia {
     pub fn new(tx: Sender<Message>, intf: Arc<Mutex<BluetoothInterface>>) -> BluetoothMedia {
         BluetoothMedia {
             intf,
-            initialized: false,
             callbacks: Arc::new(Mutex::new(vec![])),
             callback_last_id: 0,
             tx,
@@ -455,27 +453,40 @@ impl IBluetoothMedia for BluetoothMedia {
     }
 
     fn initialize(&mut self) -> bool {
-        if self.initialized {
-            return false;
-        }
-        self.initialized = true;
+        // Profiles initialized on an earlier enable are kept, since their
+        // dispatchers can only be registered once.
 
         // TEST A2dp
-        let a2dp_dispatcher = get_a2dp_dispatcher(self.tx.clone());
-        self.a2dp = Some(A2dp::new(&self.intf.lock().unwrap()));
-        self.a2dp.as_mut().unwrap().initialize(a2dp_dispatcher);
+        if self.a2dp.is_none() {
+            let mut a2dp = A2dp::new(&self.intf.lock().unwrap());
+            if a2dp.initialize(get_a2dp_dispatcher(self.tx.clone())) {
+                self.a2dp = Some(a2dp);
+            } else {
+                warn!("Failed to initialize A2dp");
+            }
+        }
 
         // AVRCP
-        let avrcp_dispatcher = get_avrcp_dispatcher(self.tx.clone());
-        self.avrcp = Some(Avrcp::new(&self.intf.lock().unwrap()));
-        self.avrcp.as_mut().unwrap().initialize(avrcp_dispatcher);
+        if self.avrcp.is_none() {
+            let mut avrcp = Avrcp::new(&self.intf.lock().unwrap());
+            if avrcp.initialize(get_avrcp_dispatcher(self.tx.clone())) {
+                self.avrcp = Some(avrcp);
+            } else {
+                warn!("Failed to initialize Avrcp");
+            }
+        }
 
         // HFP
-        let hfp_dispatcher = get_hfp_dispatcher(self.tx.clone());
-        self.hfp = Some(Hfp::new(&self.intf.lock().unwrap()));
-        self.hfp.as_mut().unwrap().initialize(hfp_dispatcher);
+        if self.hfp.is_none() {
+            let mut hfp = Hfp::new(&self.intf.lock().unwrap());
+            if hfp.initialize(get_hfp_dispatcher(self.tx.clone())) {
+                self.hfp = Some(hfp);
+            } else {
+                warn!("Failed to initialize HFP");
+            }
+        }
 
-        true
+        self.a2dp.is_some() && self.avrcp.is_some() && self.hfp.is_some()
     }
 
     fn connect(&mut self, device: String) {
@@ -96,6 +96,10 @@ void BleAdvertiserIntf::OnOwnAddressRead(uint8_t advertiser_id, uint8_t address_
   RustRawAddress converted = rusty::CopyToRustAddress(address);
   rusty::gdadv_on_own_address_read(advertiser_id, address_type, &converted);
 }
+void BleAdvertiserIntf::OnAdvertisingSetTerminated(
+    uint8_t advertiser_id, uint8_t status, uint8_t num_completed_extended_adv_events) {
+  rusty::gdadv_on_advertising_set_terminated(advertiser_id, status, num_completed_extended_adv_events);
+}
 
 // BleAdvertiserInterface implementations
 
@@ -126,14 +130,14 @@ void BleAdvertiserIntf::SetData(uint8_t adv_id, bool set_scan_rsp, ::rust::Vec<u
       adv_id,
       set_scan_rsp,
       converted,
-      base::Bind(&BleAdvertiserIntf::OnIdStatusCallback, base::Unretained(this), adv_id));
+      base::Bind(&BleAdvertiserIntf::OnSetDataCallback, base::Unretained(this), adv_id, set_scan_rsp));
 }
 
 void BleAdvertiserIntf::Enable(uint8_t adv_id, bool enable, uint16_t duration, uint8_t max_ext_adv_events) {
   adv_intf_->Enable(
       adv_id,
       enable,
-      base::Bind(&BleAdvertiserIntf::OnIdStatusCallback, base::Unretained(this), adv_id),
+      base::Bind(&BleAdvertiserIntf::OnEnableCallback, base::Unretained(this), adv_id, enable),
       duration,
       max_ext_adv_events,
       base::Bind(&BleAdvertiserIntf::OnIdStatusCallback, base::Unretained(this), adv_id));
@@ -178,7 +182,7 @@ void BleAdvertiserIntf::StartAdvertisingSet(
 
   adv_intf_->StartAdvertisingSet(
       reg_id,
-      base::Bind(&BleAdvertiserIntf::OnIdTxPowerStatusCallback, base::Unretained(this)),
+      base::Bind(&BleAdvertiserIntf::OnAdvertisingSetStartedCallback, base::Unretained(this), reg_id),
       converted_params,
       converted_adv_data,
       converted_scan_rsp_data,
@@ -193,7 +197,9 @@ void BleAdvertiserIntf::SetPeriodicAdvertisingParameters(uint8_t adv_id, RustPer
   PeriodicAdvertisingParameters converted = internal::ConvertRustPeriodicAdvParams(params);
 
   adv_intf_->SetPeriodicAdvertisingParameters(
-      adv_id, converted, base::Bind(&BleAdvertiserIntf::OnIdStatusCallback, base::Unretained(this), adv_id));
+      adv_id,
+      converted,
+      base::Bind(&BleAdvertiserIntf::OnPeriodicAdvertisingParametersUpdated, base::Unretained(this), adv_id));
 }
 
 void BleAdvertiserIntf::SetPeriodicAdvertisingData(uint8_t adv_id, ::rust::Vec<uint8_t> data) {
@@ -201,12 +207,14 @@ void BleAdvertiserIntf::SetPeriodicAdvertisingData(uint8_t adv_id, ::rust::Vec<u
   std::copy(data.begin(), data.end(), std::back_inserter(converted));
 
   adv_intf_->SetPeriodicAdvertisingData(
-      adv_id, converted, base::Bind(&BleAdvertiserIntf::OnIdStatusCallback, base::Unretained(this), adv_id));
+      adv_id, converted, base::Bind(&BleAdvertiserIntf::OnPeriodicAdvertisingDataSet, base::Unretained(this), adv_id));
 }
 
 void BleAdvertiserIntf::SetPeriodicAdvertisingEnable(uint8_t adv_id, bool enable) {
   adv_intf_->SetPeriodicAdvertisingEnable(
-      adv_id, enable, base::Bind(&BleAdvertiserIntf::OnIdStatusCallback, base::Unretained(this), adv_id));
+      adv_id,
+      enable,
+      base::Bind(&BleAdvertiserIntf::OnPeriodicAdvertisingEnabled, base::Unretained(this), adv_id, enable));
 }
 
 void BleAdvertiserIntf::RegisterCallbacks() {
@@ -224,9 +232,23 @@ void BleAdvertiserIntf::OnIdTxPowerStatusCallback(uint8_t adv_id, int8_t tx_powe
 void BleAdvertiserIntf::OnParametersCallback(uint8_t adv_id, uint8_t status, int8_t tx_power) {
   gdadv_parameters_callback(adv_id, status, tx_power);
 }
+void BleAdvertiserIntf::OnAdvertisingSetStartedCallback(
+    int32_t reg_id, uint8_t adv_id, int8_t tx_power, uint8_t status) {
+  rusty::gdadv_on_advertising_set_started(reg_id, adv_id, tx_power, status);
+}
+void BleAdvertiserIntf::OnEnableCallback(uint8_t adv_id, bool enable, uint8_t status) {
+  rusty::gdadv_on_advertising_enabled(adv_id, enable, status);
+}
+void BleAdvertiserIntf::OnSetDataCallback(uint8_t adv_id, bool set_scan_rsp, uint8_t status) {
+  if (set_scan_rsp) {
+    rusty::gdadv_on_scan_response_data_set(adv_id, status);
+  } else {
+    rusty::gdadv_on_advertising_data_set(adv_id, status);
+  }
+}
 void BleAdvertiserIntf::OnGetAddressCallback(uint8_t adv_id, uint8_t addr_type, RawAddress address) {
   RustRawAddress converted = rusty::CopyToRustAddress(address);
-  gdadv_getaddress_callback(adv_id, addr_type, &converted);
+  rusty::gdadv_on_own_address_read(adv_id, addr_type, &converted);
 }
 
 std::unique_ptr<BleAdvertiserIntf> GetBleAdvertiserIntf(const unsigned char* gatt_intf) {
@@ -50,6 +50,8 @@ class BleAdvertiserIntf : public AdvertisingCallbacks {
   void OnPeriodicAdvertisingDataSet(uint8_t advertiser_id, uint8_t status) override;
   void OnPeriodicAdvertisingEnabled(uint8_t advertiser_id, bool enable, uint8_t status) override;
   void OnOwnAddressRead(uint8_t advertiser_id, uint8_t address_type, RawAddress address) override;
+  void OnAdvertisingSetTerminated(
+      uint8_t advertiser_id, uint8_t status, uint8_t num_completed_extended_adv_events) override;
 
   // BleAdvertiserInterface implementations
 
@@ -87,8 +89,20 @@ class BleAdvertiserIntf : public AdvertisingCallbacks {
   void OnIdStatusCallback(uint8_t adv_id, uint8_t status);
   void OnIdTxPowerStatusCallback(uint8_t adv_id, int8_t tx_power, uint8_t status);
   void OnParametersCallback(uint8_t adv_id, uint8_t status, int8_t tx_power);
+  // Completion of |GetOwnAddress|. Dispatched as |OnOwnAddressRead|.
   void OnGetAddressCallback(uint8_t adv_id, uint8_t addr_type, RawAddress address);
 
+  // Completion of |Enable|. Dispatched as |OnAdvertisingEnabled|.
+  void OnEnableCallback(uint8_t adv_id, bool enable, uint8_t status);
+
+  // Completion of |SetData|. Dispatched as either |OnAdvertisingDataSet| or
+  // |OnScanResponseDataSet| depending on |set_scan_rsp|.
+  void OnSetDataCallback(uint8_t adv_id, bool set_scan_rsp, uint8_t status);
+
+  // Completion of |StartAdvertisingSet|. The |reg_id| is bound at the callsite
+  // so that it can be reported via |OnAdvertisingSetStarted|.
+  void OnAdvertisingSetStartedCallback(int32_t reg_id, uint8_t adv_id, int8_t tx_power, uint8_t status);
+
   BleAdvertiserInterface* adv_intf_;
 };
 
@@ -94,12 +94,42 @@ std::vector<ApcfCommand> ConvertApcfVec(const ::rust::Vec<RustApcfCommand>& rust
 
   return converted;
 }
+
+MsftAdvMonitor ConvertMsftAdvMonitorFromRust(const RustMsftAdvMonitor& monitor) {
+  MsftAdvMonitor converted = {
+      .rssi_threshold_high = monitor.rssi_high_threshold,
+      .rssi_threshold_low = monitor.rssi_low_threshold,
+      .rssi_threshold_low_time_interval = monitor.rssi_low_timeout,
+      .rssi_sampling_period = monitor.rssi_sampling_period,
+      // .patterns is copied below
+  };
+
+  for (const RustMsftAdvMonitorPattern& pattern : monitor.patterns) {
+    std::vector<uint8_t> content;
+    std::copy(pattern.content.begin(), pattern.content.end(), std::back_inserter(content));
+    converted.patterns.push_back({
+        .ad_type = pattern.ad_type,
+        .start_byte = pattern.start_byte,
+        .pattern = content,
+    });
+  }
+
+  return converted;
+}
+
+RustUuid ConvertToRustUuid(const bluetooth::Uuid& uuid) {
+  RustUuid converted;
+  auto uu = uuid.To128BitBE();
+  std::copy(uu.begin(), uu.end(), std::begin(converted.uu));
+
+  return converted;
+}
 }  // namespace internal
 
 // ScanningCallbacks implementations
 
 void BleScannerIntf::OnScannerRegistered(const bluetooth::Uuid app_uuid, uint8_t scannerId, uint8_t status) {
-  rusty::gdscan_on_scanner_registered(reinterpret_cast<const signed char*>(&app_uuid), scannerId, status);
+  rusty::gdscan_on_scanner_registered(internal::ConvertToRustUuid(app_uuid), scannerId, status);
 }
 
 void BleScannerIntf::OnSetScannerParameterComplete(uint8_t scannerId, uint8_t status) {
@@ -135,7 +165,6 @@ void BleScannerIntf::OnScanResult(
 void BleScannerIntf::OnTrackAdvFoundLost(AdvertisingTrackInfo ati) {
   rusty::RustRawAddress addr = rusty::CopyToRustAddress(ati.advertiser_address);
   rusty::RustAdvertisingTrackInfo rust_info = {
-      .monitor_handle = ati.monitor_handle,
       .scanner_id = ati.scanner_id,
       .filter_index = ati.filter_index,
       .advertiser_state = ati.advertiser_state,
@@ -151,8 +180,8 @@ void BleScannerIntf::OnTrackAdvFoundLost(AdvertisingTrackInfo ati) {
       // .scan_response is copied below
   };
 
-  std::copy(rust_info.adv_packet.begin(), rust_info.adv_packet.end(), std::back_inserter(ati.adv_packet));
-  std::copy(rust_info.scan_response.begin(), rust_info.scan_response.end(), std::back_inserter(ati.scan_response));
+  std::copy(ati.adv_packet.begin(), ati.adv_packet.end(), std::back_inserter(rust_info.adv_packet));
+  std::copy(ati.scan_response.begin(), ati.scan_response.end(), std::back_inserter(rust_info.scan_response));
 
   rusty::gdscan_on_track_adv_found_lost(rust_info);
 }
@@ -212,11 +241,13 @@ void BleScannerIntf::ScanFilterEnable(bool enable) {
   scanner_intf_->ScanFilterEnable(enable, base::Bind(&BleScannerIntf::OnEnableCallback, base::Unretained(this)));
 }
 
-void BleScannerIntf::SetScanParameters(uint8_t scanner_id, uint16_t scan_interval, uint16_t scan_window) {
+void BleScannerIntf::SetScanParameters(
+    uint8_t scanner_id, uint16_t scan_interval, uint16_t scan_window, uint8_t scan_phy) {
   scanner_intf_->SetScanParameters(
       scanner_id,
       scan_interval,
       scan_window,
+      scan_phy,
       base::Bind(&BleScannerIntf::OnStatusCallback, base::Unretained(this), scanner_id));
 }
 
@@ -281,8 +312,24 @@ void BleScannerIntf::SyncTxParameters(RustRawAddress address, uint8_t mode, uint
   scanner_intf_->SyncTxParameters(converted, mode, skip, timeout, 0 /* place holder */);
 }
 
+void BleScannerIntf::MsftAdvMonitorAdd(RustMsftAdvMonitor monitor) {
+  scanner_intf_->MsftAdvMonitorAdd(
+      internal::ConvertMsftAdvMonitorFromRust(monitor),
+      base::Bind(&BleScannerIntf::OnMsftAdvMonitorAddCallback, base::Unretained(this)));
+}
+
+void BleScannerIntf::MsftAdvMonitorRemove(uint8_t monitor_handle) {
+  scanner_intf_->MsftAdvMonitorRemove(
+      monitor_handle, base::Bind(&BleScannerIntf::OnMsftAdvMonitorRemoveCallback, base::Unretained(this)));
+}
+
+void BleScannerIntf::MsftAdvMonitorEnable(bool enable) {
+  scanner_intf_->MsftAdvMonitorEnable(
+      enable, base::Bind(&BleScannerIntf::OnMsftAdvMonitorEnableCallback, base::Unretained(this)));
+}
+
 void BleScannerIntf::OnRegisterCallback(RustUuid uuid, uint8_t scanner_id, uint8_t btm_status) {
-  rusty::gdscan_register_callback(uuid, scanner_id, btm_status);
+  rusty::gdscan_on_scanner_registered(uuid, scanner_id, btm_status);
 }
 
 void BleScannerIntf::OnStatusCallback(uint8_t scanner_id, uint8_t btm_status) {
@@ -303,8 +350,20 @@ void BleScannerIntf::OnFilterConfigCallback(
   rusty::gdscan_filter_config_callback(filter_index, filt_type, avbl_space, action, btm_status);
 }
 
+void BleScannerIntf::OnMsftAdvMonitorAddCallback(uint8_t monitor_handle, uint8_t status) {
+  rusty::gdscan_msft_adv_monitor_add_callback(monitor_handle, status);
+}
+
+void BleScannerIntf::OnMsftAdvMonitorRemoveCallback(uint8_t status) {
+  rusty::gdscan_msft_adv_monitor_remove_callback(status);
+}
+
+void BleScannerIntf::OnMsftAdvMonitorEnableCallback(uint8_t status) {
+  rusty::gdscan_msft_adv_monitor_enable_callback(status);
+}
+
 void BleScannerIntf::OnPeriodicSyncStarted(
-    int,
+    int reg_id,
     uint8_t status,
     uint16_t sync_handle,
     uint8_t advertising_sid,
@@ -313,26 +372,40 @@ void BleScannerIntf::OnPeriodicSyncStarted(
     uint8_t phy,
     uint16_t interval) {
   RustRawAddress converted = rusty::CopyToRustAddress(address);
-  rusty::gdscan_start_sync_callback(status, sync_handle, advertising_sid, address_type, &converted, phy, interval);
+  rusty::gdscan_on_periodic_sync_started(
+      reg_id, status, sync_handle, advertising_sid, address_type, &converted, phy, interval);
 }
 
 void BleScannerIntf::OnPeriodicSyncReport(
     uint16_t sync_handle, int8_t tx_power, int8_t rssi, uint8_t status, std::vector<uint8_t> data) {
-  rusty::gdscan_sync_report_callback(sync_handle, tx_power, rssi, status, data.data(), data.size());
+  rusty::gdscan_on_periodic_sync_report(sync_handle, tx_power, rssi, status, data.data(), data.size());
 }
 
 void BleScannerIntf::OnPeriodicSyncLost(uint16_t sync_handle) {
-  rusty::gdscan_sync_lost_callback(sync_handle);
+  rusty::gdscan_on_periodic_sync_lost(sync_handle);
 }
 
-void BleScannerIntf::OnPeriodicSyncTransferred(int, uint8_t status, RawAddress address) {
+void BleScannerIntf::OnPeriodicSyncTransferred(int pa_source, uint8_t status, RawAddress address) {
   RustRawAddress converted = rusty::CopyToRustAddress(address);
-  rusty::gdscan_sync_transfer_callback(status, &converted);
+  rusty::gdscan_on_periodic_sync_transferred(pa_source, status, &converted);
 }
 
-void BleScannerIntf::RegisterCallbacks() {
+void BleScannerIntf::OnMsftAdvMonitorDeviceFound(uint8_t monitor_handle, RawAddress address) {
+  RustRawAddress converted = rusty::CopyToRustAddress(address);
+  rusty::gdscan_on_msft_adv_monitor_device_found(monitor_handle, &converted);
+}
+
+void BleScannerIntf::OnMsftAdvMonitorDeviceLost(uint8_t monitor_handle, RawAddress address) {
+  RustRawAddress converted = rusty::CopyToRustAddress(address);
+  rusty::gdscan_on_msft_adv_monitor_device_lost(monitor_handle, &converted);
+}
+
+bool BleScannerIntf::RegisterCallbacks() {
+  if (!scanner_intf_) return false;
+
   // Register self as a callback handler. We will dispatch to Rust callbacks.
   scanner_intf_->RegisterCallbacks(this);
+  return true;
 }
 
 // ScanningCallbacks overrides
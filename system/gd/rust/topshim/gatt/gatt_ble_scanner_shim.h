@@ -28,6 +28,7 @@ namespace rust {
 
 struct RustApcfCommand;
 struct RustGattFilterParam;
+struct RustMsftAdvMonitor;
 struct RustRawAddress;
 struct RustUuid;
 
@@ -73,13 +74,15 @@ class BleScannerIntf : public ScanningCallbacks {
       uint16_t sync_handle, int8_t tx_power, int8_t rssi, uint8_t status, std::vector<uint8_t> data) override;
   void OnPeriodicSyncLost(uint16_t sync_handle) override;
   void OnPeriodicSyncTransferred(int pa_source, uint8_t status, RawAddress address) override;
+  void OnMsftAdvMonitorDeviceFound(uint8_t monitor_handle, RawAddress address) override;
+  void OnMsftAdvMonitorDeviceLost(uint8_t monitor_handle, RawAddress address) override;
 
   // Implementations of BleScannerInterface. These don't inherit from
   // BleScannerInterface because the Rust FFI boundary requires some clever
   // modifications.
 
   // Register a scanner for a Uuid. Response comes back via
-  // |OnRegisterCallback|.
+  // |OnScannerRegistered|.
   void RegisterScanner(RustUuid uuid);
 
   // Unregister a scanner with a |scanner_id|.
@@ -102,9 +105,10 @@ class BleScannerIntf : public ScanningCallbacks {
   // Enable/disable scan filter. Gets responses via |OnEnableCallback|.
   void ScanFilterEnable(bool enable);
 
-  // Sets the LE scan interval and window in units of N * 0.625 msec. The result
-  // of this action is returned via |OnStatusCallback|.
-  void SetScanParameters(uint8_t scanner_id, uint16_t scan_interval, uint16_t scan_window);
+  // Sets the LE scan interval and window in units of N * 0.625 msec, and the
+  // PHYs to scan on. The result of this action is returned via
+  // |OnStatusCallback|.
+  void SetScanParameters(uint8_t scanner_id, uint16_t scan_interval, uint16_t scan_window, uint8_t scan_phy);
 
   // Configure the batchscan storage and get a response via |OnStatusCallback|.
   void BatchscanConfigStorage(
@@ -126,8 +130,8 @@ class BleScannerIntf : public ScanningCallbacks {
   // |ScanningCallbacks::OnBatchScanReports|.
   void BatchscanReadReports(uint8_t scanner_id, int32_t scan_mode);
 
-  // Start periodic sync. Gets responses via |OnStartSyncCb|. Periodic reports
-  // come via |OnSyncReportCb| and |OnSyncLostCb|.
+  // Start periodic sync. Gets responses via |OnPeriodicSyncStarted|. Periodic
+  // reports come via |OnPeriodicSyncReport| and |OnPeriodicSyncLost|.
   void StartSync(uint8_t sid, RustRawAddress address, uint16_t skip, uint16_t timeout);
 
   // Stop periodic sync.
@@ -137,18 +141,32 @@ class BleScannerIntf : public ScanningCallbacks {
   void CancelCreateSync(uint8_t sid, RustRawAddress address);
 
   // Transfer sync data to target address. Gets responses via
-  // |OnSyncTransferCb|.
+  // |OnPeriodicSyncTransferred|.
   void TransferSync(RustRawAddress address, uint16_t service_data, uint16_t sync_handle);
 
-  // Transfer set info to target address. Gets responses via |OnSyncTransferCb|.
+  // Transfer set info to target address. Gets responses via
+  // |OnPeriodicSyncTransferred|.
   void TransferSetInfo(RustRawAddress address, uint16_t service_data, uint8_t adv_handle);
 
-  // Sync tx parameters to target address. Gets responses via |OnStartSyncCb|.
+  // Sync tx parameters to target address. Gets responses via
+  // |OnPeriodicSyncStarted|.
   void SyncTxParameters(RustRawAddress address, uint8_t mode, uint16_t skip, uint16_t timeout);
 
+  // Add an MSFT advertisement monitor. Gets responses via
+  // |OnMsftAdvMonitorAddCallback|.
+  void MsftAdvMonitorAdd(RustMsftAdvMonitor monitor);
+
+  // Remove an MSFT advertisement monitor. Gets responses via
+  // |OnMsftAdvMonitorRemoveCallback|.
+  void MsftAdvMonitorRemove(uint8_t monitor_handle);
+
+  // Enable/disable all MSFT advertisement monitors. Gets responses via
+  // |OnMsftAdvMonitorEnableCallback|.
+  void MsftAdvMonitorEnable(bool enable);
+
   // Register scanning callbacks to be dispatched to the Rust layer via static
-  // methods.
-  void RegisterCallbacks();
+  // methods. Returns false if there is no scanner interface to register with.
+  bool RegisterCallbacks();
 
  private:
   // The callback functions below will get base::Bind to the apis that need it
@@ -162,6 +180,9 @@ class BleScannerIntf : public ScanningCallbacks {
   void OnFilterParamSetupCallback(uint8_t scanner_id, uint8_t avbl_space, uint8_t action_type, uint8_t btm_status);
   void OnFilterConfigCallback(
       uint8_t filt_index, uint8_t filt_type, uint8_t avbl_space, uint8_t action, uint8_t btm_status);
+  void OnMsftAdvMonitorAddCallback(uint8_t monitor_handle, uint8_t status);
+  void OnMsftAdvMonitorRemoveCallback(uint8_t status);
+  void OnMsftAdvMonitorEnableCallback(uint8_t status);
 
   BleScannerInterface* scanner_intf_;
 };
@@ -18,7 +18,10 @@
 
 #include "base/bind.h"
 #include "base/callback.h"
+#include "gd/hci/msft.h"
 #include "gd/rust/topshim/common/utils.h"
+#include "main/shim/controller.h"
+#include "main/shim/entry.h"
 #include "rust/cxx.h"
 #include "src/profiles/gatt.rs.h"
 #include "types/raw_address.h"
@@ -33,6 +36,11 @@ void ReadPhyCallback(int client_if, RawAddress address, uint8_t tx_phy, uint8_t
   bluetooth::topshim::rust::read_phy_callback(client_if, CopyToRustAddress(address), tx_phy, rx_phy, status);
 }
 
+void ServerReadPhyCallback(int server_if, RawAddress address, uint8_t tx_phy, uint8_t rx_phy, uint8_t status) {
+  bluetooth::topshim::rust::server_read_phy_callback(
+      server_if, CopyToRustAddress(address), tx_phy, rx_phy, status);
+}
+
 }  // namespace internal
 
 int GattClientIntf::read_phy(int client_if, RustRawAddress addr) {
@@ -40,10 +48,35 @@ int GattClientIntf::read_phy(int client_if, RustRawAddress addr) {
   return client_intf_->read_phy(address, base::Bind(&internal::ReadPhyCallback, client_if, address));
 }
 
+int GattServerIntf::read_phy(int server_if, RustRawAddress addr) {
+  RawAddress address = CopyFromRustAddress(addr);
+  return server_intf_->read_phy(address, base::Bind(&internal::ServerReadPhyCallback, server_if, address));
+}
+
 std::unique_ptr<GattClientIntf> GetGattClientProfile(const unsigned char* gatt_intf) {
   return std::make_unique<GattClientIntf>(reinterpret_cast<const btgatt_interface_t*>(gatt_intf)->client);
 }
 
+std::unique_ptr<GattServerIntf> GetGattServerProfile(const unsigned char* gatt_intf) {
+  return std::make_unique<GattServerIntf>(reinterpret_cast<const btgatt_interface_t*>(gatt_intf)->server);
+}
+
+RustGattCapabilities GetGattCapabilities() {
+  RustGattCapabilities capabilities{};
+  const controller_t* controller = controller_get_interface();
+  // Nothing is supported until the controller has been read.
+  if (controller == nullptr || !controller->get_is_ready()) return capabilities;
+
+  capabilities.le_extended_advertising_supported = controller->supports_ble_extended_advertising();
+  capabilities.le_periodic_advertising_supported = controller->supports_ble_periodic_advertising();
+  capabilities.le_2m_phy_supported = controller->supports_ble_2m_phy();
+  capabilities.le_coded_phy_supported = controller->supports_ble_coded_phy();
+  capabilities.max_advertising_sets = controller->get_ble_number_of_supported_advertising_sets();
+  capabilities.max_advertising_data_length = controller->get_ble_maximum_advertising_data_length();
+  capabilities.msft_supported = bluetooth::shim::GetMsftExtensionManager()->SupportsMsftExtensions();
+  return capabilities;
+}
+
 }  // namespace rust
 }  // namespace topshim
 }  // namespace bluetooth
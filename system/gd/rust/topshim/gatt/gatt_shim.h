@@ -27,6 +27,7 @@ namespace topshim {
 namespace rust {
 
 struct RustRawAddress;
+struct RustGattCapabilities;
 
 class GattClientIntf {
  public:
@@ -39,7 +40,21 @@ class GattClientIntf {
   const btgatt_client_interface_t* client_intf_;
 };
 
+class GattServerIntf {
+ public:
+  GattServerIntf(const btgatt_server_interface_t* server_intf) : server_intf_(server_intf){};
+  ~GattServerIntf() = default;
+
+  int read_phy(int server_if, RustRawAddress bt_addr);
+
+ private:
+  const btgatt_server_interface_t* server_intf_;
+};
+
 std::unique_ptr<GattClientIntf> GetGattClientProfile(const unsigned char* gatt_intf);
+std::unique_ptr<GattServerIntf> GetGattServerProfile(const unsigned char* gatt_intf);
+
+RustGattCapabilities GetGattCapabilities();
 
 }  // namespace rust
 }  // namespace topshim
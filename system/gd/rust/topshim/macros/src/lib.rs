@@ -91,6 +91,8 @@ impl Parse for CbVariant {
 ///
 /// Example:
 ///     u32 -> _
+///
+/// A callback arriving while no dispatcher of its type is set is logged and dropped.
 pub fn cb_variant(input: TokenStream) -> TokenStream {
     let parsed_cptr = parse_macro_input!(input as CbVariant);
 
@@ -127,8 +129,12 @@ pub fn cb_variant(input: TokenStream) -> TokenStream {
         extern "C" fn #ident(#params) {
             #stmts
 
-            unsafe {
-                (get_dispatchers().lock().unwrap().get::<#dispatcher>().unwrap().clone().lock().unwrap().dispatch)(#rpath(#args));
+            // Callbacks can still arrive after the profile dropped its
+            // dispatcher. Panicking here would unwind across the FFI boundary.
+            let dispatcher = get_dispatchers().lock().unwrap().get::<#dispatcher>();
+            match dispatcher {
+                Some(dispatcher) => (dispatcher.lock().unwrap().dispatch)(#rpath(#args)),
+                None => log::warn!("{}: no dispatcher set, dropping callback", stringify!(#ident)),
             }
         }
     };
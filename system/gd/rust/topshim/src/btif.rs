@@ -289,6 +289,68 @@ pub type BtRemoteVersion = bindings::bt_remote_version_t;
 pub type Uuid = bindings::bluetooth::Uuid;
 pub type Uuid128Bit = bindings::bluetooth::Uuid_UUID128Bit;
 
+/// Bluetooth Base UUID (00000000-0000-1000-8000-00805F9B34FB) that 16 and
+/// 32-bit uuids are shortened from.
+const BASE_UUID: [u8; 16] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5f, 0x9b, 0x34, 0xfb,
+];
+
+/// |Uuid::uu| is in big-endian order, most significant byte first, the same
+/// layout as bluetooth::Uuid in C++. It crosses the FFI boundary unchanged in
+/// both directions: the shims convert with To128BitBE/From128BitBE and the
+/// btif interfaces take the C++ type directly. Uuids found in ATT PDUs and
+/// advertising data are little-endian and must go through |from_le_bytes|.
+impl Uuid {
+    /// Uuid from its big-endian bytes, e.g. as written out in its string form.
+    pub fn from_be_bytes(bytes: [u8; 16]) -> Uuid {
+        Uuid { uu: bytes }
+    }
+
+    /// Big-endian bytes of this uuid.
+    pub fn to_be_bytes(&self) -> [u8; 16] {
+        self.uu
+    }
+
+    /// Uuid from its little-endian bytes, as sent over the air.
+    pub fn from_le_bytes(mut bytes: [u8; 16]) -> Uuid {
+        bytes.reverse();
+        Uuid { uu: bytes }
+    }
+
+    /// Little-endian bytes of this uuid.
+    pub fn to_le_bytes(&self) -> [u8; 16] {
+        let mut bytes = self.uu;
+        bytes.reverse();
+        bytes
+    }
+
+    /// Expands a 16-bit uuid, e.g. an assigned number like 0x180F.
+    pub fn from_u16(uuid: u16) -> Uuid {
+        Uuid::from_u32(uuid.into())
+    }
+
+    /// Expands a 32-bit uuid.
+    pub fn from_u32(uuid: u32) -> Uuid {
+        let mut uu = BASE_UUID;
+        uu[..4].copy_from_slice(&uuid.to_be_bytes());
+        Uuid { uu }
+    }
+
+    /// The 16-bit form of this uuid, if it has one.
+    pub fn as_u16(&self) -> Option<u16> {
+        self.as_u32().filter(|uuid| *uuid <= u16::MAX.into()).map(|uuid| uuid as u16)
+    }
+
+    /// The 32-bit form of this uuid, if it has one.
+    pub fn as_u32(&self) -> Option<u32> {
+        if self.uu[4..] != BASE_UUID[4..] {
+            return None;
+        }
+
+        Some(u32::from_be_bytes([self.uu[0], self.uu[1], self.uu[2], self.uu[3]]))
+    }
+}
+
 /// All supported Bluetooth properties after conversion.
 #[derive(Debug, Clone)]
 pub enum BluetoothProperty {
@@ -596,6 +658,7 @@ pub type FfiAddress = bindings::RawAddress;
 /// bindings::RawAddress. Macros `deref_ffi_address` and `cast_to_ffi_address`
 /// are used for transforming between bindings::RawAddress at ffi boundaries.
 #[derive(Copy, Clone, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct RawAddress {
     pub val: [u8; 6],
@@ -603,6 +666,10 @@ pub struct RawAddress {
 
 impl Debug for RawAddress {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        // Callbacks carrying addresses are logged through their Debug output.
+        if cfg!(feature = "redact-addresses") {
+            return f.write_str(&self.to_redacted_string());
+        }
         f.write_fmt(format_args!(
             "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
             self.val[0], self.val[1], self.val[2], self.val[3], self.val[4], self.val[5]
@@ -660,6 +727,12 @@ impl RawAddress {
     pub fn to_byte_arr(&self) -> [u8; 6] {
         self.val.clone()
     }
+
+    /// The address with its middle bytes masked, e.g. "AA:XX:XX:XX:XX:FF", to
+    /// tell devices apart in logs without identifying them.
+    pub fn to_redacted_string(&self) -> String {
+        format!("{:02X}:XX:XX:XX:XX:{:02X}", self.val[0], self.val[5])
+    }
 }
 
 #[macro_export]
@@ -861,8 +934,11 @@ impl BluetoothInterface {
         let converted: cxx::UniquePtr<ffi::InitFlags> = ffi::ConvertFlags(init_flags);
         let flags = (*converted).GetFlagsPtr();
 
-        if get_dispatchers().lock().unwrap().set::<BaseCb>(Arc::new(Mutex::new(callbacks))) {
-            panic!("Tried to set dispatcher for BaseCallbacks but it already existed");
+        if let Err(e) =
+            get_dispatchers().lock().unwrap().try_set::<BaseCb>(Arc::new(Mutex::new(callbacks)))
+        {
+            log::error!("Failed to initialize the Bluetooth interface: {:?}", e);
+            return false;
         }
 
         // Fill up callbacks struct to pass to init function (will be copied so
@@ -1059,8 +1135,12 @@ pub fn get_btinterface() -> Option<BluetoothInterface> {
     ret
 }
 
-// Turns C-array T[] to Vec<U>.
+// Turns C-array T[] to Vec<U>. A null |start| yields an empty Vec.
 pub(crate) fn ptr_to_vec<T: Copy, U: From<T>>(start: *const T, length: usize) -> Vec<U> {
+    if start.is_null() {
+        return Vec::new();
+    }
+
     unsafe { (0..length).map(|i| U::from(*start.offset(i as isize))).collect::<Vec<U>>() }
 }
 
@@ -1069,6 +1149,69 @@ mod tests {
     use super::*;
     use std::mem;
 
+    #[test]
+    fn test_uuid_short_forms() {
+        let battery = Uuid::from_u16(0x180f);
+        assert_eq!(
+            battery.uu,
+            [
+                0x00, 0x00, 0x18, 0x0f, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5f, 0x9b,
+                0x34, 0xfb
+            ]
+        );
+        assert_eq!(battery.as_u16(), Some(0x180f));
+        assert_eq!(battery.as_u32(), Some(0x180f));
+
+        let long = Uuid::from_u32(0x12345678);
+        assert_eq!(long.as_u16(), None);
+        assert_eq!(long.as_u32(), Some(0x12345678));
+
+        let custom = Uuid { uu: [1; 16] };
+        assert_eq!(custom.as_u16(), None);
+        assert_eq!(custom.as_u32(), None);
+    }
+
+    #[test]
+    fn test_uuid_byte_order() {
+        let be = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        let mut le = be;
+        le.reverse();
+
+        let uuid = Uuid::from_be_bytes(be);
+        assert_eq!(uuid.uu, be);
+        assert_eq!(uuid.to_be_bytes(), be);
+        assert_eq!(uuid.to_le_bytes(), le);
+        assert_eq!(Uuid::from_le_bytes(le), uuid);
+
+        // The 16-bit form sits in the most significant bytes.
+        let battery = Uuid::from_u16(0x180f);
+        assert_eq!(battery.to_be_bytes()[..4], [0x00, 0x00, 0x18, 0x0f]);
+        assert_eq!(battery.to_le_bytes()[12..], [0x0f, 0x18, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_address_redaction() {
+        let addr = RawAddress { val: [0xaa, 0x12, 0x34, 0x56, 0x78, 0xff] };
+        assert_eq!(addr.to_redacted_string(), "AA:XX:XX:XX:XX:FF");
+        assert_eq!(addr.to_string(), "AA:12:34:56:78:FF");
+
+        let expected = if cfg!(feature = "redact-addresses") {
+            "AA:XX:XX:XX:XX:FF"
+        } else {
+            "AA:12:34:56:78:FF"
+        };
+        assert_eq!(format!("{:?}", addr), expected);
+    }
+
+    #[test]
+    fn test_uuid_size() {
+        // Passed by reference as a bluetooth::Uuid.
+        assert_eq!(mem::size_of::<Uuid>(), 16);
+    }
+
     #[test]
     fn test_addr_size() {
         assert_eq!(mem::size_of::<RawAddress>(), mem::size_of::<FfiAddress>());
@@ -1125,6 +1268,16 @@ mod tests {
         assert_eq!(expected, vec);
     }
 
+    #[test]
+    fn test_ptr_to_vec_null_and_empty() {
+        let vec: Vec<i32> = ptr_to_vec(std::ptr::null::<i32>(), 5);
+        assert!(vec.is_empty());
+
+        let arr: [i32; 3] = [1, 2, 3];
+        let vec: Vec<i32> = ptr_to_vec(arr.as_ptr(), 0);
+        assert!(vec.is_empty());
+    }
+
     #[test]
     fn test_property_with_string_conversions() {
         {
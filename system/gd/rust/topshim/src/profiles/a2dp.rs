@@ -286,8 +286,11 @@ impl A2dp {
     }
 
     pub fn initialize(&mut self, callbacks: A2dpCallbacksDispatcher) -> bool {
-        if get_dispatchers().lock().unwrap().set::<A2dpCb>(Arc::new(Mutex::new(callbacks))) {
-            panic!("Tried to set dispatcher for A2dp callbacks while it already exists");
+        if let Err(e) =
+            get_dispatchers().lock().unwrap().try_set::<A2dpCb>(Arc::new(Mutex::new(callbacks)))
+        {
+            log::error!("Failed to initialize A2dp: {:?}", e);
+            return false;
         }
         self.internal.init();
         true
@@ -353,8 +356,11 @@ impl A2dpSink {
     }
 
     pub fn initialize(&mut self, callbacks: A2dpSinkCallbacksDispatcher) -> bool {
-        if get_dispatchers().lock().unwrap().set::<A2dpSinkCb>(Arc::new(Mutex::new(callbacks))) {
-            panic!("Tried to set dispatcher for A2dp Sink Callbacks while it already exists");
+        if let Err(e) =
+            get_dispatchers().lock().unwrap().try_set::<A2dpSinkCb>(Arc::new(Mutex::new(callbacks)))
+        {
+            log::error!("Failed to initialize A2dp Sink: {:?}", e);
+            return false;
         }
         self.internal.init();
         true
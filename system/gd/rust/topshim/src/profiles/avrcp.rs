@@ -67,8 +67,11 @@ impl Avrcp {
     }
 
     pub fn initialize(&mut self, callbacks: AvrcpCallbacksDispatcher) -> bool {
-        if get_dispatchers().lock().unwrap().set::<AvrcpCb>(Arc::new(Mutex::new(callbacks))) {
-            panic!("Tried to set dispatcher for Avrcp callbacks while it already exists");
+        if let Err(e) =
+            get_dispatchers().lock().unwrap().try_set::<AvrcpCb>(Arc::new(Mutex::new(callbacks)))
+        {
+            log::error!("Failed to initialize Avrcp: {:?}", e);
+            return false;
         }
         self.internal.pin_mut().init();
         true
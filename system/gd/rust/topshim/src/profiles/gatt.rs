@@ -1,19 +1,30 @@
 use crate::bindings::root as bindings;
 use crate::btif::{
-    ptr_to_vec, BluetoothInterface, BtStatus, FfiAddress, RawAddress, SupportedProfiles, Uuid,
+    ptr_to_vec, BluetoothInterface, BtStatus, BtTransport, FfiAddress, RawAddress,
+    SupportedProfiles, Uuid,
 };
 use crate::profiles::gatt::bindings::{
     btgatt_callbacks_t, btgatt_client_callbacks_t, btgatt_client_interface_t, btgatt_interface_t,
     btgatt_scanner_callbacks_t, btgatt_server_callbacks_t, btgatt_server_interface_t,
-    BleAdvertiserInterface, BleScannerInterface,
+    BleScannerInterface,
 };
-use crate::topstack::get_dispatchers;
+use crate::topstack::{get_dispatchers, DispatchContainer};
 use crate::{cast_to_ffi_address, ccall, deref_ffi_address, mutcxxcall};
 
 use num_traits::cast::FromPrimitive;
 
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "connection-state-events")]
+use tokio::sync::broadcast;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::Stream;
 use topshim_macros::cb_variant;
 
 pub type BtGattNotifyParams = bindings::btgatt_notify_params_t;
@@ -83,6 +94,24 @@ pub mod ffi {
         irk: [u8; 16],
     }
 
+    // Defined in C++ and needs a translation in shim.
+    #[derive(Debug, Clone)]
+    pub struct RustMsftAdvMonitorPattern {
+        ad_type: u8,
+        start_byte: u8,
+        content: Vec<u8>,
+    }
+
+    // Defined in C++ and needs a translation in shim.
+    #[derive(Debug, Clone)]
+    pub struct RustMsftAdvMonitor {
+        rssi_high_threshold: i8,
+        rssi_low_threshold: i8,
+        rssi_low_timeout: u8,
+        rssi_sampling_period: u8,
+        patterns: Vec<RustMsftAdvMonitorPattern>,
+    }
+
     #[derive(Debug, Clone)]
     pub struct RustAdvertiseParameters {
         advertising_event_properties: u16,
@@ -104,6 +133,18 @@ pub mod ffi {
         periodic_advertising_properties: u16,
     }
 
+    // Read from the controller in C++.
+    #[derive(Debug, Clone)]
+    pub struct RustGattCapabilities {
+        le_extended_advertising_supported: bool,
+        le_periodic_advertising_supported: bool,
+        le_2m_phy_supported: bool,
+        le_coded_phy_supported: bool,
+        max_advertising_sets: u8,
+        max_advertising_data_length: u16,
+        msft_supported: bool,
+    }
+
     unsafe extern "C++" {
         include!("gatt/gatt_shim.h");
 
@@ -113,6 +154,15 @@ pub mod ffi {
 
         fn read_phy(self: Pin<&mut GattClientIntf>, client_if: i32, bt_addr: RustRawAddress)
             -> i32;
+
+        type GattServerIntf;
+
+        unsafe fn GetGattServerProfile(btif: *const u8) -> UniquePtr<GattServerIntf>;
+
+        fn read_phy(self: Pin<&mut GattServerIntf>, server_if: i32, bt_addr: RustRawAddress)
+            -> i32;
+
+        fn GetGattCapabilities() -> RustGattCapabilities;
     }
 
     extern "Rust" {
@@ -124,6 +174,14 @@ pub mod ffi {
             rx_phy: u8,
             status: u8,
         );
+
+        fn server_read_phy_callback(
+            server_if: i32,
+            addr: RustRawAddress,
+            tx_phy: u8,
+            rx_phy: u8,
+            status: u8,
+        );
     }
 
     unsafe extern "C++" {
@@ -155,6 +213,7 @@ pub mod ffi {
             scanner_id: u8,
             scan_interval: u16,
             scan_window: u16,
+            scan_phy: u8,
         );
 
         fn BatchscanConfigStorage(
@@ -203,16 +262,19 @@ pub mod ffi {
             skip: u16,
             timeout: u16,
         );
+        fn MsftAdvMonitorAdd(self: Pin<&mut BleScannerIntf>, monitor: RustMsftAdvMonitor);
+        fn MsftAdvMonitorRemove(self: Pin<&mut BleScannerIntf>, monitor_handle: u8);
+        fn MsftAdvMonitorEnable(self: Pin<&mut BleScannerIntf>, enable: bool);
 
         /// Registers a C++ |ScanningCallbacks| implementation with the BleScanner.
         /// The shim implementation will call all the callbacks defined via |cb_variant!|.
-        fn RegisterCallbacks(self: Pin<&mut BleScannerIntf>);
+        fn RegisterCallbacks(self: Pin<&mut BleScannerIntf>) -> bool;
     }
 
     extern "Rust" {
         // All callbacks below are generated by cb_variant! and will be called
         // by the ScanningCallbacks handler in shim.
-        unsafe fn gdscan_on_scanner_registered(uuid: *const i8, scannerId: u8, status: u8);
+        unsafe fn gdscan_on_scanner_registered(uuid: RustUuid, scannerId: u8, status: u8);
         unsafe fn gdscan_on_set_scanner_parameter_complete(scannerId: u8, status: u8);
         unsafe fn gdscan_on_scan_result(
             event_type: u16,
@@ -237,9 +299,40 @@ pub mod ffi {
             data_len: usize,
         );
         unsafe fn gdscan_on_batch_scan_threshold_crossed(client_if: i32);
+        unsafe fn gdscan_on_periodic_sync_started(
+            reg_id: i32,
+            status: u8,
+            sync_handle: u16,
+            advertising_sid: u8,
+            addr_type: u8,
+            address: *const RustRawAddress,
+            phy: u8,
+            interval: u16,
+        );
+        unsafe fn gdscan_on_periodic_sync_report(
+            sync_handle: u16,
+            tx_power: i8,
+            rssi: i8,
+            status: u8,
+            data: *const u8,
+            len: usize,
+        );
+        unsafe fn gdscan_on_periodic_sync_lost(sync_handle: u16);
+        unsafe fn gdscan_on_periodic_sync_transferred(
+            pa_source: i32,
+            status: u8,
+            address: *const RustRawAddress,
+        );
+        unsafe fn gdscan_on_msft_adv_monitor_device_found(
+            monitor_handle: u8,
+            address: *const RustRawAddress,
+        );
+        unsafe fn gdscan_on_msft_adv_monitor_device_lost(
+            monitor_handle: u8,
+            address: *const RustRawAddress,
+        );
 
         // Static cb_variant! callbacks using base::Callback
-        unsafe fn gdscan_register_callback(uuid: RustUuid, scanner_id: u8, btm_status: u8);
         unsafe fn gdscan_status_callback(scanner_id: u8, btm_status: u8);
         unsafe fn gdscan_enable_callback(action: u8, btm_status: u8);
         unsafe fn gdscan_filter_param_setup_callback(
@@ -255,25 +348,9 @@ pub mod ffi {
             action: u8,
             btm_status: u8,
         );
-        unsafe fn gdscan_start_sync_callback(
-            status: u8,
-            sync_handle: u16,
-            advertising_sid: u8,
-            addr_type: u8,
-            address: *const RustRawAddress,
-            phy: u8,
-            interval: u16,
-        );
-        unsafe fn gdscan_sync_report_callback(
-            sync_handle: u16,
-            tx_power: i8,
-            rssi: i8,
-            status: u8,
-            data: *const u8,
-            len: usize,
-        );
-        unsafe fn gdscan_sync_lost_callback(sync_handle: u16);
-        unsafe fn gdscan_sync_transfer_callback(status: u8, address: *const RustRawAddress);
+        unsafe fn gdscan_msft_adv_monitor_add_callback(monitor_handle: u8, status: u8);
+        unsafe fn gdscan_msft_adv_monitor_remove_callback(status: u8);
+        unsafe fn gdscan_msft_adv_monitor_enable_callback(status: u8);
     }
 
     unsafe extern "C++" {
@@ -363,16 +440,16 @@ pub mod ffi {
             addr_type: u8,
             address: *const RustRawAddress,
         );
+        unsafe fn gdadv_on_advertising_set_terminated(
+            adv_id: u8,
+            status: u8,
+            num_completed_extended_adv_events: u8,
+        );
 
         // In-band callbacks also generated with cb_variant!.
         unsafe fn gdadv_idstatus_callback(adv_id: u8, status: u8);
         unsafe fn gdadv_idtxpowerstatus_callback(adv_id: u8, tx_power: i8, status: u8);
         unsafe fn gdadv_parameters_callback(adv_id: u8, status: u8, tx_power: i8);
-        unsafe fn gdadv_getaddress_callback(
-            adv_id: u8,
-            addr_type: u8,
-            address: *const RustRawAddress,
-        );
     }
 }
 
@@ -384,17 +461,181 @@ pub type PeriodicAdvertisingParameters = ffi::RustPeriodicAdvertisingParameters;
 
 impl From<ffi::RustUuid> for Uuid {
     fn from(item: ffi::RustUuid) -> Self {
-        Uuid { uu: item.uu }
+        Uuid::from_be_bytes(item.uu)
     }
 }
 
 impl From<Uuid> for ffi::RustUuid {
     fn from(item: Uuid) -> Self {
-        ffi::RustUuid { uu: item.uu }
+        ffi::RustUuid { uu: item.to_be_bytes() }
+    }
+}
+
+/// APCF filter types. Matches the BTM_BLE_PF_* values in btm_ble_api_types.h.
+#[derive(Debug, FromPrimitive, PartialEq)]
+#[repr(u8)]
+enum ApcfFilterType {
+    Address = 0,
+    ServiceUuid = 2,
+    LocalName = 4,
+    ManufacturerData = 5,
+    ServiceData = 6,
+}
+
+/// A single advertising packet content filter (APCF) condition. A scan result
+/// is only reported when it matches the conditions added to a filter index.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanFilterCondition {
+    /// Params: Address, Address Type
+    Address(RawAddress, u8),
+
+    /// Params: Service Uuid, Uuid Mask
+    ServiceUuid(Uuid, Uuid),
+
+    /// Params: Service Data, Data Mask
+    ServiceData(Vec<u8>, Vec<u8>),
+
+    /// Params: Company Id, Company Mask, Data, Data Mask
+    ManufacturerData(u16, u16, Vec<u8>, Vec<u8>),
+
+    /// Params: Local Name
+    LocalName(String),
+}
+
+impl From<ScanFilterCondition> for ApcfCommand {
+    fn from(item: ScanFilterCondition) -> Self {
+        let mut command = ApcfCommand {
+            type_: 0,
+            address: ffi::RustRawAddress { address: [0; 6] },
+            addr_type: 0,
+            uuid: Uuid::default().into(),
+            uuid_mask: Uuid::default().into(),
+            name: vec![],
+            company: 0,
+            company_mask: 0,
+            ad_type: 0,
+            data: vec![],
+            data_mask: vec![],
+            irk: [0; 16],
+        };
+
+        match item {
+            ScanFilterCondition::Address(address, addr_type) => {
+                command.type_ = ApcfFilterType::Address as u8;
+                command.address = ffi::RustRawAddress { address: address.val };
+                command.addr_type = addr_type;
+            }
+            ScanFilterCondition::ServiceUuid(uuid, uuid_mask) => {
+                command.type_ = ApcfFilterType::ServiceUuid as u8;
+                command.uuid = uuid.into();
+                command.uuid_mask = uuid_mask.into();
+            }
+            ScanFilterCondition::ServiceData(data, data_mask) => {
+                command.type_ = ApcfFilterType::ServiceData as u8;
+                command.data = data;
+                command.data_mask = data_mask;
+            }
+            ScanFilterCondition::ManufacturerData(company, company_mask, data, data_mask) => {
+                command.type_ = ApcfFilterType::ManufacturerData as u8;
+                command.company = company;
+                command.company_mask = company_mask;
+                command.data = data;
+                command.data_mask = data_mask;
+            }
+            ScanFilterCondition::LocalName(name) => {
+                command.type_ = ApcfFilterType::LocalName as u8;
+                command.name = name.into_bytes();
+            }
+        }
+
+        command
+    }
+}
+
+impl ScanFilterCondition {
+    /// Recovers the condition carried by an |ApcfCommand|. Returns None for
+    /// filter types that have no matching condition.
+    pub fn from_apcf(command: &ApcfCommand) -> Option<Self> {
+        match ApcfFilterType::from_u8(command.type_)? {
+            ApcfFilterType::Address => Some(ScanFilterCondition::Address(
+                RawAddress { val: command.address.address },
+                command.addr_type,
+            )),
+            ApcfFilterType::ServiceUuid => Some(ScanFilterCondition::ServiceUuid(
+                command.uuid.into(),
+                command.uuid_mask.into(),
+            )),
+            ApcfFilterType::ServiceData => Some(ScanFilterCondition::ServiceData(
+                command.data.clone(),
+                command.data_mask.clone(),
+            )),
+            ApcfFilterType::ManufacturerData => Some(ScanFilterCondition::ManufacturerData(
+                command.company,
+                command.company_mask,
+                command.data.clone(),
+                command.data_mask.clone(),
+            )),
+            ApcfFilterType::LocalName => Some(ScanFilterCondition::LocalName(
+                String::from_utf8_lossy(&command.name).into_owned(),
+            )),
+        }
+    }
+
+    // Whether |result|, whose advertising data parses into |fields|, meets
+    // the condition. Empty masks compare every bit.
+    fn matches(&self, result: &ScanResult, fields: &[AdvDataField]) -> bool {
+        match self {
+            ScanFilterCondition::Address(address, _) => result.address == *address,
+            ScanFilterCondition::ServiceUuid(uuid, uuid_mask) => {
+                let mask = if uuid_mask.uu == [0; 16] { [0xff; 16] } else { uuid_mask.uu };
+                let mut uuids = fields.iter().flat_map(|field| match field {
+                    AdvDataField::ServiceUuids16 { uuids, .. } => {
+                        uuids.iter().map(|uuid| Uuid::from_u16(*uuid).uu).collect()
+                    }
+                    AdvDataField::ServiceUuids128 { uuids, .. } => uuids.clone(),
+                    _ => vec![],
+                });
+                uuids.any(|advertised| masked_prefix_eq(&advertised, &uuid.uu, &mask))
+            }
+            // APCF service data starts with the little-endian 16-bit uuid.
+            ScanFilterCondition::ServiceData(data, data_mask) => {
+                fields.iter().any(|field| match field {
+                    AdvDataField::ServiceData16 { uuid, data: advertised } => {
+                        let advertised = [&uuid.to_le_bytes()[..], &advertised[..]].concat();
+                        masked_prefix_eq(&advertised, data, data_mask)
+                    }
+                    _ => false,
+                })
+            }
+            ScanFilterCondition::ManufacturerData(company, company_mask, data, data_mask) => {
+                let company_mask = if *company_mask == 0 { 0xffff } else { *company_mask };
+                fields.iter().any(|field| match field {
+                    AdvDataField::ManufacturerData { company_id, data: advertised } => {
+                        company_id & company_mask == company & company_mask
+                            && masked_prefix_eq(advertised, data, data_mask)
+                    }
+                    _ => false,
+                })
+            }
+            ScanFilterCondition::LocalName(name) => fields.iter().any(|field| match field {
+                AdvDataField::LocalName { name: advertised, .. } => advertised == name,
+                _ => false,
+            }),
+        }
     }
 }
 
-#[derive(Debug, FromPrimitive, ToPrimitive, PartialEq, PartialOrd)]
+// Whether |actual| starts with |expected| in the bits set in |mask|. Bytes
+// past the end of |mask| are compared in full.
+fn masked_prefix_eq(actual: &[u8], expected: &[u8], mask: &[u8]) -> bool {
+    actual.len() >= expected.len()
+        && expected.iter().zip(actual).enumerate().all(|(i, (expected, actual))| {
+            let mask = mask.get(i).copied().unwrap_or(0xff);
+            expected & mask == actual & mask
+        })
+}
+
+#[derive(Clone, Copy, Debug, FromPrimitive, ToPrimitive, PartialEq, PartialOrd)]
 #[repr(u32)]
 pub enum GattStatus {
     Success = 0x00,
@@ -447,508 +688,1585 @@ pub enum GattStatus {
     OutOfRange = 0xFF,
 }
 
-#[derive(Debug)]
-pub enum GattClientCallbacks {
-    RegisterClient(i32, i32, Uuid),
-    Connect(i32, i32, i32, RawAddress),
-    Disconnect(i32, i32, i32, RawAddress),
-    SearchComplete(i32, i32),
-    RegisterForNotification(i32, i32, i32, u16),
-    Notify(i32, BtGattNotifyParams),
-    ReadCharacteristic(i32, i32, BtGattReadParams),
-    WriteCharacteristic(i32, i32, u16, u16, *const u8),
-    ReadDescriptor(i32, i32, BtGattReadParams),
-    WriteDescriptor(i32, i32, u16, u16, *const u8),
-    ExecuteWrite(i32, i32),
-    ReadRemoteRssi(i32, RawAddress, i32, i32),
-    ConfigureMtu(i32, i32, i32),
-    Congestion(i32, bool),
-    GetGattDb(i32, Vec<BtGattDbElement>, i32),
-    PhyUpdated(i32, u8, u8, u8),
-    ConnUpdated(i32, u16, u16, u16, u8),
-    ServiceChanged(i32),
-    ReadPhy(i32, RawAddress, u8, u8, u8),
+impl From<u8> for GattStatus {
+    fn from(item: u8) -> Self {
+        GattStatus::from_u8(item).unwrap_or(GattStatus::Error)
+    }
 }
 
-#[derive(Debug)]
-pub enum GattServerCallbacks {
-    RegisterServer(i32, i32, Uuid),
-    Connection(i32, i32, i32, RawAddress),
-    ServiceAdded(i32, i32, Vec<BtGattDbElement>, usize),
-    ServiceStopped(i32, i32, i32),
-    ServiceDeleted(i32, i32, i32),
-    RequestReadCharacteristic(i32, i32, RawAddress, i32, i32, bool),
-    RequestReadDescriptor(i32, i32, RawAddress, i32, i32, bool),
-    RequestWriteCharacteristic(i32, i32, RawAddress, i32, i32, bool, bool, Vec<u8>, usize),
-    RequestWriteDescriptor(i32, i32, RawAddress, i32, i32, bool, bool, Vec<u8>, usize),
-    RequestExecWrite(i32, i32, RawAddress, i32),
-    ResponseConfirmation(i32, i32),
-    IndicationSent(i32, i32),
-    Congestion(i32, bool),
-    MtuChanged(i32, i32),
-    PhyUpdated(i32, u8, u8, u8),
-    ConnUpdated(i32, u16, u16, u16, u8),
+impl std::fmt::Display for GattStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} (0x{:02x})", self, *self as u32)
+    }
 }
 
-pub struct GattClientCallbacksDispatcher {
-    pub dispatch: Box<dyn Fn(GattClientCallbacks) + Send>,
+/// Error of the async |GattClient| operations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GattOpError {
+    /// The request failed, either when issued or as reported by its callback.
+    Status(GattStatus),
+    /// The callback completing the request didn't arrive in time.
+    Timeout,
+    /// Abandoned through |GattClient::cancel_operation|.
+    Cancelled,
 }
 
-pub struct GattServerCallbacksDispatcher {
-    pub dispatch: Box<dyn Fn(GattServerCallbacks) + Send>,
+impl From<GattStatus> for GattOpError {
+    fn from(status: GattStatus) -> Self {
+        GattOpError::Status(status)
+    }
 }
 
-type GattClientCb = Arc<Mutex<GattClientCallbacksDispatcher>>;
-type GattServerCb = Arc<Mutex<GattServerCallbacksDispatcher>>;
+/// Write types for |GattClient::write_characteristic|. Matches GATT_WRITE_* in
+/// stack/include/gatt_api.h. btif has no signed write type; |Signed| is sent
+/// as |NoResponse| together with a signed |GattAuthReq|.
+///
+/// The stack only signs writes on unencrypted LE links, using the local CSRK
+/// distributed while bonding. Without a CSRK the link is encrypted instead, and
+/// on an encrypted link the write goes out as a plain Write Command.
+#[derive(Clone, Copy, Debug, FromPrimitive, ToPrimitive, PartialEq)]
+#[repr(i32)]
+pub enum GattWriteType {
+    NoResponse = 1,
+    Default = 2,
+    Prepare = 3,
+    Signed = 4,
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_register_client_cb -> GattClientCallbacks::RegisterClient,
-    i32, i32, *const Uuid, {
-        let _2 = unsafe { *_2.clone() };
-    }
-);
+/// Authentication requirements. Matches GATT_AUTH_REQ_* in
+/// stack/include/gatt_api.h.
+#[derive(Clone, Copy, Debug, FromPrimitive, ToPrimitive, PartialEq)]
+#[repr(i32)]
+pub enum GattAuthReq {
+    None = 0,
+    NoMitm = 1,
+    Mitm = 2,
+    SignedNoMitm = 3,
+    SignedMitm = 4,
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_open_cb -> GattClientCallbacks::Connect,
-    i32, i32, i32, *const FfiAddress, {
-        let _3 = unsafe { deref_ffi_address!(_3) };
+impl GattAuthReq {
+    fn is_signed(&self) -> bool {
+        *self == GattAuthReq::SignedNoMitm || *self == GattAuthReq::SignedMitm
     }
-);
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_close_cb -> GattClientCallbacks::Disconnect,
-    i32, i32, i32, *const FfiAddress, {
-        let _3 = unsafe { deref_ffi_address!(_3) };
+/// Bearer a single read or write is sent on. Matches tGATT_BEARER in
+/// stack/include/gatt_api.h, where 0 (GATT_BEARER_AUTO) is passed when no
+/// bearer is given and follows the |eatt_support| the client registered with.
+///
+/// Requests of a client on a connection are still sent one at a time, so the
+/// bearer only picks the channel a request goes out on.
+#[derive(Clone, Copy, Debug, FromPrimitive, ToPrimitive, PartialEq)]
+#[repr(i32)]
+pub enum GattBearer {
+    /// An available enhanced bearer, or the unenhanced one if there is none.
+    Enhanced = 1,
+    Unenhanced = 2,
+}
+
+impl GattBearer {
+    fn to_raw(bearer: Option<GattBearer>) -> i32 {
+        bearer.map_or(0, |bearer| bearer as i32)
     }
-);
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_search_complete_cb -> GattClientCallbacks::SearchComplete,
-    i32, i32, {}
-);
+/// LE PHYs as reported by the controller.
+#[derive(Clone, Copy, Debug, FromPrimitive, ToPrimitive, PartialEq)]
+#[repr(u8)]
+pub enum LePhy {
+    Phy1m = 1,
+    Phy2m = 2,
+    PhyCoded = 3,
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_register_for_notification_cb -> GattClientCallbacks::RegisterForNotification,
-    i32, i32, i32, u16, {}
-);
+impl TryFrom<u8> for LePhy {
+    type Error = u8;
 
-cb_variant!(
-    GattClientCb,
-    gc_notify_cb -> GattClientCallbacks::Notify,
-    i32, *const BtGattNotifyParams, {
-        let _1 = unsafe { *_1.clone() };
+    fn try_from(item: u8) -> Result<Self, Self::Error> {
+        LePhy::from_u8(item).ok_or(item)
     }
-);
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_read_characteristic_cb -> GattClientCallbacks::ReadCharacteristic,
-    i32, i32, *mut BtGattReadParams, {
-        let _2 = unsafe { *_2.clone() };
+impl LePhy {
+    /// Bit used for this PHY in the preference masks of |set_preferred_phy|.
+    fn to_mask(&self) -> u8 {
+        1 << (*self as u8 - 1)
     }
-);
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_write_characteristic_cb -> GattClientCallbacks::WriteCharacteristic,
-    i32, i32, u16, u16, *const u8, {}
-);
+/// Transports a remote device was seen on, as returned by
+/// |GattClient::get_device_type|. Matches bt_device_type_t in
+/// include/hardware/bluetooth.h.
+#[derive(Clone, Copy, Debug, FromPrimitive, ToPrimitive, PartialEq)]
+#[repr(i32)]
+pub enum DeviceType {
+    /// The device hasn't been seen yet.
+    Unknown = 0,
+    Bredr = 1,
+    Le = 2,
+    Dual = 3,
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_read_descriptor_cb -> GattClientCallbacks::ReadDescriptor,
-    i32, i32, *const BtGattReadParams, {
-        let _2 = unsafe { *_2.clone() };
-    }
-);
+impl TryFrom<i32> for DeviceType {
+    type Error = i32;
 
-cb_variant!(
-    GattClientCb,
-    gc_write_descriptor_cb -> GattClientCallbacks::WriteDescriptor,
-    i32, i32, u16, u16, *const u8, {}
-);
+    fn try_from(item: i32) -> Result<Self, Self::Error> {
+        DeviceType::from_i32(item).ok_or(item)
+    }
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_execute_write_cb -> GattClientCallbacks::ExecuteWrite,
-    i32, i32, {}
-);
+/// Parameters of an LE connection, as reported by |ConnUpdated|.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConnectionParameters {
+    pub interval_ms: f32,
+    /// Number of connection events the peripheral may skip.
+    pub latency: u16,
+    pub supervision_timeout_ms: u32,
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_read_remote_rssi_cb -> GattClientCallbacks::ReadRemoteRssi,
-    i32, *const FfiAddress, i32, i32, {
-        let _1 = unsafe { deref_ffi_address!(_1) };
+impl ConnectionParameters {
+    /// Converts from controller units: 1.25ms for |interval| and 10ms for
+    /// |timeout|.
+    pub fn from_raw(interval: u16, latency: u16, timeout: u16) -> Self {
+        ConnectionParameters {
+            interval_ms: f32::from(interval) * 1.25,
+            latency,
+            supervision_timeout_ms: u32::from(timeout) * 10,
+        }
     }
-);
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_configure_mtu_cb -> GattClientCallbacks::ConfigureMtu,
-    i32, i32, i32, {}
-);
+/// Connection parameters a peripheral prefers, as exposed by its Peripheral
+/// Preferred Connection Parameters characteristic. Values the peripheral has
+/// no preference for are None.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PreferredConnectionParameters {
+    pub min_interval_ms: Option<f32>,
+    pub max_interval_ms: Option<f32>,
+    /// Number of connection events the peripheral may skip.
+    pub latency: u16,
+    pub supervision_timeout_ms: Option<u32>,
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_congestion_cb -> GattClientCallbacks::Congestion,
-    i32, bool, {}
-);
+impl PreferredConnectionParameters {
+    // Marks a value without preference.
+    const NO_PREFERENCE: u16 = 0xffff;
 
-cb_variant!(
-    GattClientCb,
-    gc_get_gatt_db_cb -> GattClientCallbacks::GetGattDb,
-    i32, *const BtGattDbElement, i32, {
-        let _1 = ptr_to_vec(_1, _2 as usize);
+    /// Parses the characteristic value: four little-endian u16s in controller
+    /// units, like |ConnectionParameters::from_raw|.
+    pub fn parse(value: &[u8]) -> Option<Self> {
+        if value.len() < 8 {
+            return None;
+        }
+
+        let u16_at = |i: usize| u16::from_le_bytes([value[i], value[i + 1]]);
+        let preferred = |raw: u16| Some(raw).filter(|raw| *raw != Self::NO_PREFERENCE);
+        Some(PreferredConnectionParameters {
+            min_interval_ms: preferred(u16_at(0)).map(|raw| f32::from(raw) * 1.25),
+            max_interval_ms: preferred(u16_at(2)).map(|raw| f32::from(raw) * 1.25),
+            latency: u16_at(4),
+            supervision_timeout_ms: preferred(u16_at(6)).map(|raw| u32::from(raw) * 10),
+        })
     }
-);
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_phy_updated_cb -> GattClientCallbacks::PhyUpdated,
-    i32, u8, u8, u8, {}
-);
+bitflags! {
+    /// Preferred coding when transmitting on the coded PHY.
+    pub struct PhyOptions: u16 {
+        const NO_PREFERENCE = 0x0;
+        const CODED_S2 = 0x01;
+        const CODED_S8 = 0x02;
+    }
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_conn_updated_cb -> GattClientCallbacks::ConnUpdated,
-    i32, u16, u16, u16, u8, {}
-);
+bitflags! {
+    /// PHYs to initiate a connection on, see |GattClient::connect_with_phys|.
+    /// Matches the initiating_PHYs of LE Extended Create Connection.
+    pub struct InitiatingPhys: i32 {
+        const LE_1M = 0x01;
+        const LE_2M = 0x02;
+        const LE_CODED = 0x04;
+    }
+}
 
-cb_variant!(
-    GattClientCb,
-    gc_service_changed_cb -> GattClientCallbacks::ServiceChanged,
-    i32, {}
-);
+impl InitiatingPhys {
+    /// 1M, along with 2M if the controller supports it.
+    pub fn default_for(capabilities: &GattCapabilities) -> Self {
+        if capabilities.le_2m_phy {
+            InitiatingPhys::LE_1M | InitiatingPhys::LE_2M
+        } else {
+            InitiatingPhys::LE_1M
+        }
+    }
 
-cb_variant!(
-    GattClientCb,
-    read_phy_callback -> GattClientCallbacks::ReadPhy,
-    i32, ffi::RustRawAddress -> RawAddress, u8, u8, u8, {
-        let _1 = RawAddress { val: _1.address };
+    // Advertisements are only received on the 1M and Coded PHYs, so one of
+    // them is needed to connect at all.
+    fn check(self, capabilities: &GattCapabilities) -> BtStatus {
+        if !self.intersects(InitiatingPhys::LE_1M | InitiatingPhys::LE_CODED) {
+            BtStatus::InvalidParam
+        } else if (self.contains(InitiatingPhys::LE_2M) && !capabilities.le_2m_phy)
+            || (self.contains(InitiatingPhys::LE_CODED) && !capabilities.coded_phy)
+        {
+            BtStatus::Unsupported
+        } else {
+            BtStatus::Success
+        }
     }
-);
+}
+
+bitflags! {
+    /// PHYs to scan on. Matches the scanning_phys of LE Set Extended Scan
+    /// Parameters.
+    pub struct ScanPhyMask: u8 {
+        const LE_1M = 0x01;
+        const LE_CODED = 0x04;
+    }
+}
+
+bitflags! {
+    /// Characteristic properties. Matches GATT_CHAR_PROP_BIT_* in
+    /// stack/include/gatt_api.h.
+    pub struct CharacteristicProperties: u8 {
+        const BROADCAST = 1 << 0;
+        const READ = 1 << 1;
+        const WRITE_NO_RESPONSE = 1 << 2;
+        const WRITE = 1 << 3;
+        const NOTIFY = 1 << 4;
+        const INDICATE = 1 << 5;
+        const SIGNED_WRITE = 1 << 6;
+        const EXT_PROPS = 1 << 7;
+    }
+}
+
+bitflags! {
+    /// Attribute permissions. Matches GATT_PERM_* in stack/include/gatt_api.h.
+    pub struct AttributePermissions: u16 {
+        const READ = 1 << 0;
+        const READ_ENCRYPTED = 1 << 1;
+        const READ_ENC_MITM = 1 << 2;
+        const WRITE = 1 << 4;
+        const WRITE_ENCRYPTED = 1 << 5;
+        const WRITE_ENC_MITM = 1 << 6;
+        const WRITE_SIGNED = 1 << 7;
+        const WRITE_SIGNED_MITM = 1 << 8;
+    }
+}
+
+// GAP service and its Peripheral Preferred Connection Parameters
+// characteristic.
+const GAP_SERVICE_UUID: u16 = 0x1800;
+const PPCP_UUID: u16 = 0x2a04;
+
+/// Client Characteristic Configuration descriptor (0x2902).
+const CCCD_UUID: [u8; 16] = [
+    0x00, 0x00, 0x29, 0x02, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5f, 0x9b, 0x34, 0xfb,
+];
+
+/// Connection parameter presets for |GattClient::set_connection_priority|.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectionPriority {
+    High,
+    Balanced,
+    LowPower,
+}
+
+impl ConnectionPriority {
+    /// Returns (min_interval, max_interval, latency) in controller units, using
+    /// the same presets as the Android framework.
+    fn params(&self) -> (i32, i32, i32) {
+        match self {
+            ConnectionPriority::High => (9, 12, 0),
+            ConnectionPriority::Balanced => (24, 40, 0),
+            ConnectionPriority::LowPower => (80, 100, 2),
+        }
+    }
+}
+
+/// Attribute types of a |BtGattDbElement|. Matches bt_gatt_db_attribute_type_t.
+#[derive(Clone, Copy, Debug, FromPrimitive, ToPrimitive, PartialEq)]
+#[repr(u32)]
+pub enum GattDbElementType {
+    PrimaryService = 0,
+    SecondaryService = 1,
+    IncludedService = 2,
+    Characteristic = 3,
+    Descriptor = 4,
+}
+
+/// Errors reported by |GattDbBuilder|.
+#[derive(Debug, PartialEq)]
+pub enum GattDbBuilderError {
+    /// The service declaration must be added first, and only once.
+    ServiceNotFirst,
+    /// Included services and characteristics must follow a service.
+    NoService,
+    /// Included services must come before the first characteristic.
+    IncludeAfterCharacteristic,
+    /// Included services are identified by the nonzero handle of their
+    /// declaration.
+    InvalidIncludedHandle,
+    /// Descriptors must follow a characteristic.
+    NoCharacteristic,
+    /// A characteristic that notifies or indicates needs a CCCD.
+    MissingCccd,
+}
+
+/// Reasons |Gatt::initialize| can fail.
+#[derive(Debug, PartialEq)]
+pub enum GattInitError {
+    /// A dispatcher for the named callbacks is already registered, usually by
+    /// another |Gatt| that is still alive.
+    DispatcherAlreadyRegistered(&'static str),
+    /// btif init returned the contained nonzero status.
+    BtifInitFailed(i32),
+    /// The scanner shim could not register its callbacks.
+    ScannerRegisterFailed,
+}
+
+fn ensure_unregistered<T: 'static + Clone + Send + Sync>(
+    dispatchers: &DispatchContainer,
+    name: &'static str,
+) -> Result<(), GattInitError> {
+    match dispatchers.get::<T>() {
+        Some(_) => Err(GattInitError::DispatcherAlreadyRegistered(name)),
+        None => Ok(()),
+    }
+}
+
+/// Builds the element list for |GattServer::add_service|. Handles are
+/// assigned by the stack when the service is added, so they are left unset
+/// except for the handle of an included service.
+#[derive(Debug, Default)]
+pub struct GattDbBuilder {
+    elements: Vec<BtGattDbElement>,
+    has_characteristic: bool,
+    // Whether the last characteristic notifies or indicates without a CCCD.
+    missing_cccd: bool,
+}
+
+impl GattDbBuilder {
+    pub fn new() -> Self {
+        GattDbBuilder::default()
+    }
+
+    fn push(&mut self, element_type: GattDbElementType, element: BtGattDbElement) {
+        self.elements.push(BtGattDbElement { type_: element_type as u32, ..element });
+    }
+
+    pub fn add_primary_service(&mut self, uuid: Uuid) -> Result<&mut Self, GattDbBuilderError> {
+        if !self.elements.is_empty() {
+            return Err(GattDbBuilderError::ServiceNotFirst);
+        }
+
+        self.push(
+            GattDbElementType::PrimaryService,
+            BtGattDbElement { uuid, ..Default::default() },
+        );
+        Ok(self)
+    }
+
+    /// Includes the service declared at |handle|, which must already be added.
+    ///
+    /// In |GattServerCallbacks::ServiceAdded| the stack reports the handle of
+    /// the include declaration as |attribute_handle|, while |start_handle|
+    /// keeps |handle|.
+    pub fn add_included_service(&mut self, handle: u16) -> Result<&mut Self, GattDbBuilderError> {
+        if self.elements.is_empty() {
+            return Err(GattDbBuilderError::NoService);
+        }
+        if self.has_characteristic {
+            return Err(GattDbBuilderError::IncludeAfterCharacteristic);
+        }
+        if handle == 0 {
+            return Err(GattDbBuilderError::InvalidIncludedHandle);
+        }
+
+        // The stack looks the included service up by |attribute_handle| and
+        // then overwrites it.
+        self.push(
+            GattDbElementType::IncludedService,
+            BtGattDbElement {
+                attribute_handle: handle,
+                start_handle: handle,
+                ..Default::default()
+            },
+        );
+        Ok(self)
+    }
+
+    pub fn add_characteristic(
+        &mut self,
+        uuid: Uuid,
+        properties: CharacteristicProperties,
+        permissions: AttributePermissions,
+    ) -> Result<&mut Self, GattDbBuilderError> {
+        if self.elements.is_empty() {
+            return Err(GattDbBuilderError::NoService);
+        }
+        if self.missing_cccd {
+            return Err(GattDbBuilderError::MissingCccd);
+        }
+
+        self.has_characteristic = true;
+        self.missing_cccd = properties
+            .intersects(CharacteristicProperties::NOTIFY | CharacteristicProperties::INDICATE);
+        self.push(
+            GattDbElementType::Characteristic,
+            BtGattDbElement {
+                uuid,
+                properties: properties.bits(),
+                permissions: permissions.bits(),
+                ..Default::default()
+            },
+        );
+        Ok(self)
+    }
+
+    /// Adds a descriptor to the most recently added characteristic.
+    pub fn add_descriptor(
+        &mut self,
+        uuid: Uuid,
+        permissions: AttributePermissions,
+    ) -> Result<&mut Self, GattDbBuilderError> {
+        if !self.has_characteristic {
+            return Err(GattDbBuilderError::NoCharacteristic);
+        }
+
+        if uuid.uu == CCCD_UUID {
+            self.missing_cccd = false;
+        }
+        self.push(
+            GattDbElementType::Descriptor,
+            BtGattDbElement { uuid, permissions: permissions.bits(), ..Default::default() },
+        );
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<Vec<BtGattDbElement>, GattDbBuilderError> {
+        if self.missing_cccd {
+            return Err(GattDbBuilderError::MissingCccd);
+        }
+
+        Ok(self.elements)
+    }
+}
+
+/// A descriptor of a |GattCharacteristic|.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GattDescriptor {
+    pub uuid: Uuid,
+    pub handle: u16,
+}
+
+/// A characteristic of a |GattService|, identified by its value handle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GattCharacteristic {
+    pub uuid: Uuid,
+    pub properties: CharacteristicProperties,
+    pub value_handle: u16,
+    pub descriptors: Vec<GattDescriptor>,
+}
+
+/// A service included by a |GattService|. |start_handle| is the handle of the
+/// included service's declaration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GattIncludedService {
+    pub uuid: Uuid,
+    pub handle: u16,
+    pub start_handle: u16,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GattService {
+    pub uuid: Uuid,
+    pub is_primary: bool,
+    pub handle: u16,
+    pub end_handle: u16,
+    pub included_services: Vec<GattIncludedService>,
+    pub characteristics: Vec<GattCharacteristic>,
+}
+
+impl GattService {
+    // Highest handle used by the service's own attributes.
+    fn last_handle(&self) -> u16 {
+        let includes = self.included_services.iter().map(|i| i.handle);
+        let characteristics = self
+            .characteristics
+            .iter()
+            .map(|c| c.descriptors.iter().map(|d| d.handle).fold(c.value_handle, u16::max));
+        includes.chain(characteristics).fold(self.handle, u16::max)
+    }
+}
+
+/// A GATT database as a tree of services, reassembled from the flat element
+/// list reported by |GattClientCallbacks::GetGattDb| and
+/// |GattServerCallbacks::ServiceAdded|.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GattDatabase {
+    pub services: Vec<GattService>,
+}
+
+impl GattDatabase {
+    /// Each element is attached to the closest preceding service or
+    /// characteristic; elements without one are dropped. Services reported
+    /// without an end handle end at their last attribute.
+    pub fn from_elements(elements: Vec<BtGattDbElement>) -> GattDatabase {
+        let mut services: Vec<GattService> = vec![];
+
+        for element in elements {
+            let element_type = match GattDbElementType::from_u32(element.type_) {
+                Some(element_type) => element_type,
+                None => continue,
+            };
+
+            match element_type {
+                GattDbElementType::PrimaryService | GattDbElementType::SecondaryService => {
+                    // The client reports the service handle as |start_handle|,
+                    // the server as |attribute_handle|.
+                    let handle = match element.start_handle {
+                        0 => element.attribute_handle,
+                        start_handle => start_handle,
+                    };
+                    services.push(GattService {
+                        uuid: element.uuid,
+                        is_primary: element_type == GattDbElementType::PrimaryService,
+                        handle,
+                        end_handle: element.end_handle,
+                        included_services: vec![],
+                        characteristics: vec![],
+                    });
+                }
+                GattDbElementType::IncludedService => {
+                    if let Some(service) = services.last_mut() {
+                        service.included_services.push(GattIncludedService {
+                            uuid: element.uuid,
+                            handle: element.attribute_handle,
+                            start_handle: element.start_handle,
+                        });
+                    }
+                }
+                GattDbElementType::Characteristic => {
+                    if let Some(service) = services.last_mut() {
+                        service.characteristics.push(GattCharacteristic {
+                            uuid: element.uuid,
+                            properties: CharacteristicProperties::from_bits_truncate(
+                                element.properties,
+                            ),
+                            value_handle: element.attribute_handle,
+                            descriptors: vec![],
+                        });
+                    }
+                }
+                GattDbElementType::Descriptor => {
+                    if let Some(characteristic) =
+                        services.last_mut().and_then(|s| s.characteristics.last_mut())
+                    {
+                        characteristic.descriptors.push(GattDescriptor {
+                            uuid: element.uuid,
+                            handle: element.attribute_handle,
+                        });
+                    }
+                }
+            }
+        }
+
+        for service in services.iter_mut().filter(|s| s.end_handle == 0) {
+            service.end_handle = service.last_handle();
+        }
+
+        GattDatabase { services }
+    }
+
+    /// The characteristic with |value_handle|.
+    pub fn characteristic(&self, value_handle: u16) -> Option<&GattCharacteristic> {
+        self.services
+            .iter()
+            .flat_map(|s| s.characteristics.iter())
+            .find(|c| c.value_handle == value_handle)
+    }
+
+    /// The first descriptor |desc_uuid| of a characteristic |char_uuid| in a
+    /// service |service_uuid|.
+    pub fn descriptor(
+        &self,
+        service_uuid: &Uuid,
+        char_uuid: &Uuid,
+        desc_uuid: &Uuid,
+    ) -> Option<&GattDescriptor> {
+        self.services
+            .iter()
+            .filter(|s| s.uuid.uu == service_uuid.uu)
+            .flat_map(|s| s.characteristics.iter())
+            .filter(|c| c.uuid.uu == char_uuid.uu)
+            .flat_map(|c| c.descriptors.iter())
+            .find(|d| d.uuid.uu == desc_uuid.uu)
+    }
+}
+
+/// Result of a characteristic or descriptor read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GattReadResult {
+    pub handle: u16,
+    pub status: GattStatus,
+    pub value: Vec<u8>,
+}
+
+impl From<&BtGattReadParams> for GattReadResult {
+    fn from(params: &BtGattReadParams) -> Self {
+        // Don't trust |len| to stay within the inline value buffer.
+        let len = usize::from(params.value.len).min(params.value.value.len());
+        GattReadResult {
+            handle: params.handle,
+            status: GattStatus::from(params.status),
+            value: params.value.value[..len].to_vec(),
+        }
+    }
+}
+
+/// Notification or indication received from a remote server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GattNotification {
+    pub addr: RawAddress,
+    pub handle: u16,
+    pub is_notify: bool,
+    pub value: Vec<u8>,
+}
+
+impl From<&BtGattNotifyParams> for GattNotification {
+    fn from(params: &BtGattNotifyParams) -> Self {
+        let len = usize::from(params.len).min(params.value.len());
+        GattNotification {
+            addr: RawAddress { val: params.bda.address },
+            handle: params.handle,
+            is_notify: params.is_notify != 0,
+            value: params.value[..len].to_vec(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum GattClientCallbacks {
+    RegisterClient(i32, i32, Uuid),
+    Connect(i32, i32, i32, RawAddress),
+    Disconnect(i32, i32, i32, RawAddress),
+    SearchComplete(i32, i32),
+    RegisterForNotification(i32, i32, i32, u16),
+    Notify(i32, GattNotification),
+    ReadCharacteristic(i32, i32, GattReadResult),
+    WriteCharacteristic(i32, i32, u16, Vec<u8>),
+    ReadDescriptor(i32, i32, GattReadResult),
+    WriteDescriptor(i32, i32, u16, u16, *const u8),
+    ExecuteWrite(i32, i32),
+    ReadRemoteRssi(i32, RawAddress, i8, i32),
+    ConfigureMtu(i32, i32, i32),
+    Congestion(i32, bool),
+    GetGattDb(i32, Vec<BtGattDbElement>, i32),
+    PhyUpdated(i32, Result<LePhy, u8>, Result<LePhy, u8>, u8),
+    /// Params: Conn Id, Parameters, Status
+    ConnUpdated(i32, ConnectionParameters, GattStatus),
+    ServiceChanged(i32),
+    ReadPhy(i32, RawAddress, Result<LePhy, u8>, Result<LePhy, u8>, u8),
+    ReadMultiple(i32, i32, Vec<u8>),
+    /// Params: Conn Id, Subrate Factor, Latency, Continuation Number, Timeout,
+    /// Status. Also reports a |GattClient::subrate_request| the controller
+    /// rejected, with zeroed parameters.
+    SubrateChanged(i32, u16, u16, u16, u16, u8),
+    // Dispatched instead of |ReadCharacteristic| and |WriteCharacteristic| for
+    // requests issued through |GattClient::read_characteristic_with_id| and
+    // |GattClient::write_characteristic_with_id|.
+    ReadCharacteristicWithId(GattOpId, i32, i32, GattReadResult),
+    WriteCharacteristicWithId(GattOpId, i32, i32, u16, Vec<u8>),
+    MtuChanged(i32, i32),
+    // Dispatched instead of |GetGattDb| for the database read by the automatic
+    // rediscovery enabled with |GattClient::set_auto_rediscover|.
+    DatabaseRediscovered(i32, Vec<BtGattDbElement>),
+}
+
+#[derive(Clone, Debug)]
+pub enum GattServerCallbacks {
+    RegisterServer(i32, i32, Uuid),
+    Connection(i32, i32, i32, RawAddress),
+    ServiceAdded(i32, i32, Vec<BtGattDbElement>, usize),
+    ServiceStopped(i32, i32, i32),
+    ServiceDeleted(i32, i32, i32),
+    RequestReadCharacteristic(i32, i32, RawAddress, i32, i32, bool),
+    RequestReadDescriptor(i32, i32, RawAddress, i32, i32, bool),
+    RequestWriteCharacteristic(i32, i32, RawAddress, i32, i32, bool, bool, Vec<u8>, usize),
+    RequestWriteDescriptor(i32, i32, RawAddress, i32, i32, bool, bool, Vec<u8>, usize),
+    RequestExecWrite(i32, i32, RawAddress, ExecuteWriteMode),
+    ResponseConfirmation(i32, i32),
+    IndicationSent(i32, i32),
+    Congestion(i32, bool),
+    MtuChanged(i32, i32),
+    PhyUpdated(i32, Result<LePhy, u8>, Result<LePhy, u8>, u8),
+    /// Params: Conn Id, Parameters, Status
+    ConnUpdated(i32, ConnectionParameters, GattStatus),
+    ReadPhy(i32, RawAddress, Result<LePhy, u8>, Result<LePhy, u8>, u8),
+}
+
+pub struct GattClientCallbacksDispatcher {
+    pub dispatch: Box<dyn Fn(GattClientCallbacks) + Send>,
+}
+
+pub struct GattServerCallbacksDispatcher {
+    pub dispatch: Box<dyn Fn(GattServerCallbacks) + Send>,
+}
+
+type GattClientCb = Arc<Mutex<GattClientCallbacksDispatcher>>;
+type GattServerCb = Arc<Mutex<GattServerCallbacksDispatcher>>;
 
 cb_variant!(
-    GattServerCb,
-    gs_register_server_cb -> GattServerCallbacks::RegisterServer,
+    GattClientCb,
+    gc_register_client_cb -> GattClientCallbacks::RegisterClient,
     i32, i32, *const Uuid, {
         let _2 = unsafe { *_2.clone() };
     }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_connection_cb -> GattServerCallbacks::Connection,
+    GattClientCb,
+    gc_open_cb -> GattClientCallbacks::Connect,
     i32, i32, i32, *const FfiAddress, {
         let _3 = unsafe { deref_ffi_address!(_3) };
     }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_service_added_cb -> GattServerCallbacks::ServiceAdded,
-    i32, i32, *const BtGattDbElement, usize, {
-        let _2 = ptr_to_vec(_2, _3);
+    GattClientCb,
+    gc_close_cb -> GattClientCallbacks::Disconnect,
+    i32, i32, i32, *const FfiAddress, {
+        let _3 = unsafe { deref_ffi_address!(_3) };
     }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_service_stopped_cb -> GattServerCallbacks::ServiceStopped,
-    i32, i32, i32, {}
+    GattClientCb,
+    gc_search_complete_cb -> GattClientCallbacks::SearchComplete,
+    i32, i32, {}
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_service_deleted_cb -> GattServerCallbacks::ServiceDeleted,
-    i32, i32, i32, {}
+    GattClientCb,
+    gc_register_for_notification_cb -> GattClientCallbacks::RegisterForNotification,
+    i32, i32, i32, u16, {}
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_request_read_characteristic_cb -> GattServerCallbacks::RequestReadCharacteristic,
-    i32, i32, *const FfiAddress, i32, i32, bool, {
-        let _2 = unsafe { deref_ffi_address!(_2) };
+    GattClientCb,
+    gc_notify_cb -> GattClientCallbacks::Notify,
+    i32, *const BtGattNotifyParams, {
+        let _1 = GattNotification::from(unsafe { &*_1 });
     }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_request_read_descriptor_cb -> GattServerCallbacks::RequestReadDescriptor,
-    i32, i32, *const FfiAddress, i32, i32, bool, {
-        let _2 = unsafe { deref_ffi_address!(_2) };
+    GattClientCb,
+    gc_read_characteristic_cb -> GattClientCallbacks::ReadCharacteristic,
+    i32, i32, *mut BtGattReadParams, {
+        // btif owns |_2| only for the duration of the callback.
+        let _2 = GattReadResult::from(unsafe { &*_2 });
     }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_request_write_characteristic_cb -> GattServerCallbacks::RequestWriteCharacteristic,
-    i32, i32, *const FfiAddress, i32, i32, bool, bool, *const u8, usize, {
-        let _2 = unsafe { deref_ffi_address!(_2) };
-        let _7 = ptr_to_vec(_7, _8);
+    GattClientCb,
+    gc_write_characteristic_cb -> GattClientCallbacks::WriteCharacteristic,
+    i32, i32, u16, u16 -> _, *const u8, {
+        let _4 = ptr_to_vec(_4, _3 as usize);
     }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_request_write_descriptor_cb -> GattServerCallbacks::RequestWriteDescriptor,
-    i32, i32, *const FfiAddress, i32, i32, bool, bool, *const u8, usize, {
-        let _2 = unsafe { deref_ffi_address!(_2) };
-        let _7 = ptr_to_vec(_7, _8);
+    GattClientCb,
+    gc_read_descriptor_cb -> GattClientCallbacks::ReadDescriptor,
+    i32, i32, *const BtGattReadParams, {
+        let _2 = GattReadResult::from(unsafe { &*_2 });
     }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_request_exec_write_cb -> GattServerCallbacks::RequestExecWrite,
-    i32, i32, *const FfiAddress, i32, {
-        let _2 = unsafe { deref_ffi_address!(_2) };
-    }
+    GattClientCb,
+    gc_write_descriptor_cb -> GattClientCallbacks::WriteDescriptor,
+    i32, i32, u16, u16, *const u8, {}
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_response_confirmation_cb -> GattServerCallbacks::ResponseConfirmation,
+    GattClientCb,
+    gc_execute_write_cb -> GattClientCallbacks::ExecuteWrite,
     i32, i32, {}
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_indication_sent_cb -> GattServerCallbacks::IndicationSent,
-    i32, i32, {}
+    GattClientCb,
+    gc_read_remote_rssi_cb -> GattClientCallbacks::ReadRemoteRssi,
+    i32, *const FfiAddress, i32, i32, {
+        let _1 = unsafe { deref_ffi_address!(_1) };
+        let _2 = _2 as i8;
+    }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_congestion_cb -> GattServerCallbacks::Congestion,
+    GattClientCb,
+    gc_configure_mtu_cb -> GattClientCallbacks::ConfigureMtu,
+    i32, i32, i32, {}
+);
+
+cb_variant!(
+    GattClientCb,
+    gc_congestion_cb -> GattClientCallbacks::Congestion,
     i32, bool, {}
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_mtu_changed_cb -> GattServerCallbacks::MtuChanged,
-    i32, i32, {}
+    GattClientCb,
+    gc_get_gatt_db_cb -> GattClientCallbacks::GetGattDb,
+    i32, *const BtGattDbElement, i32, {
+        let _1 = ptr_to_vec(_1, _2 as usize);
+    }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_phy_updated_cb -> GattServerCallbacks::PhyUpdated,
-    i32, u8, u8, u8, {}
+    GattClientCb,
+    gc_phy_updated_cb -> GattClientCallbacks::PhyUpdated,
+    i32, u8, u8, u8, {
+        let _1 = LePhy::try_from(_1);
+        let _2 = LePhy::try_from(_2);
+    }
 );
 
 cb_variant!(
-    GattServerCb,
-    gs_conn_updated_cb -> GattServerCallbacks::ConnUpdated,
-    i32, u16, u16, u16, u8, {}
+    GattClientCb,
+    gc_conn_updated_cb -> GattClientCallbacks::ConnUpdated,
+    i32, u16, u16 -> _, u16 -> _, u8 -> GattStatus, {
+        let _1 = ConnectionParameters::from_raw(_1, _2, _3);
+    }
 );
 
-/// Scanning callbacks used by the GD implementation of BleScannerInterface.
-/// These callbacks should be registered using |RegisterCallbacks| on
-/// `BleScannerInterface`.
-#[derive(Debug)]
-pub enum GattScannerCallbacks {
-    OnScannerRegistered(Uuid, u8, u8),
-    OnSetScannerParameterComplete(u8, u8),
-    OnScanResult(u16, u8, RawAddress, u8, u8, u8, i8, i8, u16, Vec<u8>),
-    OnTrackAdvFoundLost(AdvertisingTrackInfo),
-    OnBatchScanReports(i32, i32, i32, i32, Vec<u8>),
-    OnBatchScanThresholdCrossed(i32),
-}
+cb_variant!(
+    GattClientCb,
+    gc_subrate_chg_cb -> GattClientCallbacks::SubrateChanged,
+    i32, u16, u16, u16, u16, u8, {}
+);
 
-pub struct GattScannerCallbacksDispatcher {
-    pub dispatch: Box<dyn Fn(GattScannerCallbacks) + Send>,
-}
+cb_variant!(
+    GattClientCb,
+    gc_mtu_changed_cb -> GattClientCallbacks::MtuChanged,
+    i32, i32, {}
+);
 
-type GDScannerCb = Arc<Mutex<GattScannerCallbacksDispatcher>>;
+cb_variant!(
+    GattClientCb,
+    gc_service_changed_cb -> GattClientCallbacks::ServiceChanged,
+    i32, {}
+);
 
 cb_variant!(
-    GDScannerCb,
-    gdscan_on_scanner_registered -> GattScannerCallbacks::OnScannerRegistered,
-    *const i8, u8, u8, {
-        let _0 = unsafe { *(_0 as *const Uuid).clone() };
+    GattClientCb,
+    gc_read_multiple_cb -> GattClientCallbacks::ReadMultiple,
+    i32, i32, *const u8, u16 -> _, {
+        let _2 = ptr_to_vec(_2, _3 as usize);
     }
 );
 
 cb_variant!(
-    GDScannerCb,
-    gdscan_on_set_scanner_parameter_complete -> GattScannerCallbacks::OnSetScannerParameterComplete,
-    u8, u8
+    GattClientCb,
+    read_phy_callback -> GattClientCallbacks::ReadPhy,
+    i32, ffi::RustRawAddress -> RawAddress, u8, u8, u8, {
+        let _1 = RawAddress { val: _1.address };
+        let _2 = LePhy::try_from(_2);
+        let _3 = LePhy::try_from(_3);
+    }
 );
 
 cb_variant!(
-    GDScannerCb,
-    gdscan_on_scan_result -> GattScannerCallbacks::OnScanResult,
-    u16, u8, *const i8, u8, u8, u8, i8, i8, u16, *const u8, usize -> _, {
-        // Convert FfiAddress to RawAddress
-        let _2 = unsafe { deref_ffi_address!(_2) };
+    GattServerCb,
+    gs_register_server_cb -> GattServerCallbacks::RegisterServer,
+    i32, i32, *const Uuid, {
+        let _2 = unsafe { *_2.clone() };
+    }
+);
 
-        // Convert the vec! at the end. Since this cb is being called via cxx
-        // ffi, we do the vector separation at the cxx layer. The usize is consumed during
-        // conversion.
-        let _9 : Vec<u8> = ptr_to_vec(_9, _10);
+cb_variant!(
+    GattServerCb,
+    gs_connection_cb -> GattServerCallbacks::Connection,
+    i32, i32, i32, *const FfiAddress, {
+        let _3 = unsafe { deref_ffi_address!(_3) };
     }
 );
 
 cb_variant!(
-    GDScannerCb,
-    gdscan_on_track_adv_found_lost -> GattScannerCallbacks::OnTrackAdvFoundLost,
-    AdvertisingTrackInfo);
+    GattServerCb,
+    gs_service_added_cb -> GattServerCallbacks::ServiceAdded,
+    i32, i32, *const BtGattDbElement, usize, {
+        let _2 = ptr_to_vec(_2, _3);
+    }
+);
 
 cb_variant!(
-    GDScannerCb,
-    gdscan_on_batch_scan_reports -> GattScannerCallbacks::OnBatchScanReports,
-    i32, i32, i32, i32, *const u8, usize -> _, {
-        // Write the vector to the output and consume the usize in the input.
-        let _4 : Vec<u8> = ptr_to_vec(_4, _5);
+    GattServerCb,
+    gs_service_stopped_cb -> GattServerCallbacks::ServiceStopped,
+    i32, i32, i32, {}
+);
+
+cb_variant!(
+    GattServerCb,
+    gs_service_deleted_cb -> GattServerCallbacks::ServiceDeleted,
+    i32, i32, i32, {}
+);
+
+cb_variant!(
+    GattServerCb,
+    gs_request_read_characteristic_cb -> GattServerCallbacks::RequestReadCharacteristic,
+    i32, i32, *const FfiAddress, i32, i32, bool, {
+        let _2 = unsafe { deref_ffi_address!(_2) };
     }
 );
 
-cb_variant!(GDScannerCb, gdscan_on_batch_scan_threshold_crossed -> GattScannerCallbacks::OnBatchScanThresholdCrossed, i32);
+cb_variant!(
+    GattServerCb,
+    gs_request_read_descriptor_cb -> GattServerCallbacks::RequestReadDescriptor,
+    i32, i32, *const FfiAddress, i32, i32, bool, {
+        let _2 = unsafe { deref_ffi_address!(_2) };
+    }
+);
 
-/// In-band callbacks from the various |BleScannerInterface| methods. Rather than
-/// store closures for each registered callback, we instead bind and return an
-/// identifier for the callback instead (such as scanner id or Uuid).
-#[derive(Debug)]
-pub enum GattScannerInbandCallbacks {
-    /// Params: App Uuid, Scanner Id, BTM Status
-    RegisterCallback(Uuid, u8, u8),
+cb_variant!(
+    GattServerCb,
+    gs_request_write_characteristic_cb -> GattServerCallbacks::RequestWriteCharacteristic,
+    i32, i32, *const FfiAddress, i32, i32, bool, bool, *const u8, usize, {
+        let _2 = unsafe { deref_ffi_address!(_2) };
+        let _7 = ptr_to_vec(_7, _8);
+    }
+);
 
-    /// Params: Scanner Id, BTM Status
-    StatusCallback(u8, u8),
+cb_variant!(
+    GattServerCb,
+    gs_request_write_descriptor_cb -> GattServerCallbacks::RequestWriteDescriptor,
+    i32, i32, *const FfiAddress, i32, i32, bool, bool, *const u8, usize, {
+        let _2 = unsafe { deref_ffi_address!(_2) };
+        let _7 = ptr_to_vec(_7, _8);
+    }
+);
 
-    /// Params: Action (enable/disable), BTM Status
-    EnableCallback(u8, u8),
+cb_variant!(
+    GattServerCb,
+    gs_request_exec_write_cb -> GattServerCallbacks::RequestExecWrite,
+    i32, i32, *const FfiAddress, i32 -> ExecuteWriteMode, {
+        let _2 = unsafe { deref_ffi_address!(_2) };
+    }
+);
 
-    /// Params: Scanner Id, Available Space, Action Type, BTM Status
-    FilterParamSetupCallback(u8, u8, u8, u8),
+cb_variant!(
+    GattServerCb,
+    gs_response_confirmation_cb -> GattServerCallbacks::ResponseConfirmation,
+    i32, i32, {}
+);
 
-    /// Params: Filter Index, Filter Type, Available Space, Action, BTM Status
-    FilterConfigCallback(u8, u8, u8, u8, u8),
+cb_variant!(
+    GattServerCb,
+    gs_indication_sent_cb -> GattServerCallbacks::IndicationSent,
+    i32, i32, {}
+);
 
-    /// Params: Status, Sync Handle, Advertising Sid, Address Type, Address, Phy, Interval
-    StartSyncCallback(u8, u16, u8, u8, RawAddress, u8, u16),
+cb_variant!(
+    GattServerCb,
+    gs_congestion_cb -> GattServerCallbacks::Congestion,
+    i32, bool, {}
+);
 
-    /// Params: Sync Handle, Tx Power, RSSI, Status, Data
-    SyncReportCallback(u16, i8, i8, u8, Vec<u8>),
+cb_variant!(
+    GattServerCb,
+    gs_mtu_changed_cb -> GattServerCallbacks::MtuChanged,
+    i32, i32, {}
+);
 
-    /// Params: Sync Handle
-    SyncLostCallback(u16),
+cb_variant!(
+    GattServerCb,
+    gs_phy_updated_cb -> GattServerCallbacks::PhyUpdated,
+    i32, u8, u8, u8, {
+        let _1 = LePhy::try_from(_1);
+        let _2 = LePhy::try_from(_2);
+    }
+);
+
+cb_variant!(
+    GattServerCb,
+    gs_conn_updated_cb -> GattServerCallbacks::ConnUpdated,
+    i32, u16, u16 -> _, u16 -> _, u8 -> GattStatus, {
+        let _1 = ConnectionParameters::from_raw(_1, _2, _3);
+    }
+);
+
+cb_variant!(
+    GattServerCb,
+    server_read_phy_callback -> GattServerCallbacks::ReadPhy,
+    i32, ffi::RustRawAddress -> RawAddress, u8, u8, u8, {
+        let _1 = RawAddress { val: _1.address };
+        let _2 = LePhy::try_from(_2);
+        let _3 = LePhy::try_from(_3);
+    }
+);
 
-    /// Params: Status, Address
-    SyncTransferCallback(u8, RawAddress),
+/// Completeness of the data in an advertising report.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdvertisingDataStatus {
+    Complete,
+    /// More data follows in later reports.
+    Incomplete,
+    /// Incomplete, and no more data follows.
+    Truncated,
+    Reserved,
 }
 
-pub struct GattScannerInbandCallbacksDispatcher {
-    pub dispatch: Box<dyn Fn(GattScannerInbandCallbacks) + Send>,
+impl From<u8> for AdvertisingDataStatus {
+    fn from(item: u8) -> Self {
+        match item {
+            0 => AdvertisingDataStatus::Complete,
+            1 => AdvertisingDataStatus::Incomplete,
+            2 => AdvertisingDataStatus::Truncated,
+            _ => AdvertisingDataStatus::Reserved,
+        }
+    }
 }
 
-type GDScannerInbandCb = Arc<Mutex<GattScannerInbandCallbacksDispatcher>>;
+/// Event type of an advertising report. The bits are those of the
+/// LE Extended Advertising Report event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdvertisingEventType(pub u16);
 
-cb_variant!(GDScannerInbandCb, gdscan_register_callback -> GattScannerInbandCallbacks::RegisterCallback,
-    ffi::RustUuid -> Uuid, u8, u8);
+impl AdvertisingEventType {
+    pub fn is_connectable(&self) -> bool {
+        self.0 & 0x0001 != 0
+    }
 
-cb_variant!(GDScannerInbandCb, gdscan_status_callback -> GattScannerInbandCallbacks::StatusCallback, u8, u8);
-cb_variant!(GDScannerInbandCb, gdscan_enable_callback -> GattScannerInbandCallbacks::EnableCallback, u8, u8);
-cb_variant!(GDScannerInbandCb,
-    gdscan_filter_param_setup_callback -> GattScannerInbandCallbacks::FilterParamSetupCallback,
-    u8, u8, u8, u8);
-cb_variant!(GDScannerInbandCb,
-    gdscan_filter_config_callback -> GattScannerInbandCallbacks::FilterConfigCallback,
-    u8, u8, u8, u8, u8);
-cb_variant!(GDScannerInbandCb,
-gdscan_start_sync_callback -> GattScannerInbandCallbacks::StartSyncCallback,
-u8, u16, u8, u8, *const ffi::RustRawAddress, u8, u16, {
-    let _4 = unsafe { deref_ffi_address!(_4) };
-});
-cb_variant!(GDScannerInbandCb,
-gdscan_sync_report_callback -> GattScannerInbandCallbacks::SyncReportCallback,
-u16, i8, i8, u8, *const u8, usize -> _, {
-    let _4 = ptr_to_vec(_4, _5 as usize);
-});
-cb_variant!(GDScannerInbandCb, gdscan_sync_lost_callback -> GattScannerInbandCallbacks::SyncLostCallback, u16);
-cb_variant!(GDScannerInbandCb, gdscan_sync_transfer_callback -> GattScannerInbandCallbacks::SyncTransferCallback,
-u8, *const ffi::RustRawAddress, {
-    let _1 = unsafe { deref_ffi_address!(_1) };
-});
+    pub fn is_scannable(&self) -> bool {
+        self.0 & 0x0002 != 0
+    }
 
-/// Advertising callbacks used by the GD implementation of BleAdvertiserInterface.
-/// These callbacks should be registered using |RegisterCallbacks| on
-/// `BleAdvertiser`.
-#[derive(Debug)]
-pub enum GattAdvCallbacks {
-    /// Params: Reg Id, Advertiser Id, Tx Power, Status
-    OnAdvertisingSetStarted(i32, u8, i8, u8),
+    pub fn is_directed(&self) -> bool {
+        self.0 & 0x0004 != 0
+    }
 
-    /// Params: Advertiser Id, Enabled, Status
-    OnAdvertisingEnabled(u8, bool, u8),
+    pub fn is_scan_response(&self) -> bool {
+        self.0 & 0x0008 != 0
+    }
 
-    /// Params: Advertiser Id, Status
-    OnAdvertisingDataSet(u8, u8),
+    /// Whether the report is of a legacy rather than extended advertisement.
+    pub fn is_legacy(&self) -> bool {
+        self.0 & 0x0010 != 0
+    }
 
-    /// Params: Advertiser Id, Status
-    OnScanResponseDataSet(u8, u8),
+    pub fn data_status(&self) -> AdvertisingDataStatus {
+        AdvertisingDataStatus::from(((self.0 >> 5) & 0x3) as u8)
+    }
+}
 
-    /// Params: Advertiser Id, Tx Power, Status
-    OnAdvertisingParametersUpdated(u8, i8, u8),
+/// Address type of an advertiser, as reported in advertising reports and
+/// advertisement tracking.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BleAddressType {
+    Public,
+    Random,
+    /// A public identity address, resolved from a resolvable private address.
+    PublicIdentity,
+    /// A random static identity address, resolved from a resolvable private
+    /// address.
+    RandomIdentity,
+    /// An advertisement without an address.
+    Anonymous,
+    /// A value the specification doesn't define.
+    Unknown(u8),
+}
 
-    /// Params: Advertiser Id, Status
-    OnPeriodicAdvertisingParametersUpdated(u8, u8),
+impl From<u8> for BleAddressType {
+    fn from(item: u8) -> Self {
+        match item {
+            0x00 => BleAddressType::Public,
+            0x01 => BleAddressType::Random,
+            0x02 => BleAddressType::PublicIdentity,
+            0x03 => BleAddressType::RandomIdentity,
+            0xFF => BleAddressType::Anonymous,
+            other => BleAddressType::Unknown(other),
+        }
+    }
+}
 
-    /// Params: Advertiser Id, Status
-    OnPeriodicAdvertisingDataSet(u8, u8),
+impl From<BleAddressType> for u8 {
+    fn from(item: BleAddressType) -> Self {
+        match item {
+            BleAddressType::Public => 0x00,
+            BleAddressType::Random => 0x01,
+            BleAddressType::PublicIdentity => 0x02,
+            BleAddressType::RandomIdentity => 0x03,
+            BleAddressType::Anonymous => 0xFF,
+            BleAddressType::Unknown(other) => other,
+        }
+    }
+}
 
-    /// Params: Advertiser Id, Enabled, Status
-    OnPeriodicAdvertisingEnabled(u8, bool, u8),
+impl BleAddressType {
+    /// Whether the controller resolved the address to the identity address of
+    /// a bonded device.
+    pub fn is_identity(&self) -> bool {
+        matches!(self, BleAddressType::PublicIdentity | BleAddressType::RandomIdentity)
+    }
+}
 
-    /// Params: Advertiser Id, Address Type, Address
-    OnOwnAddressRead(u8, u8, RawAddress),
+/// An advertising report received while scanning.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScanResult {
+    pub event_type: AdvertisingEventType,
+    pub addr_type: BleAddressType,
+    pub address: RawAddress,
+    pub primary_phy: u8,
+    pub secondary_phy: u8,
+    pub advertising_sid: u8,
+    pub tx_power: i8,
+    pub rssi: i8,
+    pub periodic_adv_interval: u16,
+    pub adv_data: Vec<u8>,
 }
 
-pub struct GattAdvCallbacksDispatcher {
-    pub dispatch: Box<dyn Fn(GattAdvCallbacks) + Send>,
+// Scan results buffered by |BleScanner::set_coalescing|.
+#[derive(Default)]
+struct ScanCoalescer {
+    interval: Option<Duration>,
+    results: Vec<ScanResult>,
+    // Bumped whenever the interval changes, so that the timer of the previous
+    // interval stops.
+    generation: u64,
 }
 
-type GDAdvCb = Arc<Mutex<GattAdvCallbacksDispatcher>>;
+impl ScanCoalescer {
+    // Replaces the result buffered for the same advertiser if |result| is
+    // stronger.
+    fn push(&mut self, result: ScanResult) {
+        match self.results.iter_mut().find(|r| r.address == result.address) {
+            Some(buffered) if buffered.rssi < result.rssi => *buffered = result,
+            Some(_) => (),
+            None => self.results.push(result),
+        }
+    }
 
-cb_variant!(GDAdvCb,
-    gdadv_on_advertising_set_started -> GattAdvCallbacks::OnAdvertisingSetStarted,
-    i32, u8, i8, u8);
-cb_variant!(GDAdvCb,
-    gdadv_on_advertising_enabled -> GattAdvCallbacks::OnAdvertisingEnabled,
-    u8, bool, u8);
-cb_variant!(GDAdvCb,
-    gdadv_on_advertising_data_set -> GattAdvCallbacks::OnAdvertisingDataSet,
-    u8, u8);
-cb_variant!(GDAdvCb,
-    gdadv_on_scan_response_data_set -> GattAdvCallbacks::OnScanResponseDataSet,
-    u8, u8);
-cb_variant!(GDAdvCb,
-    gdadv_on_advertising_parameters_updated -> GattAdvCallbacks::OnAdvertisingParametersUpdated,
-    u8, i8, u8);
-cb_variant!(GDAdvCb,
-    gdadv_on_periodic_advertising_parameters_updated -> GattAdvCallbacks::OnPeriodicAdvertisingParametersUpdated,
-    u8, u8);
-cb_variant!(GDAdvCb,
-    gdadv_on_periodic_advertising_data_set -> GattAdvCallbacks::OnPeriodicAdvertisingDataSet,
-    u8, u8);
-cb_variant!(GDAdvCb,
-    gdadv_on_periodic_advertising_enabled -> GattAdvCallbacks::OnPeriodicAdvertisingEnabled,
-    u8, bool, u8);
-cb_variant!(GDAdvCb,
-gdadv_on_own_address_read -> GattAdvCallbacks::OnOwnAddressRead, u8, u8,
-*const ffi::RustRawAddress, {
-    let _2 = unsafe { deref_ffi_address!(_2) };
-});
+    fn take(&mut self) -> Vec<ScanResult> {
+        std::mem::take(&mut self.results)
+    }
+}
 
-#[derive(Debug)]
-pub enum GattAdvInbandCallbacks {
-    /// Params: Advertiser Id, Status
-    /// StatusCallback isn't implemented because we always want advertiser id.
+/// Periodic advertising data received on a periodic sync.
+///
+/// The controller splits long data across several reports. These are
+/// reassembled before being dispatched, so |data_status| is either
+/// |Complete| or, if the controller gave up on the rest of the data,
+/// |Truncated| with whatever was received.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeriodicAdvReport {
+    pub sync_handle: u16,
+    /// Of the last fragment.
+    pub tx_power: i8,
+    /// Of the last fragment.
+    pub rssi: i8,
+    pub data_status: AdvertisingDataStatus,
+    pub data: Vec<u8>,
+}
+
+// Data of the periodic advertising trains still being received, keyed by
+// sync_handle.
+#[derive(Default)]
+struct PeriodicReportAssembler {
+    partial: HashMap<u16, Vec<u8>>,
+}
+
+impl PeriodicReportAssembler {
+    // Returns the reassembled report once |report| completes its train.
+    fn push(&mut self, mut report: PeriodicAdvReport) -> Option<PeriodicAdvReport> {
+        match report.data_status {
+            AdvertisingDataStatus::Incomplete => {
+                self.partial.entry(report.sync_handle).or_default().extend(report.data);
+                None
+            }
+            _ => {
+                if let Some(mut data) = self.partial.remove(&report.sync_handle) {
+                    data.extend(report.data);
+                    report.data = data;
+                }
+                Some(report)
+            }
+        }
+    }
+
+    fn lost(&mut self, sync_handle: u16) {
+        self.partial.remove(&sync_handle);
+    }
+}
+
+/// An advertiser found or lost by a scan filter with advertisement tracking.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackingEvent {
+    pub scanner_id: u8,
+    pub filter_index: u8,
+    /// Whether the advertiser was found, rather than lost.
+    pub found: bool,
+    pub address: RawAddress,
+    pub address_type: BleAddressType,
+    pub tx_power: i8,
+    pub rssi: i8,
+    pub adv_data: Vec<u8>,
+    pub scan_response: Vec<u8>,
+}
+
+// |advertiser_state| of an advertiser that was found. Lost ones report 0x01.
+const ADVERTISER_STATE_FOUND: u8 = 0x00;
+
+impl From<AdvertisingTrackInfo> for TrackingEvent {
+    fn from(info: AdvertisingTrackInfo) -> Self {
+        TrackingEvent {
+            scanner_id: info.scanner_id,
+            filter_index: info.filter_index,
+            found: info.advertiser_state == ADVERTISER_STATE_FOUND,
+            address: RawAddress { val: info.advertiser_address.address },
+            address_type: BleAddressType::from(info.advertiser_address_type),
+            tx_power: info.tx_power as i8,
+            rssi: info.rssi,
+            adv_data: info.adv_packet,
+            scan_response: info.scan_response,
+        }
+    }
+}
+
+/// Path-loss exponent of free space, used by |estimate_distance|.
+pub const DEFAULT_PATH_LOSS_EXPONENT: f32 = 2.0;
+
+// Free-space path loss at 1m on 2.4GHz, in dB.
+const PATH_LOSS_AT_1M: f32 = 41.0;
+
+// Bounds of |estimate_distance_with_exponent|, in meters.
+const MIN_ESTIMATED_DISTANCE: f32 = 0.1;
+const MAX_ESTIMATED_DISTANCE: f32 = 100.0;
+
+/// Estimates the distance in meters to an advertiser from the |rssi| of its
+/// advertisement and the |tx_power| it advertised, using the log-distance
+/// path-loss model with |DEFAULT_PATH_LOSS_EXPONENT|.
+///
+/// |tx_power| is the |tx_power| of a |ScanResult| or |TrackingEvent| and must
+/// not be 127, which means the advertiser didn't report it.
+pub fn estimate_distance(rssi: i8, tx_power: i8) -> f32 {
+    estimate_distance_with_exponent(rssi, tx_power, DEFAULT_PATH_LOSS_EXPONENT)
+}
+
+/// Like |estimate_distance|, with the path-loss exponent of the environment:
+/// around 2 in open space, 2.7 to 3.5 in buildings and up to 5 with many
+/// obstacles. Exponents that aren't positive use the default.
+///
+/// The result is a rough indication only. RSSI fluctuates by several dB
+/// between packets and is skewed by antennas, orientation and bodies in the
+/// way, so estimates beyond a few meters can easily be off by a factor of
+/// two. The result is clamped to 0.1m to 100m.
+pub fn estimate_distance_with_exponent(rssi: i8, tx_power: i8, path_loss_exponent: f32) -> f32 {
+    let exponent =
+        if path_loss_exponent > 0.0 { path_loss_exponent } else { DEFAULT_PATH_LOSS_EXPONENT };
+
+    let path_loss = f32::from(tx_power) - f32::from(rssi) - PATH_LOSS_AT_1M;
+    let distance = 10f32.powf(path_loss / (10.0 * exponent));
+    distance.clamp(MIN_ESTIMATED_DISTANCE, MAX_ESTIMATED_DISTANCE)
+}
+
+/// Scanning callbacks used by the GD implementation of BleScannerInterface.
+/// These callbacks should be registered using |RegisterCallbacks| on
+/// `BleScannerInterface`. Statuses are the raw HCI/BTM codes reported by the
+/// stack, 0 being success.
+#[derive(Clone, Debug)]
+pub enum GattScannerCallbacks {
+    OnScannerRegistered(Uuid, u8, u8),
+    OnSetScannerParameterComplete(u8, u8),
+    OnScanResult(ScanResult),
+    OnTrackAdvFoundLost(TrackingEvent),
+    /// Params: Client If, Status, Report Format, Num Records, Data. The data
+    /// can be parsed with |parse_batch_scan_reports|.
+    OnBatchScanReports(i32, i32, i32, i32, Vec<u8>),
+    OnBatchScanThresholdCrossed(i32),
+
+    /// Params: Reg Id, Status, Sync Handle, Advertising Sid, Address Type, Address, Phy,
+    /// Interval
+    OnPeriodicSyncStarted(i32, u8, u16, u8, u8, RawAddress, u8, u16),
+
+    OnPeriodicSyncReport(PeriodicAdvReport),
+
+    /// Params: Sync Handle
+    OnPeriodicSyncLost(u16),
+
+    /// Params: PA Source, Status, Address
+    OnPeriodicSyncTransferred(i32, u8, RawAddress),
+
+    /// Params: Monitor Handle, Address
+    OnMsftAdvMonitorDeviceFound(u8, RawAddress),
+
+    /// Params: Monitor Handle, Address
+    OnMsftAdvMonitorDeviceLost(u8, RawAddress),
+
+    /// Dispatched instead of |OnScanResult| once per interval while
+    /// |BleScanner::set_coalescing| is on. Holds the strongest result of each
+    /// advertiser seen during the interval, in the order they were first seen.
+    OnScanResultsBatch(Vec<ScanResult>),
+}
+
+pub struct GattScannerCallbacksDispatcher {
+    pub dispatch: Box<dyn Fn(GattScannerCallbacks) + Send>,
+}
+
+type GDScannerCb = Arc<Mutex<GattScannerCallbacksDispatcher>>;
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_scanner_registered -> GattScannerCallbacks::OnScannerRegistered,
+    ffi::RustUuid -> Uuid, u8, u8
+);
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_set_scanner_parameter_complete -> GattScannerCallbacks::OnSetScannerParameterComplete,
+    u8, u8
+);
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_scan_result -> GattScannerCallbacks::OnScanResult,
+    u16, u8 -> _, *const i8 -> _, u8 -> _, u8 -> _, u8 -> _, i8 -> _, i8 -> _, u16 -> _,
+    *const u8 -> _, usize -> _, {
+        // Since this cb is being called via cxx ffi, the vector separation is
+        // done at the cxx layer.
+        let _0 = ScanResult {
+            event_type: AdvertisingEventType(_0),
+            addr_type: BleAddressType::from(_1),
+            address: unsafe { deref_ffi_address!(_2) },
+            primary_phy: _3,
+            secondary_phy: _4,
+            advertising_sid: _5,
+            tx_power: _6,
+            rssi: _7,
+            periodic_adv_interval: _8,
+            adv_data: ptr_to_vec(_9, _10),
+        };
+    }
+);
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_track_adv_found_lost -> GattScannerCallbacks::OnTrackAdvFoundLost,
+    AdvertisingTrackInfo -> TrackingEvent);
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_batch_scan_reports -> GattScannerCallbacks::OnBatchScanReports,
+    i32, i32, i32, i32, *const u8, usize -> _, {
+        // Write the vector to the output and consume the usize in the input.
+        let _4 : Vec<u8> = ptr_to_vec(_4, _5);
+    }
+);
+
+cb_variant!(GDScannerCb, gdscan_on_batch_scan_threshold_crossed -> GattScannerCallbacks::OnBatchScanThresholdCrossed, i32);
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_periodic_sync_started -> GattScannerCallbacks::OnPeriodicSyncStarted,
+    i32, u8, u16, u8, u8, *const ffi::RustRawAddress, u8, u16, {
+        let _5 = unsafe { deref_ffi_address!(_5) };
+    }
+);
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_periodic_sync_report -> GattScannerCallbacks::OnPeriodicSyncReport,
+    u16, i8 -> _, i8 -> _, u8 -> _, *const u8 -> _, usize -> _, {
+        // The status is the data status of the LE Periodic Advertising Report
+        // event rather than a GATT status.
+        let _0 = PeriodicAdvReport {
+            sync_handle: _0,
+            tx_power: _1,
+            rssi: _2,
+            data_status: AdvertisingDataStatus::from(_3),
+            data: ptr_to_vec(_4, _5),
+        };
+    }
+);
+
+cb_variant!(GDScannerCb, gdscan_on_periodic_sync_lost -> GattScannerCallbacks::OnPeriodicSyncLost, u16);
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_periodic_sync_transferred -> GattScannerCallbacks::OnPeriodicSyncTransferred,
+    i32, u8, *const ffi::RustRawAddress, {
+        let _2 = unsafe { deref_ffi_address!(_2) };
+    }
+);
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_msft_adv_monitor_device_found -> GattScannerCallbacks::OnMsftAdvMonitorDeviceFound,
+    u8, *const ffi::RustRawAddress, {
+        let _1 = unsafe { deref_ffi_address!(_1) };
+    }
+);
+
+cb_variant!(
+    GDScannerCb,
+    gdscan_on_msft_adv_monitor_device_lost -> GattScannerCallbacks::OnMsftAdvMonitorDeviceLost,
+    u8, *const ffi::RustRawAddress, {
+        let _1 = unsafe { deref_ffi_address!(_1) };
+    }
+);
+
+/// In-band callbacks from the various |BleScannerInterface| methods. Rather than
+/// store closures for each registered callback, we instead bind and return an
+/// identifier for the callback instead (such as scanner id or Uuid).
+#[derive(Debug)]
+pub enum GattScannerInbandCallbacks {
+    /// Params: Scanner Id, BTM Status
+    StatusCallback(u8, u8),
+
+    /// Params: Action (enable/disable), BTM Status
+    EnableCallback(u8, u8),
+
+    /// Params: Scanner Id, Available Space, Action Type, BTM Status
+    FilterParamSetupCallback(u8, u8, u8, u8),
+
+    /// Params: Filter Index, Filter Type, Available Space, Action, BTM Status
+    FilterConfigCallback(u8, u8, u8, u8, u8),
+
+    /// Params: Monitor Handle, HCI Status
+    MsftAdvMonitorAddCallback(u8, u8),
+
+    /// Params: HCI Status
+    MsftAdvMonitorRemoveCallback(u8),
+
+    /// Params: HCI Status
+    MsftAdvMonitorEnableCallback(u8),
+}
+
+pub struct GattScannerInbandCallbacksDispatcher {
+    pub dispatch: Box<dyn Fn(GattScannerInbandCallbacks) + Send>,
+}
+
+type GDScannerInbandCb = Arc<Mutex<GattScannerInbandCallbacksDispatcher>>;
+
+cb_variant!(GDScannerInbandCb, gdscan_status_callback -> GattScannerInbandCallbacks::StatusCallback, u8, u8);
+cb_variant!(GDScannerInbandCb, gdscan_enable_callback -> GattScannerInbandCallbacks::EnableCallback, u8, u8);
+cb_variant!(GDScannerInbandCb,
+    gdscan_filter_param_setup_callback -> GattScannerInbandCallbacks::FilterParamSetupCallback,
+    u8, u8, u8, u8);
+cb_variant!(GDScannerInbandCb,
+    gdscan_filter_config_callback -> GattScannerInbandCallbacks::FilterConfigCallback,
+    u8, u8, u8, u8, u8);
+cb_variant!(GDScannerInbandCb,
+    gdscan_msft_adv_monitor_add_callback -> GattScannerInbandCallbacks::MsftAdvMonitorAddCallback,
+    u8, u8);
+cb_variant!(GDScannerInbandCb,
+    gdscan_msft_adv_monitor_remove_callback -> GattScannerInbandCallbacks::MsftAdvMonitorRemoveCallback,
+    u8);
+cb_variant!(GDScannerInbandCb,
+    gdscan_msft_adv_monitor_enable_callback -> GattScannerInbandCallbacks::MsftAdvMonitorEnableCallback,
+    u8);
+
+/// Advertising callbacks used by the GD implementation of BleAdvertiserInterface.
+/// These callbacks should be registered using |RegisterCallbacks| on
+/// `BleAdvertiser`. Statuses are the raw HCI/BTM codes reported by the stack,
+/// 0 being success.
+#[derive(Debug)]
+pub enum GattAdvCallbacks {
+    /// Params: Reg Id, Advertiser Id, Tx Power, Status
+    OnAdvertisingSetStarted(i32, u8, i8, u8),
+
+    /// Params: Advertiser Id, Enabled, Status
+    OnAdvertisingEnabled(u8, bool, u8),
+
+    /// Params: Advertiser Id, Status
+    OnAdvertisingDataSet(u8, u8),
+
+    /// Params: Advertiser Id, Status
+    OnScanResponseDataSet(u8, u8),
+
+    /// Params: Advertiser Id, Tx Power, Status
+    OnAdvertisingParametersUpdated(u8, i8, u8),
+
+    /// Params: Advertiser Id, Status
+    OnPeriodicAdvertisingParametersUpdated(u8, u8),
+
+    /// Params: Advertiser Id, Status
+    OnPeriodicAdvertisingDataSet(u8, u8),
+
+    /// Params: Advertiser Id, Enabled, Status
+    OnPeriodicAdvertisingEnabled(u8, bool, u8),
+
+    /// Params: Advertiser Id, Address Type, Address
+    OnOwnAddressRead(u8, u8, RawAddress),
+
+    /// The controller stopped a set started with a duration or maximum number
+    /// of extended advertising events once it reached that limit. Follows
+    /// |OnAdvertisingEnabled| with enabled false and the same status. The set
+    /// stays allocated and can be enabled again.
+    ///
+    /// Params: Advertiser Id, Status (HCI error code, see
+    /// |ADVERTISING_TIMEOUT| and |ADVERTISING_LIMIT_REACHED|), Completed
+    /// Extended Advertising Events
+    OnAdvertisingSetTerminated(u8, u8, u8),
+}
+
+/// Status of |GattAdvCallbacks::OnAdvertisingSetTerminated| for a set that
+/// reached its duration.
+pub const ADVERTISING_TIMEOUT: u8 = 0x3C;
+
+/// Status of |GattAdvCallbacks::OnAdvertisingSetTerminated| for a set that
+/// reached its maximum number of extended advertising events.
+pub const ADVERTISING_LIMIT_REACHED: u8 = 0x43;
+
+pub struct GattAdvCallbacksDispatcher {
+    pub dispatch: Box<dyn Fn(GattAdvCallbacks) + Send>,
+}
+
+type GDAdvCb = Arc<Mutex<GattAdvCallbacksDispatcher>>;
+
+cb_variant!(GDAdvCb,
+    gdadv_on_advertising_set_started -> GattAdvCallbacks::OnAdvertisingSetStarted,
+    i32, u8, i8, u8);
+cb_variant!(GDAdvCb,
+    gdadv_on_advertising_enabled -> GattAdvCallbacks::OnAdvertisingEnabled,
+    u8, bool, u8);
+cb_variant!(GDAdvCb,
+    gdadv_on_advertising_data_set -> GattAdvCallbacks::OnAdvertisingDataSet,
+    u8, u8);
+cb_variant!(GDAdvCb,
+    gdadv_on_scan_response_data_set -> GattAdvCallbacks::OnScanResponseDataSet,
+    u8, u8);
+cb_variant!(GDAdvCb,
+    gdadv_on_advertising_parameters_updated -> GattAdvCallbacks::OnAdvertisingParametersUpdated,
+    u8, i8, u8);
+cb_variant!(GDAdvCb,
+    gdadv_on_periodic_advertising_parameters_updated -> GattAdvCallbacks::OnPeriodicAdvertisingParametersUpdated,
+    u8, u8);
+cb_variant!(GDAdvCb,
+    gdadv_on_periodic_advertising_data_set -> GattAdvCallbacks::OnPeriodicAdvertisingDataSet,
+    u8, u8);
+cb_variant!(GDAdvCb,
+    gdadv_on_periodic_advertising_enabled -> GattAdvCallbacks::OnPeriodicAdvertisingEnabled,
+    u8, bool, u8);
+cb_variant!(GDAdvCb,
+gdadv_on_own_address_read -> GattAdvCallbacks::OnOwnAddressRead, u8, u8,
+*const ffi::RustRawAddress, {
+    let _2 = unsafe { deref_ffi_address!(_2) };
+});
+cb_variant!(GDAdvCb,
+    gdadv_on_advertising_set_terminated -> GattAdvCallbacks::OnAdvertisingSetTerminated,
+    u8, u8, u8);
+
+#[derive(Debug)]
+pub enum GattAdvInbandCallbacks {
+    /// Params: Advertiser Id, Status
+    /// StatusCallback isn't implemented because we always want advertiser id.
     IdStatusCallback(u8, u8),
 
     /// Params: Advertiser Id, Tx Power, Status
@@ -956,9 +2274,6 @@ pub enum GattAdvInbandCallbacks {
 
     /// Params: Advertiser Id, Status, Tx Power
     ParametersCallback(u8, u8, i8),
-
-    /// Params: Advertiser Id, Addr Type, Address
-    GetAddressCallback(u8, u8, RawAddress),
 }
 
 pub struct GattAdvInbandCallbacksDispatcher {
@@ -970,10 +2285,6 @@ type GDAdvInbandCb = Arc<Mutex<GattAdvInbandCallbacksDispatcher>>;
 cb_variant!(GDAdvInbandCb, gdadv_idstatus_callback -> GattAdvInbandCallbacks::IdStatusCallback, u8, u8);
 cb_variant!(GDAdvInbandCb, gdadv_idtxpowerstatus_callback -> GattAdvInbandCallbacks::IdTxPowerStatusCallback, u8, i8, u8);
 cb_variant!(GDAdvInbandCb, gdadv_parameters_callback -> GattAdvInbandCallbacks::ParametersCallback, u8, u8, i8);
-cb_variant!(GDAdvInbandCb, gdadv_getaddress_callback -> GattAdvInbandCallbacks::GetAddressCallback,
-u8, u8, *const ffi::RustRawAddress, {
-    let _2 = unsafe { deref_ffi_address!(_2) };
-});
 
 struct RawGattWrapper {
     raw: *const btgatt_interface_t,
@@ -991,720 +2302,6633 @@ struct RawBleScannerWrapper {
     _raw: *const BleScannerInterface,
 }
 
-struct RawBleAdvertiserWrapper {
-    _raw: *const BleAdvertiserInterface,
-}
-
 // Pointers unsafe due to ownership but this is a static pointer so Send is ok
 unsafe impl Send for RawGattWrapper {}
 unsafe impl Send for RawGattClientWrapper {}
 unsafe impl Send for RawGattServerWrapper {}
 unsafe impl Send for RawBleScannerWrapper {}
-unsafe impl Send for RawBleAdvertiserWrapper {}
 unsafe impl Send for btgatt_callbacks_t {}
 unsafe impl Send for GattClient {}
 unsafe impl Send for GattClientCallbacks {}
 unsafe impl Send for BleScanner {}
 unsafe impl Send for BleAdvertiser {}
 
-pub struct GattClient {
-    internal: RawGattClientWrapper,
-    internal_cxx: cxx::UniquePtr<ffi::GattClientIntf>,
+/// Identifies a read or write issued through the |*_with_id| methods of
+/// |GattClient|.
+pub type GattOpId = u64;
+
+/// State of a client connection as last reported by the stack.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnState {
+    Disconnected,
+    Connected,
+    /// Connected, but the stack has stopped accepting further data until the
+    /// congestion clears.
+    Congested,
 }
 
-impl GattClient {
-    pub fn register_client(&self, uuid: &Uuid, eatt_support: bool) -> BtStatus {
-        BtStatus::from(ccall!(self, register_client, uuid, eatt_support))
+/// A connection state transition, delivered to subscribers of
+/// |GattClient::subscribe_connection_state|.
+#[cfg(feature = "connection-state-events")]
+#[derive(Clone, Debug)]
+pub struct ConnStateEvent {
+    pub conn_id: i32,
+    pub addr: RawAddress,
+    pub state: ConnState,
+}
+
+// Number of transitions a slow subscriber can fall behind by before it starts
+// missing them.
+#[cfg(feature = "connection-state-events")]
+const CONN_STATE_EVENTS_CAPACITY: usize = 32;
+
+// Outstanding reads or writes keyed by (conn_id, handle), in issue order. The
+// flag is set for requests whose op id was handed to the caller.
+type PendingOps = HashMap<(i32, u16), VecDeque<(GattOpId, bool)>>;
+
+/// Async client requests waiting for their completion callback, along with the
+/// per-connection state they depend on.
+#[derive(Default)]
+struct PendingClientRequests {
+    next_op_id: GattOpId,
+    reads: PendingOps,
+    writes: PendingOps,
+    // Keyed by app uuid.
+    registrations: HashMap<[u8; 16], oneshot::Sender<Result<i32, GattStatus>>>,
+    // Keyed by (client_if, remote address).
+    connects: HashMap<(i32, RawAddress), oneshot::Sender<Result<i32, GattStatus>>>,
+    // Keyed by conn_id.
+    disconnects: HashMap<i32, oneshot::Sender<Result<(), GattStatus>>>,
+    // (client_if, remote address) of background connects that haven't been
+    // cancelled. These outlive the connections they establish, so that the
+    // link is reestablished whenever the device comes back in range.
+    background_connects: HashSet<(i32, RawAddress)>,
+    // Service discovery and the following database read, keyed by conn_id.
+    searches: HashMap<i32, oneshot::Sender<Result<(), GattStatus>>>,
+    gatt_dbs: HashMap<i32, oneshot::Sender<Vec<BtGattDbElement>>>,
+    // Keyed by (client_if, remote address).
+    rssi_reads: HashMap<(i32, RawAddress), oneshot::Sender<Result<i8, GattStatus>>>,
+    // Keyed by remote address, since that's all |set_preferred_phy| takes.
+    phy_updates: HashMap<RawAddress, oneshot::Sender<Result<(LePhy, LePhy), GattStatus>>>,
+    // Keyed by conn_id.
+    conn_updates: HashMap<i32, oneshot::Sender<Result<ConnectionParameters, GattStatus>>>,
+    // Reads by characteristic uuid, keyed by conn_id. The matching handle is
+    // only known once the read completes. Only reads issued through
+    // |read_using_characteristic_uuid_async| carry a sender.
+    uuid_reads: HashMap<i32, VecDeque<(GattOpId, Option<UuidReadSender>)>>,
+    // ATT MTU negotiated on each connection, whichever side initiated it.
+    // Connections without an entry use |ATT_DEFAULT_MTU|.
+    mtus: HashMap<i32, i32>,
+    // Keyed by conn_id.
+    mtu_exchanges: HashMap<i32, oneshot::Sender<Result<u16, GattStatus>>>,
+    // Remote address of each open connection, keyed by conn_id.
+    conn_addrs: HashMap<i32, RawAddress>,
+    // Open connections that are currently congested.
+    congested: HashSet<i32>,
+    // Writes without response held back by |write_characteristic_no_response|
+    // while their connection is congested, keyed by conn_id.
+    queued_writes: HashMap<i32, VecDeque<QueuedWrite>>,
+    // Conn_id of the writes without response that were sent and haven't
+    // completed yet, keyed by op id.
+    no_response_writes: HashMap<GattOpId, i32>,
+    // Last database reported by |GattClientCallbacks::GetGattDb|, keyed by
+    // conn_id.
+    databases: HashMap<i32, GattDatabase>,
+    // Streams returned by |GattClient::subscribe|, keyed by (remote address,
+    // value handle).
+    subscriptions: HashMap<(RawAddress, u16), Vec<(u64, NotificationSender)>>,
+    next_subscription_id: u64,
+    // Streams returned by |GattClient::read_all|, keyed by conn_id, so they can
+    // resume issuing reads once the connection is no longer congested.
+    read_alls: HashMap<i32, Vec<(u64, ReadAllSender)>>,
+    // Reads issued by those streams, keyed by op id, with their conn_id.
+    read_all_ops: HashMap<GattOpId, (i32, ReadAllSender)>,
+    // Futures waiting on reads and writes, keyed by op id, with their conn_id.
+    op_waiters: HashMap<GattOpId, (i32, OpWaiter)>,
+    // Ops abandoned through |GattClient::cancel_operation| that haven't
+    // completed yet.
+    cancelled: HashSet<GattOpId>,
+    // Set by |GattClient::set_auto_rediscover|.
+    auto_rediscover: bool,
+    // Connections being rediscovered after |GattClientCallbacks::ServiceChanged|.
+    rediscovers: HashSet<i32>,
+    // Created by the first subscriber.
+    #[cfg(feature = "connection-state-events")]
+    state_events: Option<broadcast::Sender<ConnStateEvent>>,
+}
+
+struct QueuedWrite {
+    handle: u16,
+    auth_req: GattAuthReq,
+    value: Vec<u8>,
+}
+
+type UuidReadSender = oneshot::Sender<Result<(u16, Vec<u8>), GattOpError>>;
+
+// Futures of the reads and writes started by |GattClient::start_read_characteristic|
+// and |GattClient::start_write_characteristic|.
+enum OpWaiter {
+    Read(oneshot::Sender<Result<Vec<u8>, GattOpError>>),
+    Write(oneshot::Sender<Result<(), GattOpError>>),
+}
+type NotificationSender = mpsc::UnboundedSender<GattNotification>;
+type ReadAllSender = mpsc::UnboundedSender<ReadAllEvent>;
+
+enum ReadAllEvent {
+    Read(GattReadResult),
+    Uncongested,
+}
+
+#[derive(Clone, Copy)]
+struct Cccd {
+    handle: u16,
+    // Value that enables notifications, or indications if the characteristic
+    // only indicates.
+    enable: u16,
+}
+
+impl Cccd {
+    // CCCD of the characteristic with |value_handle|, if it notifies or
+    // indicates.
+    fn find(db: &GattDatabase, value_handle: u16) -> Option<Cccd> {
+        let characteristic = db.characteristic(value_handle)?;
+        let descriptor = characteristic.descriptors.iter().find(|d| d.uuid.uu == CCCD_UUID)?;
+        let enable = if characteristic.properties.contains(CharacteristicProperties::NOTIFY) {
+            0x0001
+        } else if characteristic.properties.contains(CharacteristicProperties::INDICATE) {
+            0x0002
+        } else {
+            return None;
+        };
+        Some(Cccd { handle: descriptor.handle, enable })
     }
+}
 
-    pub fn unregister_client(&self, client_if: i32) -> BtStatus {
-        BtStatus::from(ccall!(self, unregister_client, client_if))
+impl PendingClientRequests {
+    // The stack completes requests on a connection in the order they were
+    // issued, so the oldest entry for (conn_id, handle) is the one completing.
+    fn pop_op(ops: &mut PendingOps, key: (i32, u16)) -> Option<(GattOpId, bool)> {
+        let queue = ops.get_mut(&key)?;
+        let op = queue.pop_front();
+        if queue.is_empty() {
+            ops.remove(&key);
+        }
+        op
     }
 
-    pub fn connect(
-        &self,
-        client_if: i32,
-        addr: &RawAddress,
-        is_direct: bool,
-        transport: i32,
-        opportunistic: bool,
-        initiating_phys: i32,
-    ) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(
-            self,
-            connect,
-            client_if,
-            ffi_addr,
-            is_direct,
-            transport,
-            opportunistic,
-            initiating_phys
-        ))
+    fn is_connected(&self, addr: &RawAddress) -> bool {
+        self.conn_addrs.values().any(|conn_addr| conn_addr == addr)
     }
 
-    pub fn disconnect(&self, client_if: i32, addr: &RawAddress, conn_id: i32) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(self, disconnect, client_if, ffi_addr, conn_id))
+    fn conn_state(&self, conn_id: i32) -> ConnState {
+        if !self.conn_addrs.contains_key(&conn_id) {
+            ConnState::Disconnected
+        } else if self.congested.contains(&conn_id) {
+            ConnState::Congested
+        } else {
+            ConnState::Connected
+        }
     }
 
-    pub fn refresh(&self, client_if: i32, addr: &RawAddress) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(self, refresh, client_if, ffi_addr))
+    #[cfg(feature = "connection-state-events")]
+    fn notify_state(&mut self, conn_id: i32, addr: RawAddress, state: ConnState) {
+        if let Some(tx) = &self.state_events {
+            // Fails only when nobody is subscribed.
+            if tx.send(ConnStateEvent { conn_id, addr, state }).is_err() {
+                self.state_events = None;
+            }
+        }
     }
 
-    pub fn search_service(&self, conn_id: i32, filter_uuid: Option<Uuid>) -> BtStatus {
-        let filter_uuid_ptr = match filter_uuid {
-            None => std::ptr::null(),
-            Some(uuid) => &uuid,
-        };
+    #[cfg(not(feature = "connection-state-events"))]
+    fn notify_state(&mut self, _conn_id: i32, _addr: RawAddress, _state: ConnState) {}
 
-        BtStatus::from(ccall!(self, search_service, conn_id, filter_uuid_ptr))
+    fn pop_uuid_read(&mut self, conn_id: i32) -> Option<(GattOpId, Option<UuidReadSender>)> {
+        let queue = self.uuid_reads.get_mut(&conn_id)?;
+        let read = queue.pop_front();
+        if queue.is_empty() {
+            self.uuid_reads.remove(&conn_id);
+        }
+        read
     }
 
-    pub fn btif_gattc_discover_service_by_uuid(&self, conn_id: i32, uuid: &Uuid) {
-        ccall!(self, btif_gattc_discover_service_by_uuid, conn_id, uuid)
+    // Ids of the reads and writes outstanding on |conn_id|.
+    fn ops_on(&self, conn_id: i32) -> Vec<GattOpId> {
+        let uuid_reads = self.uuid_reads.get(&conn_id).into_iter().flatten();
+        self.reads
+            .iter()
+            .chain(self.writes.iter())
+            .filter(|((id, _), _)| *id == conn_id)
+            .flat_map(|(_, ops)| ops.iter().map(|(op_id, _)| *op_id))
+            .chain(uuid_reads.map(|(op_id, _)| *op_id))
+            .collect()
     }
 
-    pub fn read_characteristic(&self, conn_id: i32, handle: u16, auth_req: i32) -> BtStatus {
-        BtStatus::from(ccall!(self, read_characteristic, conn_id, handle, auth_req))
+    fn remove_op(ops: &mut PendingOps, key: (i32, u16), op_id: GattOpId) {
+        if let Some(queue) = ops.get_mut(&key) {
+            queue.retain(|(id, _)| *id != op_id);
+            if queue.is_empty() {
+                ops.remove(&key);
+            }
+        }
     }
+}
 
-    pub fn read_using_characteristic_uuid(
-        &self,
-        conn_id: i32,
-        uuid: &Uuid,
-        s_handle: u16,
-        e_handle: u16,
-        auth_req: i32,
-    ) -> BtStatus {
-        BtStatus::from(ccall!(
-            self,
-            read_using_characteristic_uuid,
-            conn_id,
-            uuid,
-            s_handle,
-            e_handle,
-            auth_req
-        ))
-    }
+/// Number of operations of one kind that were issued and how many of them
+/// failed, either when issued or on completion.
+#[derive(Default)]
+struct OpCounters {
+    attempted: AtomicU64,
+    failed: AtomicU64,
+}
 
-    pub fn write_characteristic(
-        &self,
-        conn_id: i32,
-        handle: u16,
-        write_type: i32,
-        auth_req: i32,
-        value: &[u8],
-    ) -> BtStatus {
-        BtStatus::from(ccall!(
-            self,
-            write_characteristic,
-            conn_id,
-            handle,
-            write_type,
-            auth_req,
-            value.as_ptr(),
-            value.len()
-        ))
+impl OpCounters {
+    fn issued(&self, status: BtStatus) -> BtStatus {
+        self.attempted.fetch_add(1, Ordering::Relaxed);
+        if status != BtStatus::Success {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+        status
     }
 
-    pub fn read_descriptor(&self, conn_id: i32, handle: u16, auth_req: i32) -> BtStatus {
-        BtStatus::from(ccall!(self, read_descriptor, conn_id, handle, auth_req))
+    fn completed(&self, status: i32) {
+        if GattStatus::from(status as u8) != GattStatus::Success {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
     }
+}
 
-    pub fn write_descriptor(
-        &self,
-        conn_id: i32,
-        handle: u16,
-        auth_req: i32,
-        value: &[u8],
-    ) -> BtStatus {
-        BtStatus::from(ccall!(
-            self,
-            write_descriptor,
-            conn_id,
-            handle,
-            auth_req,
-            value.as_ptr(),
-            value.len()
-        ))
-    }
+/// Outcome counters for reads and writes issued by |GattClient| and
+/// notifications sent by |GattServer|.
+#[derive(Default)]
+pub struct GattMetrics {
+    reads: OpCounters,
+    writes: OpCounters,
+    notifications: OpCounters,
+}
 
-    pub fn execute_write(&self, conn_id: i32, execute: i32) -> BtStatus {
-        BtStatus::from(ccall!(self, execute_write, conn_id, execute))
-    }
+/// Point in time copy of |GattMetrics|.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GattMetricsSnapshot {
+    pub reads_attempted: u64,
+    pub reads_failed: u64,
+    pub writes_attempted: u64,
+    pub writes_failed: u64,
+    pub notifications_attempted: u64,
+    pub notifications_failed: u64,
+}
 
-    pub fn register_for_notification(
-        &self,
-        client_if: i32,
-        addr: &RawAddress,
-        handle: u16,
-    ) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(self, register_for_notification, client_if, ffi_addr, handle))
+impl GattMetrics {
+    pub fn snapshot(&self) -> GattMetricsSnapshot {
+        GattMetricsSnapshot {
+            reads_attempted: self.reads.attempted.load(Ordering::Relaxed),
+            reads_failed: self.reads.failed.load(Ordering::Relaxed),
+            writes_attempted: self.writes.attempted.load(Ordering::Relaxed),
+            writes_failed: self.writes.failed.load(Ordering::Relaxed),
+            notifications_attempted: self.notifications.attempted.load(Ordering::Relaxed),
+            notifications_failed: self.notifications.failed.load(Ordering::Relaxed),
+        }
     }
+}
 
-    pub fn deregister_for_notification(
-        &self,
-        client_if: i32,
-        addr: &RawAddress,
-        handle: u16,
-    ) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(self, deregister_for_notification, client_if, ffi_addr, handle))
-    }
+/// Notifications and indications of one characteristic, returned by
+/// |GattClient::subscribe|. Dropping the stream unsubscribes again.
+pub struct NotificationStream {
+    rx: mpsc::UnboundedReceiver<GattNotification>,
+    id: u64,
+    client_if: i32,
+    conn_id: i32,
+    addr: RawAddress,
+    handle: u16,
+    cccd: u16,
 
-    pub fn read_remote_rssi(&self, client_if: i32, addr: &RawAddress) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(self, read_remote_rssi, client_if, ffi_addr))
-    }
+    internal: RawGattClientWrapper,
+    pending: Arc<Mutex<PendingClientRequests>>,
+    metrics: Arc<GattMetrics>,
+}
 
-    pub fn get_device_type(&self, addr: &RawAddress) -> i32 {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        ccall!(self, get_device_type, ffi_addr)
+unsafe impl Send for NotificationStream {}
+
+impl NotificationStream {
+    /// Waits for the next notification. Returns None once the connection is
+    /// gone.
+    pub async fn recv(&mut self) -> Option<GattNotification> {
+        self.rx.recv().await
     }
+}
 
-    pub fn configure_mtu(&self, conn_id: i32, mtu: i32) -> BtStatus {
-        BtStatus::from(ccall!(self, configure_mtu, conn_id, mtu))
+impl Stream for NotificationStream {
+    type Item = GattNotification;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
     }
+}
 
-    pub fn conn_parameter_update(
-        &self,
-        addr: &RawAddress,
-        min_interval: i32,
-        max_interval: i32,
-        latency: i32,
-        timeout: i32,
-        min_ce_len: u16,
-        max_ce_len: u16,
-    ) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(
+impl Drop for NotificationStream {
+    fn drop(&mut self) {
+        let mut pending = self.pending.lock().unwrap();
+        let key = (self.addr, self.handle);
+        if let Some(subscribers) = pending.subscriptions.get_mut(&key) {
+            subscribers.retain(|(id, _)| *id != self.id);
+            if !subscribers.is_empty() {
+                return;
+            }
+            pending.subscriptions.remove(&key);
+        }
+
+        // The remote clears its CCCD on its own when the connection goes away.
+        if !pending.conn_addrs.contains_key(&self.conn_id) {
+            return;
+        }
+        drop(pending);
+
+        let disable = [0u8; 2];
+        self.metrics.writes.issued(BtStatus::from(ccall!(
             self,
-            conn_parameter_update,
-            ffi_addr,
-            min_interval,
-            max_interval,
-            latency,
-            timeout,
-            min_ce_len,
-            max_ce_len
-        ))
+            write_descriptor,
+            self.conn_id,
+            self.cccd,
+            GattAuthReq::None as i32,
+            disable.as_ptr(),
+            disable.len()
+        )));
+        let ffi_addr = cast_to_ffi_address!(&self.addr as *const RawAddress);
+        ccall!(self, deregister_for_notification, self.client_if, ffi_addr, self.handle);
     }
+}
 
-    pub fn set_preferred_phy(
-        &self,
-        addr: &RawAddress,
-        tx_phy: u8,
-        rx_phy: u8,
-        phy_options: u16,
-    ) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(self, set_preferred_phy, ffi_addr, tx_phy, rx_phy, phy_options))
-    }
+/// Maximum number of reads a |ReadAllStream| keeps outstanding. The stack
+/// queues requests behind the single one ATT allows in flight, so this bounds
+/// how much of that queue one batch can take up.
+pub const READ_ALL_WINDOW: usize = 3;
+
+/// Results of the reads issued by |GattClient::read_all|, in completion order.
+/// Dropping the stream stops issuing reads; those already issued still
+/// complete through |GattClientCallbacks::ReadCharacteristic|.
+pub struct ReadAllStream {
+    rx: mpsc::UnboundedReceiver<ReadAllEvent>,
+    id: u64,
+    conn_id: i32,
+    auth_req: GattAuthReq,
+    // Handles not read yet, and the number of reads outstanding.
+    handles: VecDeque<u16>,
+    in_flight: usize,
+    // Reads that failed to issue, reported ahead of the next completion.
+    failed: VecDeque<GattReadResult>,
 
-    pub fn read_phy(&mut self, client_if: i32, addr: &RawAddress) -> BtStatus {
-        BtStatus::from_i32(mutcxxcall!(
-            self,
-            read_phy,
-            client_if,
-            ffi::RustRawAddress { address: addr.val }
-        ))
-        .unwrap()
+    internal: RawGattClientWrapper,
+    pending: Arc<Mutex<PendingClientRequests>>,
+    metrics: Arc<GattMetrics>,
+}
+
+unsafe impl Send for ReadAllStream {}
+
+impl ReadAllStream {
+    // Issues reads until the window is full, the handles run out or the
+    // connection is congested.
+    fn fill(&mut self) {
+        while self.in_flight < READ_ALL_WINDOW {
+            let mut pending = self.pending.lock().unwrap();
+            if pending.congested.contains(&self.conn_id) {
+                return;
+            }
+            let tx = match pending.read_alls.get(&self.conn_id).and_then(|streams| {
+                streams.iter().find(|(id, _)| *id == self.id).map(|(_, tx)| tx.clone())
+            }) {
+                Some(tx) => tx,
+                // Disconnected.
+                None => {
+                    self.handles.clear();
+                    return;
+                }
+            };
+            let handle = match self.handles.pop_front() {
+                Some(handle) => handle,
+                None => return,
+            };
+
+            let key = (self.conn_id, handle);
+            let op_id = pending.next_op_id;
+            pending.next_op_id += 1;
+            pending.reads.entry(key).or_default().push_back((op_id, false));
+            pending.read_all_ops.insert(op_id, (self.conn_id, tx));
+            drop(pending);
+
+            let status = self.metrics.reads.issued(BtStatus::from(ccall!(
+                self,
+                read_characteristic,
+                self.conn_id,
+                handle,
+                self.auth_req as i32
+            )));
+            if status == BtStatus::Success {
+                self.in_flight += 1;
+                continue;
+            }
+
+            let mut pending = self.pending.lock().unwrap();
+            PendingClientRequests::remove_op(&mut pending.reads, key, op_id);
+            pending.read_all_ops.remove(&op_id);
+            self.failed.push_back(GattReadResult {
+                handle,
+                status: GattStatus::Error,
+                value: vec![],
+            });
+        }
     }
+}
 
-    pub fn test_command(&self, command: i32, params: &BtGattTestParams) -> BtStatus {
-        BtStatus::from(ccall!(self, test_command, command, params))
+impl Stream for ReadAllStream {
+    type Item = GattReadResult;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(result) = self.failed.pop_front() {
+                return Poll::Ready(Some(result));
+            }
+            if self.in_flight == 0 && self.handles.is_empty() {
+                return Poll::Ready(None);
+            }
+
+            match self.rx.poll_recv(cx) {
+                Poll::Ready(Some(ReadAllEvent::Read(result))) => {
+                    self.in_flight -= 1;
+                    self.fill();
+                    return Poll::Ready(Some(result));
+                }
+                Poll::Ready(Some(ReadAllEvent::Uncongested)) => self.fill(),
+                // The connection is gone.
+                Poll::Ready(None) => {
+                    self.handles.clear();
+                    self.in_flight = 0;
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
     }
+}
 
-    pub fn get_gatt_db(&self, conn_id: i32) -> BtStatus {
-        BtStatus::from(ccall!(self, get_gatt_db, conn_id))
+impl Drop for ReadAllStream {
+    fn drop(&mut self) {
+        let mut pending = self.pending.lock().unwrap();
+        let streams = match pending.read_alls.get_mut(&self.conn_id) {
+            Some(streams) => streams,
+            None => return,
+        };
+        let ours = match streams.iter().position(|(id, _)| *id == self.id) {
+            Some(index) => streams.remove(index).1,
+            None => return,
+        };
+        if streams.is_empty() {
+            pending.read_alls.remove(&self.conn_id);
+        }
+        pending.read_all_ops.retain(|_, (_, tx)| !tx.same_channel(&ours));
     }
 }
 
-pub struct GattServer {
-    internal: RawGattServerWrapper,
+pub struct GattClient {
+    internal: RawGattClientWrapper,
+    internal_cxx: cxx::UniquePtr<ffi::GattClientIntf>,
+
+    // Requests started by the async methods. Resolved from the client
+    // callbacks before they are dispatched.
+    pending: Arc<Mutex<PendingClientRequests>>,
+    metrics: Arc<GattMetrics>,
+    timeouts: GattTimeouts,
 }
 
-impl GattServer {
-    pub fn register_server(&self, uuid: &Uuid, eatt_support: bool) -> BtStatus {
-        BtStatus::from(ccall!(self, register_server, uuid, eatt_support))
+/// How long the async |GattClient| operations wait for the callback that
+/// completes them before failing with |GattOpError::Timeout|. Each operation
+/// takes an optional timeout that overrides its default here.
+///
+/// ```ignore
+/// client.set_timeouts(GattTimeouts::default().with_read(Duration::from_secs(5)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GattTimeouts {
+    /// Connecting, disconnecting, registering and PHY updates.
+    pub connect: Duration,
+    /// Service discovery, including reading back the database.
+    pub discovery: Duration,
+    pub read: Duration,
+    pub write: Duration,
+    pub mtu: Duration,
+    /// Connection parameter updates, until the controller reports the result.
+    pub conn_update: Duration,
+}
+
+impl Default for GattTimeouts {
+    fn default() -> Self {
+        // A single ATT request times out after 30 seconds (GATT_TRANS_TIMEOUT
+        // in stack/gatt/gatt_int.h), and discovery takes many of them.
+        GattTimeouts {
+            connect: Duration::from_secs(30),
+            discovery: Duration::from_secs(60),
+            read: Duration::from_secs(30),
+            write: Duration::from_secs(30),
+            mtu: Duration::from_secs(30),
+            conn_update: Duration::from_secs(30),
+        }
     }
+}
 
-    pub fn unregister_server(&self, server_if: i32) -> BtStatus {
-        BtStatus::from(ccall!(self, unregister_server, server_if))
+impl GattTimeouts {
+    pub fn with_connect(mut self, timeout: Duration) -> Self {
+        self.connect = timeout;
+        self
     }
 
-    pub fn connect(
-        &self,
-        server_if: i32,
-        addr: &RawAddress,
-        is_direct: bool,
-        transport: i32,
-    ) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(self, connect, server_if, ffi_addr, is_direct, transport))
+    pub fn with_discovery(mut self, timeout: Duration) -> Self {
+        self.discovery = timeout;
+        self
     }
 
-    pub fn disconnect(&self, server_if: i32, addr: &RawAddress, conn_id: i32) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(self, disconnect, server_if, ffi_addr, conn_id))
+    pub fn with_read(mut self, timeout: Duration) -> Self {
+        self.read = timeout;
+        self
     }
 
-    pub fn add_service(&self, server_if: i32, service: &[BtGattDbElement]) -> BtStatus {
-        BtStatus::from(ccall!(self, add_service, server_if, service.as_ptr(), service.len()))
+    pub fn with_write(mut self, timeout: Duration) -> Self {
+        self.write = timeout;
+        self
     }
 
-    pub fn stop_service(&self, server_if: i32, service_handle: i32) -> BtStatus {
-        BtStatus::from(ccall!(self, stop_service, server_if, service_handle))
+    pub fn with_mtu(mut self, timeout: Duration) -> Self {
+        self.mtu = timeout;
+        self
     }
 
-    pub fn delete_service(&self, server_if: i32, service_handle: i32) -> BtStatus {
-        BtStatus::from(ccall!(self, delete_service, server_if, service_handle))
+    pub fn with_conn_update(mut self, timeout: Duration) -> Self {
+        self.conn_update = timeout;
+        self
     }
+}
 
-    pub fn send_indication(
-        &self,
-        server_if: i32,
-        attribute_handle: i32,
-        conn_id: i32,
-        confirm: i32,
-        value: &[u8],
-    ) -> BtStatus {
-        BtStatus::from(ccall!(
-            self,
-            send_indication,
-            server_if,
-            attribute_handle,
-            conn_id,
-            confirm,
-            value.as_ptr(),
-            value.len()
-        ))
+/// How long the async |GattServer| operations wait for the callback that
+/// completes them before failing with |GattOpError::Timeout|. Each operation
+/// takes an optional timeout that overrides its default here.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GattServerTimeouts {
+    /// Adding a service until its handles are assigned.
+    pub service: Duration,
+    /// Sending an indication until the peer confirms it.
+    pub indication: Duration,
+}
+
+impl Default for GattServerTimeouts {
+    fn default() -> Self {
+        GattServerTimeouts {
+            service: Duration::from_secs(30),
+            // The peer has GATT_TRANS_TIMEOUT to confirm an indication.
+            indication: Duration::from_secs(30),
+        }
     }
+}
 
-    pub fn send_response(
-        &self,
-        conn_id: i32,
-        trans_id: i32,
-        status: i32,
-        response: &BtGattResponse,
-    ) -> BtStatus {
-        BtStatus::from(ccall!(self, send_response, conn_id, trans_id, status, response))
+impl GattServerTimeouts {
+    pub fn with_service(mut self, timeout: Duration) -> Self {
+        self.service = timeout;
+        self
     }
 
-    pub fn set_preferred_phy(
-        &self,
-        addr: &RawAddress,
-        tx_phy: u8,
-        rx_phy: u8,
-        phy_options: u16,
-    ) -> BtStatus {
-        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
-        BtStatus::from(ccall!(self, set_preferred_phy, ffi_addr, tx_phy, rx_phy, phy_options))
+    pub fn with_indication(mut self, timeout: Duration) -> Self {
+        self.indication = timeout;
+        self
     }
+}
 
-    // TODO(b/193916778): Figure out how to shim read_phy which accepts base::Callback
+// Waits for the result sent through |rx|. A sender dropped without a result
+// means the connection went away or the request was replaced by a newer one.
+async fn wait_for<T, E: Into<GattOpError>>(
+    rx: oneshot::Receiver<Result<T, E>>,
+    timeout: Duration,
+) -> Result<T, GattOpError> {
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(result)) => result.map_err(Into::into),
+        // The sender was dropped, e.g. because the connection went away.
+        Ok(Err(_)) => Err(GattStatus::Error.into()),
+        Err(_) => Err(GattOpError::Timeout),
+    }
 }
 
-pub struct BleScanner {
-    _internal: RawBleScannerWrapper,
-    internal_cxx: cxx::UniquePtr<ffi::BleScannerIntf>,
+/// Read or write started by |GattClient::start_read_characteristic| or
+/// |GattClient::start_write_characteristic|. Its id can be passed to
+/// |GattClient::cancel_operation| until it completes.
+pub struct GattOp<T> {
+    id: GattOpId,
+    rx: oneshot::Receiver<Result<T, GattOpError>>,
+    timeout: Duration,
 }
 
-impl BleScanner {
-    pub(crate) fn new(
-        raw_gatt: *const btgatt_interface_t,
-        internal_cxx: cxx::UniquePtr<ffi::BleScannerIntf>,
-    ) -> Self {
-        BleScanner {
-            _internal: RawBleScannerWrapper {
-                _raw: unsafe { (*raw_gatt).scanner as *const BleScannerInterface },
-            },
-            internal_cxx,
-        }
+impl<T> GattOp<T> {
+    pub fn id(&self) -> GattOpId {
+        self.id
     }
 
-    pub fn register_scanner(&mut self, app_uuid: Uuid) {
-        mutcxxcall!(self, RegisterScanner, app_uuid.into());
+    /// Waits for the operation to complete or be cancelled.
+    pub async fn wait(self) -> Result<T, GattOpError> {
+        wait_for(self.rx, self.timeout).await
     }
+}
 
-    pub fn unregister(&mut self, scanner_id: u8) {
-        mutcxxcall!(self, Unregister, scanner_id);
-    }
+/// Outcome of |GattClient::read_all_by_uuid|, per characteristic in handle
+/// order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UuidReadResults {
+    /// Value handle and value of each characteristic that was read.
+    pub values: Vec<(u16, Vec<u8>)>,
+    /// Value handle of each characteristic that couldn't be read, and why.
+    pub errors: Vec<(u16, GattOpError)>,
+}
+
+impl GattClient {
+    /// Resolves pending async requests that |cb| completes. Returns the callback
+    /// to dispatch, which carries the op id for requests issued with one.
+    fn on_callback(
+        pending: &Mutex<PendingClientRequests>,
+        metrics: &GattMetrics,
+        cb: GattClientCallbacks,
+    ) -> GattClientCallbacks {
+        let to_result = |status: i32| match GattStatus::from(status as u8) {
+            GattStatus::Success => Ok(()),
+            status => Err(status),
+        };
+
+        match &cb {
+            GattClientCallbacks::ReadCharacteristic(_, status, _)
+            | GattClientCallbacks::ReadDescriptor(_, status, _)
+            | GattClientCallbacks::ReadMultiple(_, status, _) => metrics.reads.completed(*status),
+            GattClientCallbacks::WriteCharacteristic(_, status, _, _)
+            | GattClientCallbacks::WriteDescriptor(_, status, _, _, _) => {
+                metrics.writes.completed(*status)
+            }
+            _ => (),
+        }
+
+        match cb {
+            GattClientCallbacks::ReadCharacteristic(conn_id, status, params) => {
+                let mut pending = pending.lock().unwrap();
+                let key = (conn_id, params.handle);
+                match PendingClientRequests::pop_op(&mut pending.reads, key) {
+                    // Nobody is waiting for the result any more.
+                    Some((op_id, _)) if pending.cancelled.remove(&op_id) => (),
+                    Some((op_id, true)) => {
+                        return GattClientCallbacks::ReadCharacteristicWithId(
+                            op_id, conn_id, status, params,
+                        );
+                    }
+                    Some((op_id, false)) => {
+                        if let Some((_, tx)) = pending.read_all_ops.remove(&op_id) {
+                            let _ = tx.send(ReadAllEvent::Read(params.clone()));
+                        }
+                        if let Some((_, OpWaiter::Read(tx))) = pending.op_waiters.remove(&op_id) {
+                            let value = params.value.clone();
+                            let _ = tx.send(to_result(status).map(|_| value).map_err(Into::into));
+                        }
+                    }
+                    // Reads by uuid complete through the same callback, with
+                    // the handle of the characteristic that matched.
+                    None => match pending.pop_uuid_read(conn_id) {
+                        Some((op_id, _)) if pending.cancelled.remove(&op_id) => (),
+                        Some((_, Some(tx))) => {
+                            let value = params.value.clone();
+                            let _ = tx.send(
+                                to_result(status)
+                                    .map(|_| (params.handle, value))
+                                    .map_err(Into::into),
+                            );
+                        }
+                        _ => (),
+                    },
+                }
+                return GattClientCallbacks::ReadCharacteristic(conn_id, status, params);
+            }
+            GattClientCallbacks::WriteCharacteristic(conn_id, status, handle, value) => {
+                let mut pending = pending.lock().unwrap();
+                let key = (conn_id, handle);
+                match PendingClientRequests::pop_op(&mut pending.writes, key) {
+                    Some((op_id, _)) if pending.cancelled.remove(&op_id) => {
+                        pending.no_response_writes.remove(&op_id);
+                    }
+                    Some((op_id, true)) => {
+                        return GattClientCallbacks::WriteCharacteristicWithId(
+                            op_id, conn_id, status, handle, value,
+                        );
+                    }
+                    Some((op_id, false)) => {
+                        pending.no_response_writes.remove(&op_id);
+                        if let Some((_, OpWaiter::Write(tx))) = pending.op_waiters.remove(&op_id) {
+                            let _ = tx.send(to_result(status).map_err(Into::into));
+                        }
+                    }
+                    None => (),
+                }
+                return GattClientCallbacks::WriteCharacteristic(conn_id, status, handle, value);
+            }
+            _ => (),
+        }
 
-    pub fn start_scan(&mut self) {
-        mutcxxcall!(self, Scan, true);
+        match &cb {
+            GattClientCallbacks::RegisterClient(status, client_if, uuid) => {
+                if let Some(tx) = pending.lock().unwrap().registrations.remove(&uuid.uu) {
+                    let _ = tx.send(to_result(*status).map(|_| *client_if));
+                }
+            }
+            GattClientCallbacks::Connect(conn_id, status, client_if, addr) => {
+                let mut pending = pending.lock().unwrap();
+                if *status == GattStatus::Success as i32 {
+                    pending.conn_addrs.insert(*conn_id, *addr);
+                    pending.notify_state(*conn_id, *addr, ConnState::Connected);
+                }
+                if let Some(tx) = pending.connects.remove(&(*client_if, *addr)) {
+                    let _ = tx.send(to_result(*status).map(|_| *conn_id));
+                }
+            }
+            GattClientCallbacks::SearchComplete(conn_id, status) => {
+                if let Some(tx) = pending.lock().unwrap().searches.remove(conn_id) {
+                    let _ = tx.send(to_result(*status));
+                }
+            }
+            GattClientCallbacks::GetGattDb(conn_id, elements, _) => {
+                let mut pending = pending.lock().unwrap();
+                pending.databases.insert(*conn_id, GattDatabase::from_elements(elements.clone()));
+                if let Some(tx) = pending.gatt_dbs.remove(conn_id) {
+                    let _ = tx.send(elements.clone());
+                }
+            }
+            GattClientCallbacks::Notify(_, data) => {
+                let mut pending = pending.lock().unwrap();
+                if let Some(subscribers) = pending.subscriptions.get_mut(&(data.addr, data.handle))
+                {
+                    // Streams that were dropped unsubscribe themselves, but
+                    // the receiver may also have been closed.
+                    subscribers.retain(|(_, tx)| tx.send(data.clone()).is_ok());
+                }
+            }
+            GattClientCallbacks::ReadRemoteRssi(client_if, addr, rssi, status) => {
+                if let Some(tx) = pending.lock().unwrap().rssi_reads.remove(&(*client_if, *addr)) {
+                    let _ = tx.send(to_result(*status).map(|_| *rssi));
+                }
+            }
+            GattClientCallbacks::ConfigureMtu(conn_id, status, mtu) => {
+                let mut pending = pending.lock().unwrap();
+                // A failed exchange leaves the MTU unchanged.
+                if GattStatus::from(*status as u8) == GattStatus::Success {
+                    pending.mtus.insert(*conn_id, *mtu);
+                }
+                if let Some(tx) = pending.mtu_exchanges.remove(conn_id) {
+                    let _ = tx.send(to_result(*status).map(|_| *mtu as u16));
+                }
+            }
+            GattClientCallbacks::MtuChanged(conn_id, mtu) => {
+                pending.lock().unwrap().mtus.insert(*conn_id, *mtu);
+            }
+            GattClientCallbacks::PhyUpdated(conn_id, tx_phy, rx_phy, status) => {
+                let mut pending = pending.lock().unwrap();
+                let tx = match pending.conn_addrs.get(conn_id).copied() {
+                    Some(addr) => pending.phy_updates.remove(&addr),
+                    None => None,
+                };
+                if let Some(tx) = tx {
+                    // A rejected update still reports the PHYs in use, which
+                    // are then the old ones.
+                    let result = match (tx_phy, rx_phy) {
+                        (Ok(tx_phy), Ok(rx_phy)) => Ok((*tx_phy, *rx_phy)),
+                        _ => match GattStatus::from(*status) {
+                            GattStatus::Success => Err(GattStatus::Error),
+                            status => Err(status),
+                        },
+                    };
+                    let _ = tx.send(result);
+                }
+            }
+            GattClientCallbacks::ConnUpdated(conn_id, params, status) => {
+                if let Some(tx) = pending.lock().unwrap().conn_updates.remove(conn_id) {
+                    // A rejected update reports the parameters still in use,
+                    // unless the controller reported none at all.
+                    let result = match status {
+                        GattStatus::Success => Ok(*params),
+                        _ if params.interval_ms > 0.0 => Ok(*params),
+                        status => Err(*status),
+                    };
+                    let _ = tx.send(result);
+                }
+            }
+            GattClientCallbacks::Congestion(conn_id, congested) => {
+                let mut pending = pending.lock().unwrap();
+                if let Some(addr) = pending.conn_addrs.get(conn_id).copied() {
+                    let changed = if *congested {
+                        pending.congested.insert(*conn_id)
+                    } else {
+                        pending.congested.remove(conn_id)
+                    };
+                    if changed && !*congested {
+                        for (_, tx) in pending.read_alls.get(conn_id).into_iter().flatten() {
+                            let _ = tx.send(ReadAllEvent::Uncongested);
+                        }
+                    }
+                    if changed {
+                        let state = pending.conn_state(*conn_id);
+                        pending.notify_state(*conn_id, addr, state);
+                    }
+                }
+            }
+            GattClientCallbacks::Disconnect(conn_id, status, _, _) => {
+                let mut pending = pending.lock().unwrap();
+                pending.mtus.remove(conn_id);
+                pending.mtu_exchanges.remove(conn_id);
+                pending.conn_updates.remove(conn_id);
+                pending.searches.remove(conn_id);
+                pending.gatt_dbs.remove(conn_id);
+                for op_id in pending.ops_on(*conn_id) {
+                    pending.cancelled.remove(&op_id);
+                }
+                pending.reads.retain(|(id, _), _| id != conn_id);
+                pending.writes.retain(|(id, _), _| id != conn_id);
+                pending.uuid_reads.remove(conn_id);
+                // Fails the futures still waiting.
+                pending.op_waiters.retain(|_, (id, _)| id != conn_id);
+                pending.queued_writes.remove(conn_id);
+                pending.no_response_writes.retain(|_, id| id != conn_id);
+                pending.congested.remove(conn_id);
+                pending.databases.remove(conn_id);
+                pending.rediscovers.remove(conn_id);
+                // Ends the |read_all| streams on this connection.
+                pending.read_alls.remove(conn_id);
+                pending.read_all_ops.retain(|_, (id, _)| id != conn_id);
+                if let Some(addr) = pending.conn_addrs.remove(conn_id) {
+                    pending.phy_updates.remove(&addr);
+                    // Ends the streams subscribed on this connection.
+                    pending.subscriptions.retain(|(sub_addr, _), _| *sub_addr != addr);
+                    pending.notify_state(*conn_id, addr, ConnState::Disconnected);
+                }
+                if let Some(tx) = pending.disconnects.remove(conn_id) {
+                    let _ = tx.send(to_result(*status));
+                }
+            }
+            _ => (),
+        }
+
+        cb
     }
 
-    pub fn stop_scan(&mut self) {
-        mutcxxcall!(self, Scan, false);
+    /// Rediscovers the remote database after |GattClientCallbacks::ServiceChanged|
+    /// if |set_auto_rediscover| is on: refreshes the cache, searches all
+    /// services and reads the database once the search completes. Returns the
+    /// callback to dispatch.
+    fn on_rediscover_callback(
+        pending: &Mutex<PendingClientRequests>,
+        raw: &RawGattClientWrapper,
+        cb: GattClientCallbacks,
+    ) -> GattClientCallbacks {
+        match cb {
+            GattClientCallbacks::ServiceChanged(conn_id) => {
+                let addr = {
+                    let pending = pending.lock().unwrap();
+                    match pending.conn_addrs.get(&conn_id) {
+                        Some(addr) if pending.auto_rediscover => *addr,
+                        _ => return cb,
+                    }
+                };
+
+                // btif refreshes by address alone and ignores the client_if.
+                let ffi_addr = cast_to_ffi_address!(&addr as *const RawAddress);
+                let refreshed = unsafe { ((*raw.raw).refresh.unwrap())(0, ffi_addr) };
+                if BtStatus::from(refreshed) != BtStatus::Success {
+                    return cb;
+                }
+
+                pending.lock().unwrap().rediscovers.insert(conn_id);
+                let searched =
+                    unsafe { ((*raw.raw).search_service.unwrap())(conn_id, std::ptr::null()) };
+                if BtStatus::from(searched) != BtStatus::Success {
+                    pending.lock().unwrap().rediscovers.remove(&conn_id);
+                }
+            }
+            GattClientCallbacks::SearchComplete(conn_id, status) => {
+                if !pending.lock().unwrap().rediscovers.contains(&conn_id) {
+                    return cb;
+                }
+
+                let read = status == GattStatus::Success as i32
+                    && BtStatus::from(unsafe { ((*raw.raw).get_gatt_db.unwrap())(conn_id) })
+                        == BtStatus::Success;
+                if !read {
+                    pending.lock().unwrap().rediscovers.remove(&conn_id);
+                }
+            }
+            GattClientCallbacks::GetGattDb(conn_id, elements, count) => {
+                if pending.lock().unwrap().rediscovers.remove(&conn_id) {
+                    return GattClientCallbacks::DatabaseRediscovered(conn_id, elements);
+                }
+                return GattClientCallbacks::GetGattDb(conn_id, elements, count);
+            }
+            _ => (),
+        }
+
+        cb
     }
 
-    pub fn scan_filter_setup(
-        &mut self,
-        scanner_id: u8,
-        action: u8,
-        filter_index: u8,
-        param: GattFilterParam,
+    /// Sends the writes without response that were queued while |conn_id| was
+    /// congested, once |GattClientCallbacks::Congestion| reports that it no
+    /// longer is.
+    fn on_flow_control_callback(
+        pending: &Mutex<PendingClientRequests>,
+        metrics: &GattMetrics,
+        raw: &RawGattClientWrapper,
+        cb: &GattClientCallbacks,
     ) {
-        mutcxxcall!(self, ScanFilterParamSetup, scanner_id, action, filter_index, param);
+        let conn_id = match cb {
+            GattClientCallbacks::Congestion(conn_id, false) => *conn_id,
+            _ => return,
+        };
+
+        loop {
+            let write = {
+                let mut pending = pending.lock().unwrap();
+                if pending.congested.contains(&conn_id) {
+                    return;
+                }
+                let queue = match pending.queued_writes.get_mut(&conn_id) {
+                    Some(queue) => queue,
+                    None => return,
+                };
+                let write = queue.pop_front();
+                if queue.is_empty() {
+                    pending.queued_writes.remove(&conn_id);
+                }
+                match write {
+                    Some(write) => write,
+                    None => return,
+                }
+            };
+
+            // A write that fails to go out is dropped, like one sent directly.
+            GattClient::issue_no_response_write(pending, metrics, raw, conn_id, &write);
+        }
     }
 
-    pub fn scan_filter_add(&mut self, filter_index: u8, filters: Vec<ApcfCommand>) {
-        mutcxxcall!(self, ScanFilterAdd, filter_index, filters);
+    // Sends a write without response and tracks it until it completes.
+    fn issue_no_response_write(
+        pending: &Mutex<PendingClientRequests>,
+        metrics: &GattMetrics,
+        raw: &RawGattClientWrapper,
+        conn_id: i32,
+        write: &QueuedWrite,
+    ) -> BtStatus {
+        let key = (conn_id, write.handle);
+        let op_id = {
+            let mut pending = pending.lock().unwrap();
+            let op_id = pending.next_op_id;
+            pending.next_op_id += 1;
+            pending.writes.entry(key).or_default().push_back((op_id, false));
+            pending.no_response_writes.insert(op_id, conn_id);
+            op_id
+        };
+
+        let status = metrics.writes.issued(BtStatus::from(unsafe {
+            ((*raw.raw).write_characteristic.unwrap())(
+                conn_id,
+                write.handle,
+                GattWriteType::NoResponse as i32,
+                write.auth_req as i32,
+                write.value.as_ptr(),
+                write.value.len(),
+                GattBearer::to_raw(None),
+            )
+        }));
+        if status != BtStatus::Success {
+            let mut pending = pending.lock().unwrap();
+            PendingClientRequests::remove_op(&mut pending.writes, key, op_id);
+            pending.no_response_writes.remove(&op_id);
+        }
+
+        status
     }
 
-    pub fn scan_filter_clear(&mut self, filter_index: u8) {
-        mutcxxcall!(self, ScanFilterClear, filter_index);
+    // Records a read or write on (conn_id, handle) before issuing it so that its
+    // completion can be matched, and forgets it again if |issue| fails.
+    fn track_op(
+        &self,
+        is_read: bool,
+        conn_id: i32,
+        handle: u16,
+        with_id: bool,
+        issue: impl FnOnce() -> BtStatus,
+    ) -> Result<GattOpId, BtStatus> {
+        self.track_op_with_waiter(is_read, conn_id, handle, with_id, None, issue)
     }
 
-    pub fn scan_filter_enable(&mut self) {
-        mutcxxcall!(self, ScanFilterEnable, true);
+    // Like |track_op|, but resolves |waiter| once the op completes.
+    fn track_op_with_waiter(
+        &self,
+        is_read: bool,
+        conn_id: i32,
+        handle: u16,
+        with_id: bool,
+        waiter: Option<OpWaiter>,
+        issue: impl FnOnce() -> BtStatus,
+    ) -> Result<GattOpId, BtStatus> {
+        let key = (conn_id, handle);
+        let op_id = {
+            let mut pending = self.pending.lock().unwrap();
+            let op_id = pending.next_op_id;
+            pending.next_op_id += 1;
+            let ops = if is_read { &mut pending.reads } else { &mut pending.writes };
+            ops.entry(key).or_default().push_back((op_id, with_id));
+            if let Some(waiter) = waiter {
+                pending.op_waiters.insert(op_id, (conn_id, waiter));
+            }
+            op_id
+        };
+
+        let counters = if is_read { &self.metrics.reads } else { &self.metrics.writes };
+        let status = counters.issued(issue());
+        if status != BtStatus::Success {
+            let mut pending = self.pending.lock().unwrap();
+            let ops = if is_read { &mut pending.reads } else { &mut pending.writes };
+            PendingClientRequests::remove_op(ops, key, op_id);
+            pending.op_waiters.remove(&op_id);
+            return Err(status);
+        }
+
+        Ok(op_id)
     }
 
-    pub fn scan_filter_disable(&mut self) {
-        mutcxxcall!(self, ScanFilterEnable, false);
+    /// Registers a client. With |eatt_support| set, requests use an available
+    /// enhanced bearer and fall back to the unenhanced one; otherwise they use
+    /// the unenhanced bearer. Reads and writes given a |GattBearer| override
+    /// this.
+    pub fn register_client(&self, uuid: &Uuid, eatt_support: bool) -> BtStatus {
+        BtStatus::from(ccall!(self, register_client, uuid, eatt_support))
     }
 
-    pub fn set_scan_parameters(&mut self, scanner_id: u8, scan_interval: u16, scan_window: u16) {
-        mutcxxcall!(self, SetScanParameters, scanner_id, scan_interval, scan_window);
+    /// Timeouts used by the async operations that aren't given one.
+    pub fn timeouts(&self) -> GattTimeouts {
+        self.timeouts
     }
 
-    pub fn batchscan_config_storage(
-        &mut self,
-        scanner_id: u8,
-        full_max: i32,
-        trunc_max: i32,
-        notify_threshold: i32,
-    ) {
-        mutcxxcall!(
-            self,
-            BatchscanConfigStorage,
-            scanner_id,
-            full_max,
-            trunc_max,
-            notify_threshold
-        );
+    pub fn set_timeouts(&mut self, timeouts: GattTimeouts) {
+        self.timeouts = timeouts;
     }
 
-    pub fn batchscan_enable(
-        &mut self,
-        scan_mode: i32,
-        scan_interval: u16,
-        scan_window: u16,
-        addr_type: i32,
-        discard_rule: i32,
-    ) {
-        mutcxxcall!(
-            self,
-            BatchscanEnable,
-            scan_mode,
-            scan_interval,
-            scan_window,
-            addr_type,
-            discard_rule
-        );
+    /// Registers a client and resolves with its client_if once
+    /// |GattClientCallbacks::RegisterClient| arrives for |uuid|. The callback is
+    /// still dispatched as usual.
+    pub async fn register_client_async(
+        &self,
+        uuid: Uuid,
+        eatt_support: bool,
+        timeout: Option<Duration>,
+    ) -> Result<i32, GattOpError> {
+        let (tx, rx) = oneshot::channel();
+        // A previous request for the same uuid resolves with an error.
+        self.pending.lock().unwrap().registrations.insert(uuid.uu, tx);
+
+        if self.register_client(&uuid, eatt_support) != BtStatus::Success {
+            self.pending.lock().unwrap().registrations.remove(&uuid.uu);
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, timeout.unwrap_or(self.timeouts.connect)).await
     }
 
-    pub fn batchscan_disable(&mut self) {
-        mutcxxcall!(self, BatchscanDisable);
+    pub fn unregister_client(&self, client_if: i32) -> BtStatus {
+        // The stack drops the background connects of the client along with it.
+        self.pending.lock().unwrap().background_connects.retain(|(id, _)| *id != client_if);
+        BtStatus::from(ccall!(self, unregister_client, client_if))
     }
 
-    pub fn batchscan_read_reports(&mut self, scanner_id: u8, scan_mode: i32) {
-        mutcxxcall!(self, BatchscanReadReports, scanner_id, scan_mode);
+    /// Connects to |addr| using the address type stored for it, or public if
+    /// the device hasn't been seen. See |connect_with_addr_type|.
+    pub fn connect(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        is_direct: bool,
+        transport: BtTransport,
+        opportunistic: bool,
+        initiating_phys: i32,
+    ) -> BtStatus {
+        self.connect_with_addr_type(
+            client_if,
+            addr,
+            BleAddressType::Public,
+            is_direct,
+            transport,
+            opportunistic,
+            initiating_phys,
+        )
     }
 
-    pub fn start_sync(&mut self, sid: u8, address: RawAddress, skip: u16, timeout: u16) {
-        let addr = unsafe { *((&address as *const RawAddress) as *const ffi::RustRawAddress) };
-        mutcxxcall!(self, StartSync, sid, addr, skip, timeout);
+    /// Like |connect|, with the PHYs to connect on checked against what the
+    /// controller supports first. None picks |InitiatingPhys::default_for|
+    /// the controller; connecting over |InitiatingPhys::LE_CODED| reaches
+    /// further.
+    ///
+    /// Returns |BtStatus::InvalidParam| if |phys| has neither the 1M nor the
+    /// Coded PHY, and |BtStatus::Unsupported| if the controller lacks one of
+    /// them.
+    pub fn connect_with_phys(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        is_direct: bool,
+        transport: BtTransport,
+        opportunistic: bool,
+        phys: Option<InitiatingPhys>,
+    ) -> BtStatus {
+        let capabilities = GattCapabilities::query();
+        let phys = phys.unwrap_or_else(|| InitiatingPhys::default_for(&capabilities));
+        match phys.check(&capabilities) {
+            BtStatus::Success => {
+                self.connect(client_if, addr, is_direct, transport, opportunistic, phys.bits())
+            }
+            status => status,
+        }
     }
 
-    pub fn stop_sync(&mut self, handle: u16) {
-        mutcxxcall!(self, StopSync, handle);
+    /// Connects to |addr| of type |addr_type|. Non-public types are used as
+    /// given, so a device with a static random address can be connected to
+    /// without having been seen first. A public type falls back to the type
+    /// stored for |addr|, like |connect|.
+    ///
+    /// Returns |BtStatus::InvalidParam| for anonymous and unknown types, which
+    /// can't be connected to.
+    pub fn connect_with_addr_type(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        addr_type: BleAddressType,
+        is_direct: bool,
+        transport: BtTransport,
+        opportunistic: bool,
+        initiating_phys: i32,
+    ) -> BtStatus {
+        if let BleAddressType::Anonymous | BleAddressType::Unknown(_) = addr_type {
+            return BtStatus::InvalidParam;
+        }
+
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        let status = BtStatus::from(ccall!(
+            self,
+            connect,
+            client_if,
+            ffi_addr,
+            u8::from(addr_type),
+            is_direct,
+            i32::from(transport),
+            opportunistic,
+            initiating_phys
+        ));
+
+        // Opportunistic connects never initiate, so there's nothing to cancel.
+        if status == BtStatus::Success && !is_direct && !opportunistic {
+            self.pending.lock().unwrap().background_connects.insert((client_if, *addr));
+        }
+        status
     }
 
-    pub fn cancel_create_sync(&mut self, sid: u8, address: RawAddress) {
-        let addr = unsafe { *((&address as *const RawAddress) as *const ffi::RustRawAddress) };
-        mutcxxcall!(self, CancelCreateSync, sid, addr);
+    /// Attaches |client_if| to the link with |addr| without ever initiating it.
+    ///
+    /// No |GattClientCallbacks::Connect| arrives until another client or
+    /// profile establishes the link, at which point this client is connected
+    /// as well. Useful for background clients such as battery service readers.
+    pub fn connect_opportunistic(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        transport: BtTransport,
+    ) -> BtStatus {
+        // The PHY mask is unused since this client never initiates; 1M is the
+        // stack default.
+        let initiating_phys = 1 << (LePhy::Phy1m as i32 - 1);
+        self.connect(client_if, addr, false, transport, true, initiating_phys)
     }
 
-    pub fn transfer_sync(&mut self, address: RawAddress, service_data: u16, sync_handle: u16) {
-        let addr = unsafe { *((&address as *const RawAddress) as *const ffi::RustRawAddress) };
-        mutcxxcall!(self, TransferSync, addr, service_data, sync_handle);
+    /// Closes |conn_id|, or cancels a pending connect if |conn_id| is 0. Any
+    /// background connect of |client_if| to |addr| is cancelled either way.
+    pub fn disconnect(&self, client_if: i32, addr: &RawAddress, conn_id: i32) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        let status = BtStatus::from(ccall!(self, disconnect, client_if, ffi_addr, conn_id));
+        if status == BtStatus::Success {
+            self.pending.lock().unwrap().background_connects.remove(&(client_if, *addr));
+        }
+        status
     }
 
-    pub fn transfer_set_info(&mut self, address: RawAddress, service_data: u16, adv_handle: u8) {
-        let addr = unsafe { *((&address as *const RawAddress) as *const ffi::RustRawAddress) };
-        mutcxxcall!(self, TransferSetInfo, addr, service_data, adv_handle);
+    /// Cancels the pending direct or background connect of |client_if| to
+    /// |addr|.
+    ///
+    /// If the connect already succeeded the connection stays open; only the
+    /// automatic reconnection after it drops is cancelled. Use |disconnect|
+    /// to close the connection as well.
+    pub fn cancel_connect(&self, client_if: i32, addr: &RawAddress) -> BtStatus {
+        self.disconnect(client_if, addr, 0)
     }
 
-    pub fn sync_tx_parameters(&mut self, address: RawAddress, mode: u8, skip: u16, timeout: u16) {
-        let addr = unsafe { *((&address as *const RawAddress) as *const ffi::RustRawAddress) };
-        mutcxxcall!(self, SyncTxParameters, addr, mode, skip, timeout);
+    /// Whether |client_if| has a background connect to |addr| that hasn't
+    /// been cancelled, regardless of whether it's currently connected.
+    pub fn has_background_connect(&self, client_if: i32, addr: &RawAddress) -> bool {
+        self.pending.lock().unwrap().background_connects.contains(&(client_if, *addr))
     }
-}
 
-pub struct BleAdvertiser {
-    _internal: RawBleAdvertiserWrapper,
-    internal_cxx: cxx::UniquePtr<ffi::BleAdvertiserIntf>,
-}
+    /// Remote addresses |client_if| has background connects to.
+    pub fn background_connects(&self, client_if: i32) -> Vec<RawAddress> {
+        let pending = self.pending.lock().unwrap();
+        pending
+            .background_connects
+            .iter()
+            .filter(|(id, _)| *id == client_if)
+            .map(|(_, a)| *a)
+            .collect()
+    }
 
-impl BleAdvertiser {
-    pub(crate) fn new(
-        raw_gatt: *const btgatt_interface_t,
-        internal_cxx: cxx::UniquePtr<ffi::BleAdvertiserIntf>,
-    ) -> Self {
-        BleAdvertiser {
-            _internal: RawBleAdvertiserWrapper {
-                _raw: unsafe { (*raw_gatt).advertiser as *const BleAdvertiserInterface },
-            },
-            internal_cxx,
+    /// Connects and resolves with the conn_id once |GattClientCallbacks::Connect|
+    /// arrives for (|client_if|, |addr|). If that doesn't happen within |timeout|,
+    /// or |GattTimeouts::connect| if none is given, the connection attempt is
+    /// cancelled.
+    pub async fn connect_async(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        is_direct: bool,
+        transport: BtTransport,
+        opportunistic: bool,
+        initiating_phys: i32,
+        timeout: Option<Duration>,
+    ) -> Result<i32, GattOpError> {
+        let key = (client_if, *addr);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().connects.insert(key, tx);
+
+        let status =
+            self.connect(client_if, addr, is_direct, transport, opportunistic, initiating_phys);
+        if status != BtStatus::Success {
+            self.pending.lock().unwrap().connects.remove(&key);
+            return Err(GattStatus::Error.into());
+        }
+
+        let result = wait_for(rx, timeout.unwrap_or(self.timeouts.connect)).await;
+        if result == Err(GattOpError::Timeout) {
+            self.pending.lock().unwrap().connects.remove(&key);
+            // A conn_id of 0 cancels the pending connection.
+            self.disconnect(client_if, addr, 0);
         }
+        result
     }
 
-    pub fn register_advertiser(&mut self) {
-        mutcxxcall!(self, RegisterAdvertiser);
+    /// Disconnects and resolves once |GattClientCallbacks::Disconnect| arrives
+    /// for |conn_id|.
+    pub async fn disconnect_async(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        conn_id: i32,
+        timeout: Option<Duration>,
+    ) -> Result<(), GattOpError> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().disconnects.insert(conn_id, tx);
+
+        if self.disconnect(client_if, addr, conn_id) != BtStatus::Success {
+            self.pending.lock().unwrap().disconnects.remove(&conn_id);
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, timeout.unwrap_or(self.timeouts.connect)).await
     }
 
-    pub fn unregister(&mut self, adv_id: u8) {
-        mutcxxcall!(self, Unregister, adv_id);
+    /// Clears the attribute cache of |addr| and rediscovers its services.
+    /// Returns |BtStatus::RemoteDeviceDown| if no client is connected to |addr|.
+    ///
+    /// Returns once the request is queued. Use |refresh_async| to wait for the
+    /// refreshed database.
+    pub fn refresh(&self, client_if: i32, addr: &RawAddress) -> BtStatus {
+        if !self.pending.lock().unwrap().is_connected(addr) {
+            return BtStatus::RemoteDeviceDown;
+        }
+
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(self, refresh, client_if, ffi_addr))
     }
 
-    pub fn get_own_address(&mut self, adv_id: u8) {
-        mutcxxcall!(self, GetOwnAddress, adv_id);
+    /// Whether to rediscover the remote database whenever
+    /// |GattClientCallbacks::ServiceChanged| arrives. The new database is
+    /// dispatched as |GattClientCallbacks::DatabaseRediscovered|. Off by
+    /// default.
+    pub fn set_auto_rediscover(&self, enable: bool) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.auto_rediscover = enable;
+        if !enable {
+            pending.rediscovers.clear();
+        }
     }
 
-    pub fn set_parameters(&mut self, adv_id: u8, params: AdvertiseParameters) {
-        mutcxxcall!(self, SetParameters, adv_id, params);
+    pub fn search_service(&self, conn_id: i32, filter_uuid: Option<Uuid>) -> BtStatus {
+        let filter_uuid_ptr = match filter_uuid {
+            None => std::ptr::null(),
+            Some(uuid) => &uuid,
+        };
+
+        BtStatus::from(ccall!(self, search_service, conn_id, filter_uuid_ptr))
     }
-    pub fn set_data(&mut self, adv_id: u8, set_scan_rsp: bool, data: Vec<u8>) {
-        mutcxxcall!(self, SetData, adv_id, set_scan_rsp, data);
+
+    pub fn btif_gattc_discover_service_by_uuid(&self, conn_id: i32, uuid: &Uuid) {
+        ccall!(self, btif_gattc_discover_service_by_uuid, conn_id, uuid)
     }
-    pub fn enable(&mut self, adv_id: u8, enable: bool, duration: u16, max_ext_adv_events: u8) {
-        mutcxxcall!(self, Enable, adv_id, enable, duration, max_ext_adv_events);
+
+    /// Reads a remote characteristic on |bearer|, or on the bearer picked for
+    /// the client if it's None.
+    pub fn read_characteristic(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        auth_req: i32,
+        bearer: Option<GattBearer>,
+    ) -> BtStatus {
+        let issue = || self.do_read_characteristic(conn_id, handle, auth_req, bearer);
+        self.track_op(true, conn_id, handle, false, issue).err().unwrap_or(BtStatus::Success)
     }
-    pub fn start_advertising(
-        &mut self,
-        adv_id: u8,
-        params: AdvertiseParameters,
-        advertise_data: Vec<u8>,
-        scan_response_data: Vec<u8>,
-        timeout_in_sec: i32,
-    ) {
-        mutcxxcall!(
-            self,
-            StartAdvertising,
-            adv_id,
-            params,
-            advertise_data,
-            scan_response_data,
-            timeout_in_sec
-        );
+
+    /// Like |read_characteristic|, but completes with
+    /// |GattClientCallbacks::ReadCharacteristicWithId| carrying the returned op id.
+    pub fn read_characteristic_with_id(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        auth_req: i32,
+        bearer: Option<GattBearer>,
+    ) -> Result<GattOpId, BtStatus> {
+        let issue = || self.do_read_characteristic(conn_id, handle, auth_req, bearer);
+        self.track_op(true, conn_id, handle, true, issue)
     }
-    pub fn start_advertising_set(
-        &mut self,
-        reg_id: i32,
-        params: AdvertiseParameters,
-        advertise_data: Vec<u8>,
-        scan_response_data: Vec<u8>,
-        periodic_params: PeriodicAdvertisingParameters,
-        periodic_data: Vec<u8>,
-        duration: u16,
-        max_ext_adv_events: u8,
-    ) {
-        mutcxxcall!(
-            self,
-            StartAdvertisingSet,
-            reg_id,
-            params,
-            advertise_data,
+
+    /// Like |read_characteristic|, but returns a |GattOp| that resolves with
+    /// the value read. Takes |GattTimeouts::read| unless |timeout| is given.
+    pub fn start_read_characteristic(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        auth_req: i32,
+        bearer: Option<GattBearer>,
+        timeout: Option<Duration>,
+    ) -> Result<GattOp<Vec<u8>>, BtStatus> {
+        let (tx, rx) = oneshot::channel();
+        let issue = || self.do_read_characteristic(conn_id, handle, auth_req, bearer);
+        let waiter = Some(OpWaiter::Read(tx));
+        let id = self.track_op_with_waiter(true, conn_id, handle, false, waiter, issue)?;
+        Ok(GattOp { id, rx, timeout: timeout.unwrap_or(self.timeouts.read) })
+    }
+
+    fn do_read_characteristic(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        auth_req: i32,
+        bearer: Option<GattBearer>,
+    ) -> BtStatus {
+        let bearer = GattBearer::to_raw(bearer);
+        BtStatus::from(ccall!(self, read_characteristic, conn_id, handle, auth_req, bearer))
+    }
+
+    /// Abandons the read or write |op_id| on |conn_id|, whether it was issued
+    /// with an id or through |start_read_characteristic|,
+    /// |start_write_characteristic| or |read_using_characteristic_uuid_async|.
+    /// Its future resolves with |GattOpError::Cancelled| and its completion is
+    /// dispatched without the op id. The request itself can't be recalled and
+    /// still completes on the remote. Returns false if |op_id| isn't
+    /// outstanding on |conn_id|.
+    pub fn cancel_operation(&self, conn_id: i32, op_id: GattOpId) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        // The op keeps its place in the queue until its callback arrives, so
+        // that later ops on the same handle still match their callbacks.
+        if !pending.ops_on(conn_id).contains(&op_id) || !pending.cancelled.insert(op_id) {
+            return false;
+        }
+
+        match pending.op_waiters.remove(&op_id) {
+            Some((_, OpWaiter::Read(tx))) => {
+                let _ = tx.send(Err(GattOpError::Cancelled));
+            }
+            Some((_, OpWaiter::Write(tx))) => {
+                let _ = tx.send(Err(GattOpError::Cancelled));
+            }
+            None => (),
+        }
+        let uuid_read = pending
+            .uuid_reads
+            .get_mut(&conn_id)
+            .and_then(|reads| reads.iter_mut().find(|(id, _)| *id == op_id))
+            .and_then(|(_, tx)| tx.take());
+        if let Some(tx) = uuid_read {
+            let _ = tx.send(Err(GattOpError::Cancelled));
+        }
+
+        true
+    }
+
+    /// Reads each of |handles|, keeping at most |READ_ALL_WINDOW| reads
+    /// outstanding and holding back new ones while the connection is
+    /// congested. Results are yielded as the reads complete, each with its
+    /// handle; reads that can't be issued yield |GattStatus::Error|. The
+    /// stream ends after the last result, or early if the connection goes away.
+    pub fn read_all(&self, conn_id: i32, handles: &[u16], auth_req: GattAuthReq) -> ReadAllStream {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let id = {
+            let mut pending = self.pending.lock().unwrap();
+            let id = pending.next_subscription_id;
+            pending.next_subscription_id += 1;
+            pending.read_alls.entry(conn_id).or_default().push((id, tx));
+            id
+        };
+
+        let mut stream = ReadAllStream {
+            rx,
+            id,
+            conn_id,
+            auth_req,
+            handles: handles.iter().copied().collect(),
+            in_flight: 0,
+            failed: VecDeque::new(),
+            internal: RawGattClientWrapper { raw: self.internal.raw },
+            pending: self.pending.clone(),
+            metrics: self.metrics.clone(),
+        };
+        stream.fill();
+        stream
+    }
+
+    /// Reads up to |GATT_MAX_READ_MULTI_HANDLES| handles in a single request.
+    /// The values are returned concatenated in |GattClientCallbacks::ReadMultiple|.
+    pub fn read_multiple(&self, conn_id: i32, handles: &[u16], auth_req: GattAuthReq) -> BtStatus {
+        if handles.is_empty() || handles.len() > GATT_MAX_READ_MULTI_HANDLES {
+            return BtStatus::InvalidParam;
+        }
+
+        self.metrics.reads.issued(BtStatus::from(ccall!(
+            self,
+            read_multiple,
+            conn_id,
+            handles.as_ptr(),
+            handles.len(),
+            auth_req as i32
+        )))
+    }
+
+    pub fn read_using_characteristic_uuid(
+        &self,
+        conn_id: i32,
+        uuid: &Uuid,
+        s_handle: u16,
+        e_handle: u16,
+        auth_req: i32,
+    ) -> BtStatus {
+        self.track_uuid_read(conn_id, uuid, s_handle, e_handle, auth_req, None)
+    }
+
+    /// Reads the first characteristic with |uuid| between |s_handle| and
+    /// |e_handle| and resolves with its handle and value.
+    pub async fn read_using_characteristic_uuid_async(
+        &self,
+        conn_id: i32,
+        uuid: &Uuid,
+        s_handle: u16,
+        e_handle: u16,
+        auth_req: i32,
+        timeout: Option<Duration>,
+    ) -> Result<(u16, Vec<u8>), GattOpError> {
+        let (tx, rx) = oneshot::channel();
+        let status = self.track_uuid_read(conn_id, uuid, s_handle, e_handle, auth_req, Some(tx));
+        if status != BtStatus::Success {
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, timeout.unwrap_or(self.timeouts.read)).await
+    }
+
+    /// Reads every characteristic |char_uuid| in the database last discovered
+    /// on |conn_id|, across all services, e.g. each of several sensors of the
+    /// same kind. One failed read doesn't fail the others; it is reported in
+    /// |UuidReadResults::errors| instead. |timeout| applies to each read.
+    ///
+    /// Fails with |GattStatus::Error| if services haven't been discovered yet,
+    /// and |GattStatus::NotFound| if the database has no such characteristic.
+    pub async fn read_all_by_uuid(
+        &self,
+        conn_id: i32,
+        char_uuid: &Uuid,
+        auth_req: i32,
+        timeout: Option<Duration>,
+    ) -> Result<UuidReadResults, GattStatus> {
+        let mut handles: Vec<u16> = match self.pending.lock().unwrap().databases.get(&conn_id) {
+            Some(db) => db
+                .services
+                .iter()
+                .flat_map(|s| s.characteristics.iter())
+                .filter(|c| c.uuid.uu == char_uuid.uu)
+                .map(|c| c.value_handle)
+                .collect(),
+            None => return Err(GattStatus::Error),
+        };
+        if handles.is_empty() {
+            return Err(GattStatus::NotFound);
+        }
+        handles.sort_unstable();
+
+        // All reads are queued up front; the stack sends them one at a time.
+        let ops: Vec<_> = handles
+            .into_iter()
+            .map(|handle| {
+                (handle, self.start_read_characteristic(conn_id, handle, auth_req, None, timeout))
+            })
+            .collect();
+
+        let mut results = UuidReadResults::default();
+        for (handle, op) in ops {
+            let result = match op {
+                Ok(op) => op.wait().await,
+                Err(_) => Err(GattStatus::Error.into()),
+            };
+            match result {
+                Ok(value) => results.values.push((handle, value)),
+                Err(error) => results.errors.push((handle, error)),
+            }
+        }
+        Ok(results)
+    }
+
+    // Like |track_op|, but reads by uuid are only keyed by conn_id since the
+    // handle that matches isn't known up front.
+    fn track_uuid_read(
+        &self,
+        conn_id: i32,
+        uuid: &Uuid,
+        s_handle: u16,
+        e_handle: u16,
+        auth_req: i32,
+        tx: Option<UuidReadSender>,
+    ) -> BtStatus {
+        let op_id = {
+            let mut pending = self.pending.lock().unwrap();
+            let op_id = pending.next_op_id;
+            pending.next_op_id += 1;
+            pending.uuid_reads.entry(conn_id).or_default().push_back((op_id, tx));
+            op_id
+        };
+
+        let status = self.metrics.reads.issued(BtStatus::from(ccall!(
+            self,
+            read_using_characteristic_uuid,
+            conn_id,
+            uuid,
+            s_handle,
+            e_handle,
+            auth_req
+        )));
+        if status != BtStatus::Success {
+            let mut pending = self.pending.lock().unwrap();
+            if let Some(queue) = pending.uuid_reads.get_mut(&conn_id) {
+                queue.retain(|(id, _)| *id != op_id);
+                if queue.is_empty() {
+                    pending.uuid_reads.remove(&conn_id);
+                }
+            }
+        }
+
+        status
+    }
+
+    /// Writes a remote characteristic on |bearer|, or on the bearer picked for
+    /// the client if it's None. A signed write type must come with a signed
+    /// |auth_req| and vice versa, otherwise InvalidParam is returned.
+    ///
+    /// Signed writes require a bond with a CSRK, see |GattWriteType|. Their
+    /// value must leave room for the opcode, handle and signature within the
+    /// MTU, since the stack would otherwise truncate it.
+    pub fn write_characteristic(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        write_type: GattWriteType,
+        auth_req: GattAuthReq,
+        value: &[u8],
+        bearer: Option<GattBearer>,
+    ) -> BtStatus {
+        let issue =
+            || self.do_write_characteristic(conn_id, handle, write_type, auth_req, value, bearer);
+        self.track_op(false, conn_id, handle, false, issue).err().unwrap_or(BtStatus::Success)
+    }
+
+    /// Like |write_characteristic|, but completes with
+    /// |GattClientCallbacks::WriteCharacteristicWithId| carrying the returned op id.
+    pub fn write_characteristic_with_id(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        write_type: GattWriteType,
+        auth_req: GattAuthReq,
+        value: &[u8],
+        bearer: Option<GattBearer>,
+    ) -> Result<GattOpId, BtStatus> {
+        let issue =
+            || self.do_write_characteristic(conn_id, handle, write_type, auth_req, value, bearer);
+        self.track_op(false, conn_id, handle, true, issue)
+    }
+
+    /// Like |write_characteristic|, but returns a |GattOp| that resolves once
+    /// the write completes. Takes |GattTimeouts::write| unless |timeout| is
+    /// given.
+    pub fn start_write_characteristic(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        write_type: GattWriteType,
+        auth_req: GattAuthReq,
+        value: &[u8],
+        bearer: Option<GattBearer>,
+        timeout: Option<Duration>,
+    ) -> Result<GattOp<()>, BtStatus> {
+        let (tx, rx) = oneshot::channel();
+        let issue =
+            || self.do_write_characteristic(conn_id, handle, write_type, auth_req, value, bearer);
+        let waiter = Some(OpWaiter::Write(tx));
+        let id = self.track_op_with_waiter(false, conn_id, handle, false, waiter, issue)?;
+        Ok(GattOp { id, rx, timeout: timeout.unwrap_or(self.timeouts.write) })
+    }
+
+    /// Writes a value that may not fit in a single write request, up to
+    /// |GATT_MAX_ATTR_LEN| bytes.
+    ///
+    /// The stack performs the long write itself: a value that doesn't fit in
+    /// the current MTU is sent as prepared writes of (MTU - 5) bytes followed
+    /// by an execute write, and the queued writes are cancelled if any of them
+    /// fails. The whole write completes with a single
+    /// |GattClientCallbacks::WriteCharacteristic|. Prepared writes can't be
+    /// issued individually since btif doesn't take a value offset.
+    pub fn write_long_characteristic(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        value: &[u8],
+        auth_req: GattAuthReq,
+    ) -> BtStatus {
+        if value.len() > GATT_MAX_ATTR_LEN {
+            return BtStatus::InvalidParam;
+        }
+
+        self.write_characteristic(conn_id, handle, GattWriteType::Default, auth_req, value, None)
+    }
+
+    /// Writes a remote characteristic without response, with flow control:
+    /// while |conn_id| is congested writes are queued rather than sent, and
+    /// go out in order once the congestion clears. Returns Success once the
+    /// write is sent or queued. Signed writes go through
+    /// |write_characteristic| instead, and |value| must fit in a single
+    /// packet at the current MTU; otherwise InvalidParam is returned.
+    pub fn write_characteristic_no_response(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        value: &[u8],
+        auth_req: GattAuthReq,
+    ) -> BtStatus {
+        // Opcode and handle.
+        let mtu = usize::try_from(self.mtu(conn_id)).unwrap_or(0);
+        if auth_req.is_signed() || value.len() + 3 > mtu {
+            return BtStatus::InvalidParam;
+        }
+
+        let write = QueuedWrite { handle, auth_req, value: value.to_vec() };
+        {
+            let mut pending = self.pending.lock().unwrap();
+            // Queue behind earlier writes still waiting, to keep them in order.
+            if pending.congested.contains(&conn_id) || pending.queued_writes.contains_key(&conn_id)
+            {
+                pending.queued_writes.entry(conn_id).or_default().push_back(write);
+                return BtStatus::Success;
+            }
+        }
+
+        GattClient::issue_no_response_write(
+            &self.pending,
+            &self.metrics,
+            &self.internal,
+            conn_id,
+            &write,
+        )
+    }
+
+    /// Number of writes issued through |write_characteristic_no_response| on
+    /// |conn_id| that are still queued or haven't completed.
+    pub fn pending_no_response_writes(&self, conn_id: i32) -> usize {
+        let pending = self.pending.lock().unwrap();
+        let queued = pending.queued_writes.get(&conn_id).map_or(0, |queue| queue.len());
+        queued + pending.no_response_writes.values().filter(|id| **id == conn_id).count()
+    }
+
+    fn do_write_characteristic(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        write_type: GattWriteType,
+        auth_req: GattAuthReq,
+        value: &[u8],
+        bearer: Option<GattBearer>,
+    ) -> BtStatus {
+        if (write_type == GattWriteType::Signed) != auth_req.is_signed() {
+            return BtStatus::InvalidParam;
+        }
+
+        if write_type == GattWriteType::Signed {
+            // Opcode, handle and signature.
+            let overhead = 3 + GATT_AUTH_SIGN_LEN;
+            let mtu = usize::try_from(self.mtu(conn_id)).unwrap_or(0);
+            if value.len() + overhead > mtu {
+                return BtStatus::InvalidParam;
+            }
+        }
+
+        let write_type = match write_type {
+            GattWriteType::Signed => GattWriteType::NoResponse,
+            other => other,
+        };
+
+        BtStatus::from(ccall!(
+            self,
+            write_characteristic,
+            conn_id,
+            handle,
+            write_type as i32,
+            auth_req as i32,
+            value.as_ptr(),
+            value.len(),
+            GattBearer::to_raw(bearer)
+        ))
+    }
+
+    pub fn read_descriptor(&self, conn_id: i32, handle: u16, auth_req: i32) -> BtStatus {
+        self.metrics.reads.issued(BtStatus::from(ccall!(
+            self,
+            read_descriptor,
+            conn_id,
+            handle,
+            auth_req
+        )))
+    }
+
+    /// Reads the descriptor |desc_uuid| of characteristic |char_uuid| in
+    /// service |service_uuid|, as found in the database last discovered on
+    /// |conn_id|. Completion is reported via
+    /// |GattClientCallbacks::ReadDescriptor|.
+    ///
+    /// Returns |BtStatus::NotReady| if services haven't been discovered yet,
+    /// and |BtStatus::InvalidParam| if the database has no such descriptor.
+    pub fn read_descriptor_by_uuid(
+        &self,
+        conn_id: i32,
+        service_uuid: &Uuid,
+        char_uuid: &Uuid,
+        desc_uuid: &Uuid,
+        auth_req: i32,
+    ) -> BtStatus {
+        let handle = match self.pending.lock().unwrap().databases.get(&conn_id) {
+            Some(db) => match db.descriptor(service_uuid, char_uuid, desc_uuid) {
+                Some(descriptor) => descriptor.handle,
+                None => return BtStatus::InvalidParam,
+            },
+            None => return BtStatus::NotReady,
+        };
+
+        self.read_descriptor(conn_id, handle, auth_req)
+    }
+
+    /// Writes a remote descriptor. Descriptors can't be written with a signed
+    /// |auth_req|.
+    pub fn write_descriptor(
+        &self,
+        conn_id: i32,
+        handle: u16,
+        auth_req: GattAuthReq,
+        value: &[u8],
+    ) -> BtStatus {
+        if auth_req.is_signed() {
+            return BtStatus::InvalidParam;
+        }
+
+        self.metrics.writes.issued(BtStatus::from(ccall!(
+            self,
+            write_descriptor,
+            conn_id,
+            handle,
+            auth_req as i32,
+            value.as_ptr(),
+            value.len()
+        )))
+    }
+
+    pub fn execute_write(&self, conn_id: i32, execute: i32) -> BtStatus {
+        BtStatus::from(ccall!(self, execute_write, conn_id, execute))
+    }
+
+    pub fn register_for_notification(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        handle: u16,
+    ) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(self, register_for_notification, client_if, ffi_addr, handle))
+    }
+
+    pub fn deregister_for_notification(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        handle: u16,
+    ) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(self, deregister_for_notification, client_if, ffi_addr, handle))
+    }
+
+    /// Subscribes to the characteristic with value |handle| on |addr|: registers
+    /// for its notifications and enables them in its CCCD. The services of
+    /// |addr| must have been discovered so that the CCCD is known.
+    ///
+    /// Several streams can subscribe to the same characteristic. The CCCD is
+    /// cleared again once the last of them is dropped.
+    pub fn subscribe(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        handle: u16,
+    ) -> Result<NotificationStream, BtStatus> {
+        let mut pending = self.pending.lock().unwrap();
+        let conn_id = pending
+            .conn_addrs
+            .iter()
+            .find(|(_, conn_addr)| *conn_addr == addr)
+            .map(|(conn_id, _)| *conn_id)
+            .ok_or(BtStatus::RemoteDeviceDown)?;
+        let cccd = pending
+            .databases
+            .get(&conn_id)
+            .and_then(|db| Cccd::find(db, handle))
+            .ok_or(BtStatus::InvalidParam)?;
+
+        let key = (*addr, handle);
+        if !pending.subscriptions.contains_key(&key) {
+            let status = self.register_for_notification(client_if, addr, handle);
+            if status != BtStatus::Success {
+                return Err(status);
+            }
+
+            let status = self.write_descriptor(
+                conn_id,
+                cccd.handle,
+                GattAuthReq::None,
+                &cccd.enable.to_le_bytes(),
+            );
+            if status != BtStatus::Success {
+                self.deregister_for_notification(client_if, addr, handle);
+                return Err(status);
+            }
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let id = pending.next_subscription_id;
+        pending.next_subscription_id += 1;
+        pending.subscriptions.entry(key).or_default().push((id, tx));
+
+        Ok(NotificationStream {
+            rx,
+            id,
+            client_if,
+            conn_id,
+            addr: *addr,
+            handle,
+            cccd: cccd.handle,
+            internal: RawGattClientWrapper { raw: self.internal.raw },
+            pending: self.pending.clone(),
+            metrics: self.metrics.clone(),
+        })
+    }
+
+    pub fn read_remote_rssi(&self, client_if: i32, addr: &RawAddress) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(self, read_remote_rssi, client_if, ffi_addr))
+    }
+
+    /// Reads the Peripheral Preferred Connection Parameters characteristic of
+    /// the GAP service, as found in the database last discovered on |conn_id|,
+    /// so that a central can request parameters the peripheral is happy with.
+    ///
+    /// Fails with |GattStatus::Error| if services haven't been discovered yet,
+    /// |GattStatus::NotFound| if the device doesn't expose the characteristic
+    /// and |GattStatus::InvalidAttrLen| if its value is too short.
+    pub async fn read_peripheral_preferred_connection_parameters(
+        &self,
+        conn_id: i32,
+        timeout: Option<Duration>,
+    ) -> Result<PreferredConnectionParameters, GattOpError> {
+        let gap_service = Uuid::from_u16(GAP_SERVICE_UUID);
+        let ppcp = Uuid::from_u16(PPCP_UUID);
+        let handle = match self.pending.lock().unwrap().databases.get(&conn_id) {
+            Some(db) => db
+                .services
+                .iter()
+                .filter(|s| s.uuid.uu == gap_service.uu)
+                .flat_map(|s| s.characteristics.iter())
+                .find(|c| c.uuid.uu == ppcp.uu)
+                .map(|c| c.value_handle),
+            None => return Err(GattStatus::Error.into()),
+        };
+        let handle = handle.ok_or(GattOpError::Status(GattStatus::NotFound))?;
+
+        let op = self
+            .start_read_characteristic(conn_id, handle, GattAuthReq::None as i32, None, timeout)
+            .map_err(|_| GattOpError::Status(GattStatus::Error))?;
+        let value = op.wait().await?;
+        PreferredConnectionParameters::parse(&value)
+            .ok_or(GattOpError::Status(GattStatus::InvalidAttrLen))
+    }
+
+    /// Reads the RSSI of |addr| and resolves once
+    /// |GattClientCallbacks::ReadRemoteRssi| arrives for (|client_if|, |addr|).
+    pub async fn read_remote_rssi_async(
+        &self,
+        client_if: i32,
+        addr: &RawAddress,
+        timeout: Option<Duration>,
+    ) -> Result<i8, GattOpError> {
+        let key = (client_if, *addr);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().rssi_reads.insert(key, tx);
+
+        if self.read_remote_rssi(client_if, addr) != BtStatus::Success {
+            self.pending.lock().unwrap().rssi_reads.remove(&key);
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, timeout.unwrap_or(self.timeouts.read)).await
+    }
+
+    /// Transports |addr| was seen on, as stored for it in the config.
+    pub fn get_device_type(&self, addr: &RawAddress) -> DeviceType {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        DeviceType::try_from(ccall!(self, get_device_type, ffi_addr)).unwrap_or(DeviceType::Unknown)
+    }
+
+    pub fn configure_mtu(&self, conn_id: i32, mtu: i32) -> BtStatus {
+        BtStatus::from(ccall!(self, configure_mtu, conn_id, mtu))
+    }
+
+    /// Exchanges the MTU on |conn_id| and resolves with the one negotiated,
+    /// which is the smaller of |mtu| and what the remote supports.
+    ///
+    /// ATT allows a single exchange per connection, so once an MTU has been
+    /// negotiated, by either side, this resolves with it straight away
+    /// without sending another request.
+    pub async fn configure_mtu_async(
+        &self,
+        conn_id: i32,
+        mtu: u16,
+        timeout: Option<Duration>,
+    ) -> Result<u16, GattOpError> {
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            if let Some(negotiated) = pending.mtus.get(&conn_id) {
+                return Ok(*negotiated as u16);
+            }
+            pending.mtu_exchanges.insert(conn_id, tx);
+        }
+
+        if self.configure_mtu(conn_id, i32::from(mtu)) != BtStatus::Success {
+            self.pending.lock().unwrap().mtu_exchanges.remove(&conn_id);
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, timeout.unwrap_or(self.timeouts.mtu)).await
+    }
+
+    /// ATT MTU currently in use on |conn_id|, as negotiated by either
+    /// |configure_mtu| or the remote device.
+    pub fn mtu(&self, conn_id: i32) -> i32 {
+        self.pending.lock().unwrap().mtus.get(&conn_id).copied().unwrap_or(ATT_DEFAULT_MTU)
+    }
+
+    /// Remote address of the open connection |conn_id|, for callbacks that
+    /// only carry the conn_id.
+    pub fn address_for_conn(&self, conn_id: i32) -> Option<RawAddress> {
+        self.pending.lock().unwrap().conn_addrs.get(&conn_id).copied()
+    }
+
+    /// State of |conn_id| as of the last connection or congestion callback.
+    pub fn connection_state(&self, conn_id: i32) -> ConnState {
+        self.pending.lock().unwrap().conn_state(conn_id)
+    }
+
+    /// Receives every connection state transition from now on, including
+    /// congestion changes. Receivers that lag behind by more than
+    /// |CONN_STATE_EVENTS_CAPACITY| transitions lose the oldest ones.
+    #[cfg(feature = "connection-state-events")]
+    pub fn subscribe_connection_state(&self) -> broadcast::Receiver<ConnStateEvent> {
+        self.pending
+            .lock()
+            .unwrap()
+            .state_events
+            .get_or_insert_with(|| broadcast::channel(CONN_STATE_EVENTS_CAPACITY).0)
+            .subscribe()
+    }
+
+    pub fn conn_parameter_update(
+        &self,
+        addr: &RawAddress,
+        min_interval: i32,
+        max_interval: i32,
+        latency: i32,
+        timeout: i32,
+        min_ce_len: u16,
+        max_ce_len: u16,
+    ) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(
+            self,
+            conn_parameter_update,
+            ffi_addr,
+            min_interval,
+            max_interval,
+            latency,
+            timeout,
+            min_ce_len,
+            max_ce_len
+        ))
+    }
+
+    /// Requests new parameters for the connection |conn_id| and resolves with
+    /// those reported by the next |GattClientCallbacks::ConnUpdated| on it.
+    ///
+    /// The controller picks values within the requested ranges, and the
+    /// remote may reject the update altogether, in which case this resolves
+    /// with the parameters that are still in use. |supervision_timeout| is in
+    /// 10ms units like the intervals are in 1.25ms units. Takes
+    /// |GattTimeouts::conn_update| unless |timeout| is given.
+    pub async fn conn_parameter_update_async(
+        &self,
+        conn_id: i32,
+        min_interval: i32,
+        max_interval: i32,
+        latency: i32,
+        supervision_timeout: i32,
+        min_ce_len: u16,
+        max_ce_len: u16,
+        timeout: Option<Duration>,
+    ) -> Result<ConnectionParameters, GattOpError> {
+        let (tx, rx) = oneshot::channel();
+        let addr = {
+            let mut pending = self.pending.lock().unwrap();
+            let addr = match pending.conn_addrs.get(&conn_id) {
+                Some(addr) => *addr,
+                None => return Err(GattStatus::Error.into()),
+            };
+            pending.conn_updates.insert(conn_id, tx);
+            addr
+        };
+
+        let status = self.conn_parameter_update(
+            &addr,
+            min_interval,
+            max_interval,
+            latency,
+            supervision_timeout,
+            min_ce_len,
+            max_ce_len,
+        );
+        if status != BtStatus::Success {
+            self.pending.lock().unwrap().conn_updates.remove(&conn_id);
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, timeout.unwrap_or(self.timeouts.conn_update)).await
+    }
+
+    /// Requests connection parameters matching |priority| instead of raw timings.
+    pub fn set_connection_priority(
+        &self,
+        addr: &RawAddress,
+        priority: ConnectionPriority,
+    ) -> BtStatus {
+        let (min_interval, max_interval, latency) = priority.params();
+        // Supervision timeout of 5 seconds, in 10ms units.
+        let timeout = 500;
+        self.conn_parameter_update(addr, min_interval, max_interval, latency, timeout, 0, 0)
+    }
+
+    pub fn subrate_request(
+        &self,
+        addr: &RawAddress,
+        subrate_min: i32,
+        subrate_max: i32,
+        max_latency: i32,
+        cont_num: i32,
+        sup_timeout: i32,
+    ) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(
+            self,
+            subrate_request,
+            ffi_addr,
+            subrate_min,
+            subrate_max,
+            max_latency,
+            cont_num,
+            sup_timeout
+        ))
+    }
+
+    pub fn set_preferred_phy(
+        &self,
+        addr: &RawAddress,
+        tx_phy: LePhy,
+        rx_phy: LePhy,
+        phy_options: PhyOptions,
+    ) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(
+            self,
+            set_preferred_phy,
+            ffi_addr,
+            tx_phy.to_mask(),
+            rx_phy.to_mask(),
+            phy_options.bits()
+        ))
+    }
+
+    /// Requests new PHYs for the connection to |addr| and resolves with the
+    /// PHYs reported by the next |GattClientCallbacks::PhyUpdated| on it.
+    ///
+    /// The remote may reject the update or the controller may pick other
+    /// PHYs, so the result is what is actually in use, not necessarily
+    /// |tx_phy| and |rx_phy|. Only an update that reports no PHYs is an error.
+    pub async fn set_preferred_phy_async(
+        &self,
+        addr: &RawAddress,
+        tx_phy: LePhy,
+        rx_phy: LePhy,
+        phy_options: PhyOptions,
+        timeout: Option<Duration>,
+    ) -> Result<(LePhy, LePhy), GattOpError> {
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            if !pending.is_connected(addr) {
+                return Err(GattStatus::Error.into());
+            }
+            pending.phy_updates.insert(*addr, tx);
+        }
+
+        if self.set_preferred_phy(addr, tx_phy, rx_phy, phy_options) != BtStatus::Success {
+            self.pending.lock().unwrap().phy_updates.remove(addr);
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, timeout.unwrap_or(self.timeouts.connect)).await
+    }
+
+    pub fn read_phy(&mut self, client_if: i32, addr: &RawAddress) -> BtStatus {
+        BtStatus::from_i32(mutcxxcall!(
+            self,
+            read_phy,
+            client_if,
+            ffi::RustRawAddress { address: addr.val }
+        ))
+        .unwrap()
+    }
+
+    pub fn test_command(&self, command: i32, params: &BtGattTestParams) -> BtStatus {
+        BtStatus::from(ccall!(self, test_command, command, params))
+    }
+
+    pub fn get_gatt_db(&self, conn_id: i32) -> BtStatus {
+        BtStatus::from(ccall!(self, get_gatt_db, conn_id))
+    }
+
+    /// Discovers all services on |conn_id| and resolves with the remote
+    /// database once |GattClientCallbacks::GetGattDb| arrives. A failed search
+    /// resolves with the status of |GattClientCallbacks::SearchComplete|. The
+    /// timeout covers both the search and reading back the database.
+    pub async fn discover_all_services(
+        &self,
+        conn_id: i32,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<BtGattDbElement>, GattOpError> {
+        let deadline = Instant::now() + timeout.unwrap_or(self.timeouts.discovery);
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().searches.insert(conn_id, tx);
+
+        if self.search_service(conn_id, None) != BtStatus::Success {
+            self.pending.lock().unwrap().searches.remove(&conn_id);
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, deadline.saturating_duration_since(Instant::now())).await?;
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().gatt_dbs.insert(conn_id, tx);
+
+        if self.get_gatt_db(conn_id) != BtStatus::Success {
+            self.pending.lock().unwrap().gatt_dbs.remove(&conn_id);
+            return Err(GattStatus::Error.into());
+        }
+
+        match tokio::time::timeout(deadline.saturating_duration_since(Instant::now()), rx).await {
+            Ok(elements) => Ok(elements.map_err(|_| GattStatus::Error)?),
+            Err(_) => Err(GattOpError::Timeout),
+        }
+    }
+
+    /// Refreshes the remote device on |conn_id| and resolves once its services
+    /// have been rediscovered. Fails with |GattStatus::Error| if |conn_id| is
+    /// not connected.
+    ///
+    /// A service search is issued right after the refresh and resolves this.
+    /// BTA queues a search that arrives while a discovery is running, so it
+    /// completes only after the rediscovery started by the refresh.
+    pub async fn refresh_async(
+        &self,
+        client_if: i32,
+        conn_id: i32,
+        timeout: Option<Duration>,
+    ) -> Result<(), GattOpError> {
+        let addr = match self.address_for_conn(conn_id) {
+            Some(addr) => addr,
+            None => return Err(GattStatus::Error.into()),
+        };
+
+        if self.refresh(client_if, &addr) != BtStatus::Success {
+            return Err(GattStatus::Error.into());
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().searches.insert(conn_id, tx);
+
+        if self.search_service(conn_id, None) != BtStatus::Success {
+            self.pending.lock().unwrap().searches.remove(&conn_id);
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, timeout.unwrap_or(self.timeouts.discovery)).await
+    }
+}
+
+/// Maximum length of an attribute value. Matches GATT_MAX_ATTR_LEN in
+/// stack/include/gatt_api.h.
+pub const GATT_MAX_ATTR_LEN: usize = 512;
+
+/// ATT MTU used until an MTU exchange succeeds.
+pub const ATT_DEFAULT_MTU: i32 = 23;
+
+// Length of the signature appended to a Signed Write Command. Matches
+// GATT_AUTH_SIGN_LEN in stack/include/gatt_api.h.
+const GATT_AUTH_SIGN_LEN: usize = 12;
+
+/// Maximum number of handles in a single read multiple request. Matches
+/// GATT_MAX_READ_MULTI_HANDLES in stack/include/gatt_api.h.
+pub const GATT_MAX_READ_MULTI_HANDLES: usize = 10;
+
+/// Flag of an Execute Write Request. Matches GATT_PREP_WRITE_* in
+/// stack/include/gatt_api.h.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExecuteWriteMode {
+    /// Write all the prepared values.
+    Execute,
+    /// Discard all the prepared values.
+    Cancel,
+}
+
+impl From<i32> for ExecuteWriteMode {
+    fn from(exec_write: i32) -> Self {
+        // btif passes the flag through as is, and only 0 cancels.
+        match exec_write {
+            0 => ExecuteWriteMode::Cancel,
+            _ => ExecuteWriteMode::Execute,
+        }
+    }
+}
+
+/// A long write reassembled by |PreparedWriteBuffer|.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompletedWrite {
+    pub handle: i32,
+    pub value: Vec<u8>,
+}
+
+/// Reassembles the prepared writes of each connection into the values to
+/// write once they are executed.
+///
+/// Values are assembled starting at offset 0, so each prepared write must
+/// start at or before the end of the data prepared so far for its handle.
+#[derive(Debug, Default)]
+pub struct PreparedWriteBuffer {
+    // (handle, offset, value) of each prepared write in the order received,
+    // keyed by conn_id.
+    writes: HashMap<i32, Vec<(i32, usize, Vec<u8>)>>,
+}
+
+impl PreparedWriteBuffer {
+    pub fn new() -> Self {
+        PreparedWriteBuffer::default()
+    }
+
+    /// Buffers a write received with |is_prep| set.
+    pub fn prepare(&mut self, conn_id: i32, handle: i32, offset: i32, value: &[u8]) {
+        let offset = offset.max(0) as usize;
+        self.writes.entry(conn_id).or_default().push((handle, offset, value.to_vec()));
+    }
+
+    /// Handles |GattServerCallbacks::RequestExecWrite|. On
+    /// |ExecuteWriteMode::Execute|, returns one write per handle with the
+    /// prepared data put together, in the order the handles were first
+    /// written. On |ExecuteWriteMode::Cancel| the prepared writes are
+    /// discarded and nothing is returned.
+    ///
+    /// The prepared writes of |conn_id| are gone afterwards either way. Fails
+    /// with the status to respond with if the writes don't form a valid value.
+    pub fn execute(
+        &mut self,
+        conn_id: i32,
+        mode: ExecuteWriteMode,
+    ) -> Result<Vec<CompletedWrite>, GattStatus> {
+        let writes = self.writes.remove(&conn_id).unwrap_or_default();
+        if mode == ExecuteWriteMode::Cancel {
+            return Ok(vec![]);
+        }
+
+        let mut completed: Vec<CompletedWrite> = vec![];
+        for (handle, offset, value) in writes {
+            let index = match completed.iter().position(|write| write.handle == handle) {
+                Some(index) => index,
+                None => {
+                    completed.push(CompletedWrite { handle, value: vec![] });
+                    completed.len() - 1
+                }
+            };
+
+            let assembled = &mut completed[index].value;
+            if offset > assembled.len() {
+                return Err(GattStatus::InvalidOffset);
+            }
+            if offset + value.len() > GATT_MAX_ATTR_LEN {
+                return Err(GattStatus::InvalidAttrLen);
+            }
+            assembled.truncate(offset);
+            assembled.extend_from_slice(&value);
+        }
+
+        Ok(completed)
+    }
+
+    /// Feeds a server callback to the buffer. Returns the result of
+    /// |execute| for |GattServerCallbacks::RequestExecWrite|.
+    pub fn on_callback(
+        &mut self,
+        cb: &GattServerCallbacks,
+    ) -> Option<Result<Vec<CompletedWrite>, GattStatus>> {
+        match cb {
+            GattServerCallbacks::RequestWriteCharacteristic(
+                conn_id,
+                _,
+                _,
+                handle,
+                offset,
+                _,
+                true,
+                value,
+                _,
+            )
+            | GattServerCallbacks::RequestWriteDescriptor(
+                conn_id,
+                _,
+                _,
+                handle,
+                offset,
+                _,
+                true,
+                value,
+                _,
+            ) => {
+                self.prepare(*conn_id, *handle, *offset, value);
+                None
+            }
+            GattServerCallbacks::RequestExecWrite(conn_id, _, _, mode) => {
+                Some(self.execute(*conn_id, *mode))
+            }
+            GattServerCallbacks::Connection(conn_id, _, 0, _) => {
+                self.writes.remove(conn_id);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Which remote devices may connect to a server.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConnectionPolicy {
+    AcceptAll,
+    Allowlist(Vec<RawAddress>),
+    Denylist(Vec<RawAddress>),
+}
+
+impl Default for ConnectionPolicy {
+    fn default() -> Self {
+        ConnectionPolicy::AcceptAll
+    }
+}
+
+impl ConnectionPolicy {
+    pub fn allows(&self, addr: &RawAddress) -> bool {
+        match self {
+            ConnectionPolicy::AcceptAll => true,
+            ConnectionPolicy::Allowlist(addrs) => addrs.contains(addr),
+            ConnectionPolicy::Denylist(addrs) => !addrs.contains(addr),
+        }
+    }
+}
+
+/// Indications and notifications waiting for |GattServerCallbacks::IndicationSent|,
+/// along with the per-connection state they depend on.
+#[derive(Default)]
+struct PendingServerRequests {
+    next_id: u64,
+    // Outstanding indications in the order they were sent, keyed by conn_id.
+    // Only indications sent via |send_indication_confirmed| carry a sender.
+    indications: HashMap<i32, VecDeque<(u64, Option<oneshot::Sender<Result<(), GattStatus>>>)>>,
+    // Notifications whose completion has not been reported yet, keyed by
+    // conn_id.
+    notifications: HashMap<i32, usize>,
+    // Congestion reported by |GattServerCallbacks::Congestion|, keyed by
+    // conn_id.
+    congested: HashMap<i32, bool>,
+    // Set via |set_connection_policy|, keyed by server_if. Servers without an
+    // entry accept all connections.
+    connection_policies: HashMap<i32, ConnectionPolicy>,
+    // Connections disconnected for violating a policy. Their callbacks are
+    // not dispatched.
+    rejected: HashSet<i32>,
+    // Keyed by (server_if, uuid of the service).
+    services: HashMap<(i32, [u8; 16]), oneshot::Sender<Result<Vec<BtGattDbElement>, GattStatus>>>,
+    // Handles of the services each server_if added and hasn't deleted yet.
+    service_handles: HashMap<i32, HashSet<i32>>,
+    // Uuid of each of those services, keyed by handle, for the services that
+    // include them.
+    service_uuids: HashMap<i32, Uuid>,
+    // (server_if, remote address) of each open connection, keyed by conn_id.
+    // Rejected connections aren't tracked.
+    connections: HashMap<i32, (i32, RawAddress)>,
+    // ATT MTU negotiated on each open connection. Connections without an entry
+    // use |ATT_DEFAULT_MTU|.
+    mtus: HashMap<i32, i32>,
+    // Offsets of the read requests that haven't been responded to, keyed by
+    // (conn_id, trans_id).
+    read_requests: HashMap<(i32, i32), u16>,
+}
+
+impl PendingServerRequests {
+    // Fills in the uuid of included services, which the stack leaves unset,
+    // from the services they include.
+    fn resolve_includes(&self, elements: &[BtGattDbElement]) -> Vec<BtGattDbElement> {
+        elements
+            .iter()
+            .map(|element| {
+                let included = element.type_ == GattDbElementType::IncludedService as u32;
+                match self.service_uuids.get(&i32::from(element.start_handle)) {
+                    Some(uuid) if included => BtGattDbElement { uuid: *uuid, ..*element },
+                    _ => *element,
+                }
+            })
+            .collect()
+    }
+
+    fn pop_indication(
+        &mut self,
+        conn_id: i32,
+    ) -> Option<(u64, Option<oneshot::Sender<Result<(), GattStatus>>>)> {
+        let queue = self.indications.get_mut(&conn_id)?;
+        let entry = queue.pop_front();
+        if queue.is_empty() {
+            self.indications.remove(&conn_id);
+        }
+        entry
+    }
+
+    fn remove_indication(&mut self, conn_id: i32, id: u64) {
+        if let Some(queue) = self.indications.get_mut(&conn_id) {
+            queue.retain(|(entry_id, _)| *entry_id != id);
+            if queue.is_empty() {
+                self.indications.remove(&conn_id);
+            }
+        }
+    }
+
+    fn remove_notification(&mut self, conn_id: i32) -> bool {
+        match self.notifications.get_mut(&conn_id) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.notifications.remove(&conn_id);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub struct GattServer {
+    internal: RawGattServerWrapper,
+    internal_cxx: cxx::UniquePtr<ffi::GattServerIntf>,
+    pending: Arc<Mutex<PendingServerRequests>>,
+    metrics: Arc<GattMetrics>,
+    timeouts: GattServerTimeouts,
+}
+
+impl GattServer {
+    // Resolves pending indications. |IndicationSent| only carries the conn_id,
+    // so completions are matched per connection: the stack reports a
+    // notification as soon as it is sent, while ATT allows a single
+    // outstanding indication per bearer, so indications complete in order.
+    //
+    // Connections from devices the server's |ConnectionPolicy| doesn't allow
+    // are disconnected through |raw|. Returns false for callbacks of such
+    // connections, which must not be dispatched.
+    fn on_callback(
+        pending: &Mutex<PendingServerRequests>,
+        metrics: &GattMetrics,
+        raw: &RawGattServerWrapper,
+        cb: &GattServerCallbacks,
+    ) -> bool {
+        match cb {
+            GattServerCallbacks::Connection(conn_id, server_if, connected, addr)
+                if *connected != 0 =>
+            {
+                let mut pending = pending.lock().unwrap();
+                let allowed = pending
+                    .connection_policies
+                    .get(server_if)
+                    .map_or(true, |policy| policy.allows(addr));
+                if !allowed {
+                    pending.rejected.insert(*conn_id);
+                    let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+                    unsafe { ((*raw.raw).disconnect.unwrap())(*server_if, ffi_addr, *conn_id) };
+                    return false;
+                }
+                pending.connections.insert(*conn_id, (*server_if, *addr));
+            }
+            GattServerCallbacks::IndicationSent(conn_id, status) => {
+                let mut pending = pending.lock().unwrap();
+                if pending.remove_notification(*conn_id) {
+                    metrics.notifications.completed(*status);
+                    return true;
+                }
+
+                if let Some((_, Some(tx))) = pending.pop_indication(*conn_id) {
+                    let _ = tx.send(match GattStatus::from(*status as u8) {
+                        GattStatus::Success => Ok(()),
+                        status => Err(status),
+                    });
+                }
+            }
+            GattServerCallbacks::Congestion(conn_id, congested) => {
+                pending.lock().unwrap().congested.insert(*conn_id, *congested);
+            }
+            GattServerCallbacks::MtuChanged(conn_id, mtu) => {
+                pending.lock().unwrap().mtus.insert(*conn_id, *mtu);
+            }
+            GattServerCallbacks::RequestReadCharacteristic(conn_id, trans_id, _, _, offset, _)
+            | GattServerCallbacks::RequestReadDescriptor(conn_id, trans_id, _, _, offset, _) => {
+                pending.lock().unwrap().read_requests.insert((*conn_id, *trans_id), *offset as u16);
+            }
+            GattServerCallbacks::ServiceAdded(status, server_if, elements, _) => {
+                if let Some(service) = elements.first() {
+                    let mut pending = pending.lock().unwrap();
+                    if GattStatus::from(*status as u8) == GattStatus::Success {
+                        let handle = i32::from(service.attribute_handle);
+                        pending.service_handles.entry(*server_if).or_default().insert(handle);
+                        pending.service_uuids.insert(handle, service.uuid);
+                    }
+
+                    let key = (*server_if, service.uuid.uu);
+                    if let Some(tx) = pending.services.remove(&key) {
+                        let _ = tx.send(match GattStatus::from(*status as u8) {
+                            GattStatus::Success => Ok(pending.resolve_includes(elements)),
+                            status => Err(status),
+                        });
+                    }
+                }
+            }
+            GattServerCallbacks::ServiceDeleted(status, server_if, handle) => {
+                // A service that failed to be deleted is still there.
+                if GattStatus::from(*status as u8) == GattStatus::Success {
+                    let mut pending = pending.lock().unwrap();
+                    pending.service_uuids.remove(handle);
+                    if let Some(handles) = pending.service_handles.get_mut(server_if) {
+                        handles.remove(handle);
+                        if handles.is_empty() {
+                            pending.service_handles.remove(server_if);
+                        }
+                    }
+                }
+            }
+            GattServerCallbacks::Connection(conn_id, _, 0, _) => {
+                let mut pending = pending.lock().unwrap();
+                pending.connections.remove(conn_id);
+                pending.mtus.remove(conn_id);
+                pending.congested.remove(conn_id);
+                pending.notifications.remove(conn_id);
+                pending.read_requests.retain(|(id, _), _| id != conn_id);
+                // Dropping the senders fails any confirmation still waiting.
+                pending.indications.remove(conn_id);
+                if pending.rejected.remove(conn_id) {
+                    return false;
+                }
+            }
+            _ => (),
+        }
+
+        true
+    }
+
+    pub fn register_server(&self, uuid: &Uuid, eatt_support: bool) -> BtStatus {
+        BtStatus::from(ccall!(self, register_server, uuid, eatt_support))
+    }
+
+    pub fn unregister_server(&self, server_if: i32) -> BtStatus {
+        let mut pending = self.pending.lock().unwrap();
+        pending.connection_policies.remove(&server_if);
+        // The stack deletes the services of the server along with it, and
+        // closes its connections without reporting them.
+        if let Some(handles) = pending.service_handles.remove(&server_if) {
+            pending.service_uuids.retain(|handle, _| !handles.contains(handle));
+        }
+        pending.connections.retain(|_, (id, _)| *id != server_if);
+        let PendingServerRequests { connections, mtus, .. } = &mut *pending;
+        mtus.retain(|conn_id, _| connections.contains_key(conn_id));
+        drop(pending);
+
+        BtStatus::from(ccall!(self, unregister_server, server_if))
+    }
+
+    /// Restricts which devices may connect to |server_if|. Connections from
+    /// other devices are disconnected as soon as they are established, and
+    /// none of their callbacks are dispatched.
+    pub fn set_connection_policy(&self, server_if: i32, policy: ConnectionPolicy) {
+        let mut pending = self.pending.lock().unwrap();
+        match policy {
+            ConnectionPolicy::AcceptAll => pending.connection_policies.remove(&server_if),
+            policy => pending.connection_policies.insert(server_if, policy),
+        };
+    }
+
+    /// Timeouts used by the async operations that aren't given one.
+    pub fn timeouts(&self) -> GattServerTimeouts {
+        self.timeouts
+    }
+
+    pub fn set_timeouts(&mut self, timeouts: GattServerTimeouts) {
+        self.timeouts = timeouts;
+    }
+
+    pub fn connect(
+        &self,
+        server_if: i32,
+        addr: &RawAddress,
+        is_direct: bool,
+        transport: BtTransport,
+    ) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(self, connect, server_if, ffi_addr, is_direct, i32::from(transport)))
+    }
+
+    pub fn disconnect(&self, server_if: i32, addr: &RawAddress, conn_id: i32) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(self, disconnect, server_if, ffi_addr, conn_id))
+    }
+
+    /// (conn_id, remote address) of the open connections of |server_if|, in
+    /// conn_id order.
+    pub fn connected_devices(&self, server_if: i32) -> Vec<(i32, RawAddress)> {
+        let pending = self.pending.lock().unwrap();
+        let mut devices: Vec<(i32, RawAddress)> = pending
+            .connections
+            .iter()
+            .filter(|(_, (id, _))| *id == server_if)
+            .map(|(conn_id, (_, addr))| (*conn_id, *addr))
+            .collect();
+        devices.sort_unstable_by_key(|(conn_id, _)| *conn_id);
+        devices
+    }
+
+    /// ATT MTU in use on the open connection |conn_id|, as last reported by
+    /// |GattServerCallbacks::MtuChanged|, or |ATT_DEFAULT_MTU| if none was
+    /// negotiated. Returns None if |conn_id| isn't connected.
+    pub fn mtu_for_conn(&self, conn_id: i32) -> Option<u16> {
+        let pending = self.pending.lock().unwrap();
+        if !pending.connections.contains_key(&conn_id) {
+            return None;
+        }
+        let mtu = pending.mtus.get(&conn_id).copied().unwrap_or(ATT_DEFAULT_MTU);
+        u16::try_from(mtu).ok()
+    }
+
+    /// Disconnects every device connected to |server_if|. Connections stay
+    /// listed by |connected_devices| until |GattServerCallbacks::Connection|
+    /// reports them closed.
+    ///
+    /// All disconnections are issued even if some fail. Returns the first
+    /// failure, if any.
+    pub fn disconnect_all(&self, server_if: i32) -> BtStatus {
+        self.connected_devices(server_if).into_iter().fold(
+            BtStatus::Success,
+            |result, (conn_id, addr)| match self.disconnect(server_if, &addr, conn_id) {
+                BtStatus::Success => result,
+                status if result == BtStatus::Success => status,
+                _ => result,
+            },
+        )
+    }
+
+    /// Adds a service. |service| can be built with |GattDbBuilder|.
+    pub fn add_service(&self, server_if: i32, service: &[BtGattDbElement]) -> BtStatus {
+        BtStatus::from(ccall!(self, add_service, server_if, service.as_ptr(), service.len()))
+    }
+
+    /// Adds a service and resolves with its elements once
+    /// |GattServerCallbacks::ServiceAdded| reports the handles assigned to them.
+    /// Included services also get the uuid of the service they include, if it
+    /// was added by this server.
+    pub async fn add_service_async(
+        &self,
+        server_if: i32,
+        service: &[BtGattDbElement],
+        timeout: Option<Duration>,
+    ) -> Result<Vec<BtGattDbElement>, GattOpError> {
+        let key = match service.first() {
+            Some(element) => (server_if, element.uuid.uu),
+            None => return Err(GattStatus::IllegalParameter.into()),
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().services.insert(key, tx);
+
+        if self.add_service(server_if, service) != BtStatus::Success {
+            self.pending.lock().unwrap().services.remove(&key);
+            return Err(GattStatus::Error.into());
+        }
+
+        wait_for(rx, timeout.unwrap_or(self.timeouts.service)).await
+    }
+
+    pub fn stop_service(&self, server_if: i32, service_handle: i32) -> BtStatus {
+        BtStatus::from(ccall!(self, stop_service, server_if, service_handle))
+    }
+
+    pub fn delete_service(&self, server_if: i32, service_handle: i32) -> BtStatus {
+        BtStatus::from(ccall!(self, delete_service, server_if, service_handle))
+    }
+
+    /// Handles of the services |server_if| has added and not deleted.
+    pub fn service_handles(&self, server_if: i32) -> Vec<i32> {
+        let pending = self.pending.lock().unwrap();
+        let mut handles: Vec<i32> =
+            pending.service_handles.get(&server_if).into_iter().flatten().copied().collect();
+        handles.sort_unstable();
+        handles
+    }
+
+    /// Deletes every service |server_if| has added. Each deletion is reported
+    /// via its own |GattServerCallbacks::ServiceDeleted|; services that fail
+    /// to be deleted stay tracked so that this can be called again.
+    ///
+    /// All deletions are issued even if some fail. Returns the first failure,
+    /// if any.
+    pub fn clear_services(&self, server_if: i32) -> BtStatus {
+        self.service_handles(server_if).into_iter().fold(BtStatus::Success, |result, handle| {
+            match self.delete_service(server_if, handle) {
+                BtStatus::Success => result,
+                status if result == BtStatus::Success => status,
+                _ => result,
+            }
+        })
+    }
+
+    pub fn send_indication(
+        &self,
+        server_if: i32,
+        attribute_handle: i32,
+        conn_id: i32,
+        confirm: i32,
+        value: &[u8],
+    ) -> BtStatus {
+        self.track_indication(server_if, attribute_handle, conn_id, confirm, value, None)
+    }
+
+    /// Like |send_indication|, but returns |BtStatus::Busy| without sending
+    /// while |conn_id| is congested, rather than piling more data into the
+    /// lower layer queues.
+    pub fn send_indication_if_not_congested(
+        &self,
+        server_if: i32,
+        attribute_handle: i32,
+        conn_id: i32,
+        confirm: i32,
+        value: &[u8],
+    ) -> BtStatus {
+        if self.is_congested(conn_id) {
+            return BtStatus::Busy;
+        }
+
+        self.send_indication(server_if, attribute_handle, conn_id, confirm, value)
+    }
+
+    /// Whether the stack last reported |conn_id| as congested.
+    pub fn is_congested(&self, conn_id: i32) -> bool {
+        self.pending.lock().unwrap().congested.get(&conn_id).copied().unwrap_or(false)
+    }
+
+    /// Sends an indication and resolves once the peer confirms it, or with
+    /// |GattOpError::Timeout| if it is not confirmed within |timeout|.
+    ///
+    /// The confirmation is reported by |GattServerCallbacks::IndicationSent|,
+    /// which identifies the connection but not the attribute. It is matched to
+    /// the oldest outstanding indication on |conn_id|, since the stack
+    /// delivers indications on a connection one at a time. Like
+    /// |send_indication|, it sends regardless of congestion; callers that
+    /// should back off check |is_congested| first.
+    pub async fn send_indication_confirmed(
+        &self,
+        server_if: i32,
+        attribute_handle: i32,
+        conn_id: i32,
+        value: &[u8],
+        timeout: Option<Duration>,
+    ) -> Result<(), GattOpError> {
+        let (tx, rx) = oneshot::channel();
+        let status =
+            self.track_indication(server_if, attribute_handle, conn_id, 1, value, Some(tx));
+        if status != BtStatus::Success {
+            return Err(GattStatus::Error.into());
+        }
+
+        // On timeout the entry stays queued so that a late confirmation is not
+        // matched to a newer indication.
+        wait_for(rx, timeout.unwrap_or(self.timeouts.indication)).await
+    }
+
+    /// Sends a notification of |value| on each connection in |conns|, e.g.
+    /// all connections subscribed to |attribute_handle|.
+    ///
+    /// Returns the status for every connection in |conns|. Connections that
+    /// are currently congested are skipped and reported as |BtStatus::Busy|.
+    pub fn send_notification_to_all(
+        &self,
+        server_if: i32,
+        attribute_handle: i32,
+        value: &[u8],
+        conns: &[i32],
+    ) -> Vec<(i32, BtStatus)> {
+        conns
+            .iter()
+            .map(|&conn_id| {
+                if self.is_congested(conn_id) {
+                    return (conn_id, BtStatus::Busy);
+                }
+
+                (conn_id, self.send_indication(server_if, attribute_handle, conn_id, 0, value))
+            })
+            .collect()
+    }
+
+    fn track_indication(
+        &self,
+        server_if: i32,
+        attribute_handle: i32,
+        conn_id: i32,
+        confirm: i32,
+        value: &[u8],
+        tx: Option<oneshot::Sender<Result<(), GattStatus>>>,
+    ) -> BtStatus {
+        let id = {
+            let mut pending = self.pending.lock().unwrap();
+            let id = pending.next_id;
+            pending.next_id += 1;
+            if confirm != 0 {
+                pending.indications.entry(conn_id).or_default().push_back((id, tx));
+            } else {
+                *pending.notifications.entry(conn_id).or_default() += 1;
+            }
+            id
+        };
+
+        let status = BtStatus::from(ccall!(
+            self,
+            send_indication,
+            server_if,
+            attribute_handle,
+            conn_id,
+            confirm,
+            value.as_ptr(),
+            value.len()
+        ));
+        if confirm == 0 {
+            self.metrics.notifications.issued(status);
+        }
+        if status != BtStatus::Success {
+            let mut pending = self.pending.lock().unwrap();
+            if confirm != 0 {
+                pending.remove_indication(conn_id, id);
+            } else {
+                pending.remove_notification(conn_id);
+            }
+        }
+
+        status
+    }
+
+    /// Responds to a read or write request. A successful response to a read
+    /// must carry the offset the remote read at, and a value no longer than
+    /// |GATT_MAX_ATTR_LEN|; otherwise InvalidParam is returned without sending
+    /// anything. The value is what the remote gets back for that offset, so
+    /// it may be shorter than the offset.
+    pub fn send_response(
+        &self,
+        conn_id: i32,
+        trans_id: i32,
+        status: i32,
+        response: &BtGattResponse,
+    ) -> BtStatus {
+        let mut pending = self.pending.lock().unwrap();
+        let key = (conn_id, trans_id);
+        if let Some(offset) = pending.read_requests.get(&key).copied() {
+            // Responses to reads always hold a value.
+            let value = unsafe { &response.attr_value };
+            let valid = value.offset == offset && usize::from(value.len) <= GATT_MAX_ATTR_LEN;
+            if status == GattStatus::Success as i32 && !valid {
+                return BtStatus::InvalidParam;
+            }
+            pending.read_requests.remove(&key);
+        }
+        drop(pending);
+
+        BtStatus::from(ccall!(self, send_response, conn_id, trans_id, status, response))
+    }
+
+    /// Builds the response to a read request for |handle|. Returns None if
+    /// |value| is longer than |GATT_MAX_ATTR_LEN|.
+    pub fn make_attr_response(handle: u16, offset: u16, value: &[u8]) -> Option<BtGattResponse> {
+        if value.len() > GATT_MAX_ATTR_LEN {
+            return None;
+        }
+
+        let mut attr_value = bindings::btgatt_value_t::default();
+        attr_value.value[..value.len()].copy_from_slice(value);
+        attr_value.handle = handle;
+        attr_value.offset = offset;
+        attr_value.len = value.len() as u16;
+
+        Some(BtGattResponse { attr_value })
+    }
+
+    /// Responds to a request with |value| and |status|. Returns InvalidParam
+    /// without sending anything if |value| is too long for an attribute.
+    pub fn send_attr_response(
+        &self,
+        conn_id: i32,
+        trans_id: i32,
+        status: GattStatus,
+        handle: u16,
+        offset: u16,
+        value: &[u8],
+    ) -> BtStatus {
+        match GattServer::make_attr_response(handle, offset, value) {
+            Some(response) => self.send_response(conn_id, trans_id, status as i32, &response),
+            None => BtStatus::InvalidParam,
+        }
+    }
+
+    pub fn set_preferred_phy(
+        &self,
+        addr: &RawAddress,
+        tx_phy: LePhy,
+        rx_phy: LePhy,
+        phy_options: PhyOptions,
+    ) -> BtStatus {
+        let ffi_addr = cast_to_ffi_address!(addr as *const RawAddress);
+        BtStatus::from(ccall!(
+            self,
+            set_preferred_phy,
+            ffi_addr,
+            tx_phy.to_mask(),
+            rx_phy.to_mask(),
+            phy_options.bits()
+        ))
+    }
+
+    pub fn read_phy(&mut self, server_if: i32, addr: &RawAddress) -> BtStatus {
+        BtStatus::from_i32(mutcxxcall!(
+            self,
+            read_phy,
+            server_if,
+            ffi::RustRawAddress { address: addr.val }
+        ))
+        .unwrap()
+    }
+}
+
+/// A field of advertising or scan response data. 128-bit UUIDs are in the
+/// same big-endian order as |Uuid::uu|.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdvDataField {
+    Flags(u8),
+    ServiceUuids16 {
+        complete: bool,
+        uuids: Vec<u16>,
+    },
+    ServiceUuids128 {
+        complete: bool,
+        uuids: Vec<[u8; 16]>,
+    },
+    LocalName {
+        complete: bool,
+        name: String,
+    },
+    TxPower(i8),
+    ManufacturerData {
+        company_id: u16,
+        data: Vec<u8>,
+    },
+    ServiceData16 {
+        uuid: u16,
+        data: Vec<u8>,
+    },
+    ServiceData128 {
+        uuid: [u8; 16],
+        data: Vec<u8>,
+    },
+    /// Any other AD type, or a known one whose data is too short.
+    Other {
+        ad_type: u8,
+        data: Vec<u8>,
+    },
+}
+
+fn le_uuid128(bytes: &[u8]) -> [u8; 16] {
+    let mut uuid = [0u8; 16];
+    uuid.copy_from_slice(&bytes[..16]);
+    Uuid::from_le_bytes(uuid).to_be_bytes()
+}
+
+impl AdvDataField {
+    fn parse(ad_type: u8, data: &[u8]) -> AdvDataField {
+        let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+        match ad_type {
+            0x01 if data.len() == 1 => AdvDataField::Flags(data[0]),
+            0x02 | 0x03 => AdvDataField::ServiceUuids16 {
+                complete: ad_type == 0x03,
+                uuids: data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect(),
+            },
+            0x06 | 0x07 => AdvDataField::ServiceUuids128 {
+                complete: ad_type == 0x07,
+                uuids: data.chunks_exact(16).map(le_uuid128).collect(),
+            },
+            0x08 | 0x09 => AdvDataField::LocalName {
+                complete: ad_type == 0x09,
+                name: String::from_utf8_lossy(data).into_owned(),
+            },
+            0x0a if data.len() == 1 => AdvDataField::TxPower(data[0] as i8),
+            0x16 if data.len() >= 2 => {
+                AdvDataField::ServiceData16 { uuid: u16_at(0), data: data[2..].to_vec() }
+            }
+            0x21 if data.len() >= 16 => AdvDataField::ServiceData128 {
+                uuid: le_uuid128(&data[..16]),
+                data: data[16..].to_vec(),
+            },
+            0xff if data.len() >= 2 => {
+                AdvDataField::ManufacturerData { company_id: u16_at(0), data: data[2..].to_vec() }
+            }
+            _ => AdvDataField::Other { ad_type, data: data.to_vec() },
+        }
+    }
+}
+
+/// Parses advertising or scan response data, e.g. from
+/// |GattScannerCallbacks::OnScanResult|, into its fields.
+///
+/// Parsing stops at the first zero length field, which marks the end of the
+/// significant data, or at a field running past the end of |data|.
+pub fn parse_adv_data(data: &[u8]) -> Vec<AdvDataField> {
+    let mut fields = vec![];
+    let mut rest = data;
+    while let Some((&len, after_len)) = rest.split_first() {
+        let len = usize::from(len);
+        if len == 0 || len > after_len.len() {
+            break;
+        }
+
+        fields.push(AdvDataField::parse(after_len[0], &after_len[1..len]));
+        rest = &after_len[len..];
+    }
+    fields
+}
+
+// Report formats of |GattScannerCallbacks::OnBatchScanReports|.
+const BATCH_SCAN_REPORT_TRUNCATED: i32 = 1;
+const BATCH_SCAN_REPORT_FULL: i32 = 2;
+
+// Size of a record in a truncated batch scan report, and of the fixed part of
+// a record in a full one.
+const TRUNCATED_RECORD_LEN: usize = 11;
+
+/// A device found during batch scanning. Records of truncated reports carry
+/// no advertising data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchScanRecord {
+    pub addr: RawAddress,
+    pub addr_type: u8,
+    pub tx_power: i8,
+    pub rssi: i8,
+    /// In units of 50ms.
+    pub timestamp: u16,
+    pub adv_data: Vec<u8>,
+    pub scan_response: Vec<u8>,
+}
+
+/// Parses the data of |GattScannerCallbacks::OnBatchScanReports|.
+///
+/// The data comes from the controller, so it isn't trusted: parsing stops at
+/// the first record that runs past the end of |data|, and at most
+/// |num_records| records are returned.
+pub fn parse_batch_scan_reports(
+    report_format: i32,
+    num_records: i32,
+    data: &[u8],
+) -> Vec<BatchScanRecord> {
+    let full = match report_format {
+        BATCH_SCAN_REPORT_TRUNCATED => false,
+        BATCH_SCAN_REPORT_FULL => true,
+        _ => return vec![],
+    };
+
+    let mut records = vec![];
+    let mut rest = data;
+    while records.len() < num_records.max(0) as usize {
+        match parse_batch_scan_record(rest, full) {
+            Some((record, remaining)) => {
+                records.push(record);
+                rest = remaining;
+            }
+            None => break,
+        }
+    }
+    records
+}
+
+// Splits a value prefixed with its one byte length off the start of |data|.
+fn split_length_prefixed(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&len, rest) = data.split_first()?;
+    if usize::from(len) > rest.len() {
+        return None;
+    }
+    Some(rest.split_at(usize::from(len)))
+}
+
+// Parses a single record from the start of |data|. Returns the record along
+// with the data following it.
+fn parse_batch_scan_record(data: &[u8], full: bool) -> Option<(BatchScanRecord, &[u8])> {
+    if data.len() < TRUNCATED_RECORD_LEN {
+        return None;
+    }
+    let (fixed, rest) = data.split_at(TRUNCATED_RECORD_LEN);
+
+    // The address is in little endian order.
+    let mut addr = RawAddress { val: [0; 6] };
+    for (dst, src) in addr.val.iter_mut().zip(fixed[..6].iter().rev()) {
+        *dst = *src;
+    }
+
+    let (adv_data, scan_response, rest) = if full {
+        let (adv_data, rest) = split_length_prefixed(rest)?;
+        let (scan_response, rest) = split_length_prefixed(rest)?;
+        (adv_data.to_vec(), scan_response.to_vec(), rest)
+    } else {
+        (vec![], vec![], rest)
+    };
+
+    let record = BatchScanRecord {
+        addr,
+        addr_type: fixed[6],
+        tx_power: fixed[7] as i8,
+        rssi: fixed[8] as i8,
+        timestamp: u16::from_le_bytes([fixed[9], fixed[10]]),
+        adv_data,
+        scan_response,
+    };
+    Some((record, rest))
+}
+
+/// Drops scan results before |GattScannerCallbacks::OnScanResult| is
+/// dispatched. Criteria left unset don't filter anything.
+#[derive(Clone, Debug, Default)]
+pub struct ScanResultFilter {
+    /// Drop results received with a lower RSSI.
+    pub min_rssi: Option<i8>,
+    /// Drop results from an address already reported within this window.
+    pub dedup_window: Option<Duration>,
+    /// Only report results from these addresses.
+    pub address_allowlist: Option<Vec<RawAddress>>,
+}
+
+// Actions of |BleScanner::scan_filter_param_setup|. Match the
+// BTM_BLE_SCAN_COND_* values in btm_ble_api_types.h.
+const APCF_ACTION_ADD: u8 = 0;
+const APCF_ACTION_DELETE: u8 = 1;
+const APCF_ACTION_CLEAR: u8 = 2;
+
+/// Receives the scan results of a single scanner, see
+/// |BleScanner::set_scan_result_callback|.
+pub type ScanResultCallback = Arc<dyn Fn(ScanResult) + Send + Sync>;
+
+// Works out which registered scanners a scan result belongs to. Advertising
+// reports don't say which filter index they matched in the controller, so
+// results are matched again against the conditions each scanner added to its
+// filter indices.
+#[derive(Default)]
+struct ScanResultRouter {
+    // Owning scanner id and conditions of each filter index.
+    filters: HashMap<u8, (u8, Vec<ScanFilterCondition>)>,
+    callbacks: HashMap<u8, ScanResultCallback>,
+}
+
+impl ScanResultRouter {
+    fn param_setup(&mut self, scanner_id: u8, action: u8, filter_index: u8) {
+        match action {
+            APCF_ACTION_ADD => {
+                self.filters.entry(filter_index).or_insert_with(|| (scanner_id, vec![])).0 =
+                    scanner_id;
+            }
+            APCF_ACTION_DELETE => {
+                self.filters.remove(&filter_index);
+            }
+            APCF_ACTION_CLEAR => self.filters.retain(|_, (owner, _)| *owner != scanner_id),
+            _ => (),
+        }
+    }
+
+    fn add_conditions(&mut self, filter_index: u8, conditions: &[ScanFilterCondition]) {
+        if let Some((_, filter)) = self.filters.get_mut(&filter_index) {
+            filter.extend_from_slice(conditions);
+        }
+    }
+
+    fn clear_conditions(&mut self, filter_index: u8) {
+        if let Some((_, filter)) = self.filters.get_mut(&filter_index) {
+            filter.clear();
+        }
+    }
+
+    fn remove_scanner(&mut self, scanner_id: u8) {
+        self.callbacks.remove(&scanner_id);
+        self.filters.retain(|_, (owner, _)| *owner != scanner_id);
+    }
+
+    // Callbacks of the scanners that |result| belongs to: those with a filter
+    // index whose conditions it all meets, and those without any filter.
+    fn route(&self, result: &ScanResult) -> Vec<ScanResultCallback> {
+        if self.callbacks.is_empty() {
+            return vec![];
+        }
+
+        let fields = parse_adv_data(&result.adv_data);
+        self.callbacks
+            .iter()
+            .filter(|(scanner_id, _)| {
+                let mut filters =
+                    self.filters.values().filter(|(owner, _)| owner == *scanner_id).peekable();
+                filters.peek().is_none()
+                    || filters.any(|(_, conditions)| {
+                        conditions.iter().all(|condition| condition.matches(result, &fields))
+                    })
+            })
+            .map(|(_, callback)| callback.clone())
+            .collect()
+    }
+}
+
+/// An advertising data pattern matched by an MSFT advertisement monitor.
+#[derive(Clone, Debug)]
+pub struct MsftAdvMonitorPattern {
+    pub ad_type: u8,
+    /// Offset of |content| within the AD structure's data.
+    pub start_byte: u8,
+    pub content: Vec<u8>,
+}
+
+/// Controller-offloaded advertisement monitor of the Microsoft HCI extension.
+/// A device is reported found once its RSSI reaches |rssi_high_threshold| and
+/// lost once it stays below |rssi_low_threshold| for |rssi_low_timeout|
+/// seconds.
+#[derive(Clone, Debug)]
+pub struct MsftAdvMonitor {
+    pub rssi_high_threshold: i8,
+    pub rssi_low_threshold: i8,
+    pub rssi_low_timeout: u8,
+    /// In units of 100ms.
+    pub rssi_sampling_period: u8,
+    pub patterns: Vec<MsftAdvMonitorPattern>,
+}
+
+impl From<MsftAdvMonitor> for ffi::RustMsftAdvMonitor {
+    fn from(item: MsftAdvMonitor) -> Self {
+        ffi::RustMsftAdvMonitor {
+            rssi_high_threshold: item.rssi_high_threshold,
+            rssi_low_threshold: item.rssi_low_threshold,
+            rssi_low_timeout: item.rssi_low_timeout,
+            rssi_sampling_period: item.rssi_sampling_period,
+            patterns: item
+                .patterns
+                .into_iter()
+                .map(|pattern| ffi::RustMsftAdvMonitorPattern {
+                    ad_type: pattern.ad_type,
+                    start_byte: pattern.start_byte,
+                    content: pattern.content,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ScanResultFilterState {
+    filter: ScanResultFilter,
+    // When each address was last reported, for |dedup_window|.
+    last_reported: HashMap<RawAddress, Instant>,
+}
+
+impl ScanResultFilterState {
+    fn should_report(&mut self, addr: &RawAddress, rssi: i8, now: Instant) -> bool {
+        if self.filter.min_rssi.map_or(false, |min_rssi| rssi < min_rssi) {
+            return false;
+        }
+
+        if let Some(allowlist) = &self.filter.address_allowlist {
+            if !allowlist.contains(addr) {
+                return false;
+            }
+        }
+
+        if let Some(window) = self.filter.dedup_window {
+            if let Some(last) = self.last_reported.get(addr) {
+                if now.duration_since(*last) < window {
+                    return false;
+                }
+            } else {
+                // Forget addresses that are out of the window anyway so the
+                // map doesn't grow with every device ever seen.
+                self.last_reported.retain(|_, last| now.duration_since(*last) < window);
+            }
+            self.last_reported.insert(*addr, now);
+        }
+
+        true
+    }
+}
+
+pub struct BleScanner {
+    _internal: RawBleScannerWrapper,
+    internal_cxx: cxx::UniquePtr<ffi::BleScannerIntf>,
+
+    // Last scan state requested via |scan|.
+    is_scanning: bool,
+
+    // Applied to scan results before they are dispatched.
+    result_filter: Arc<Mutex<ScanResultFilterState>>,
+
+    periodic_reports: Arc<Mutex<PeriodicReportAssembler>>,
+
+    coalescer: Arc<Mutex<ScanCoalescer>>,
+
+    router: Arc<Mutex<ScanResultRouter>>,
+}
+
+impl BleScanner {
+    pub(crate) fn new(
+        raw_gatt: *const btgatt_interface_t,
+        internal_cxx: cxx::UniquePtr<ffi::BleScannerIntf>,
+    ) -> Self {
+        BleScanner {
+            _internal: RawBleScannerWrapper {
+                _raw: unsafe { (*raw_gatt).scanner as *const BleScannerInterface },
+            },
+            internal_cxx,
+            is_scanning: false,
+            result_filter: Arc::new(Mutex::new(ScanResultFilterState::default())),
+            periodic_reports: Arc::new(Mutex::new(PeriodicReportAssembler::default())),
+            coalescer: Arc::new(Mutex::new(ScanCoalescer::default())),
+            router: Arc::new(Mutex::new(ScanResultRouter::default())),
+        }
+    }
+
+    // Whether |cb| passes the result filter and should be dispatched.
+    fn on_callback(
+        result_filter: &Mutex<ScanResultFilterState>,
+        cb: &GattScannerCallbacks,
+    ) -> bool {
+        match cb {
+            GattScannerCallbacks::OnScanResult(result) => result_filter
+                .lock()
+                .unwrap()
+                .should_report(&result.address, result.rssi, Instant::now()),
+            _ => true,
+        }
+    }
+
+    // Hands scan results to the callbacks of the scanners they belong to.
+    fn on_route_callback(router: &Mutex<ScanResultRouter>, cb: &GattScannerCallbacks) {
+        if let GattScannerCallbacks::OnScanResult(result) = cb {
+            // Called without the lock so that callbacks can use the scanner.
+            let callbacks = router.lock().unwrap().route(result);
+            for callback in callbacks {
+                callback(result.clone());
+            }
+        }
+    }
+
+    // Reassembles fragmented periodic advertising reports. Returns the
+    // callback to dispatch, if any.
+    fn on_periodic_callback(
+        periodic_reports: &Mutex<PeriodicReportAssembler>,
+        cb: GattScannerCallbacks,
+    ) -> Option<GattScannerCallbacks> {
+        match cb {
+            GattScannerCallbacks::OnPeriodicSyncReport(report) => periodic_reports
+                .lock()
+                .unwrap()
+                .push(report)
+                .map(GattScannerCallbacks::OnPeriodicSyncReport),
+            GattScannerCallbacks::OnPeriodicSyncLost(sync_handle) => {
+                periodic_reports.lock().unwrap().lost(sync_handle);
+                Some(cb)
+            }
+            _ => Some(cb),
+        }
+    }
+
+    // Buffers scan results while coalescing is on. Returns the callback to
+    // dispatch, if any.
+    fn on_coalesce_callback(
+        coalescer: &Mutex<ScanCoalescer>,
+        cb: GattScannerCallbacks,
+    ) -> Option<GattScannerCallbacks> {
+        match cb {
+            GattScannerCallbacks::OnScanResult(result) => {
+                let mut coalescer = coalescer.lock().unwrap();
+                if coalescer.interval.is_none() {
+                    return Some(GattScannerCallbacks::OnScanResult(result));
+                }
+                coalescer.push(result);
+                None
+            }
+            _ => Some(cb),
+        }
+    }
+
+    /// Coalesces scan results into one |GattScannerCallbacks::OnScanResultsBatch|
+    /// every |interval|, keeping only the strongest result of each advertiser.
+    /// Results are dispatched one by one as |GattScannerCallbacks::OnScanResult|
+    /// until this is called; a zero |interval| goes back to that, dispatching
+    /// whatever was buffered first.
+    pub fn set_coalescing(&self, interval: Duration) {
+        let (generation, buffered) = {
+            let mut coalescer = self.coalescer.lock().unwrap();
+            coalescer.generation += 1;
+            coalescer.interval = if interval.is_zero() { None } else { Some(interval) };
+            (coalescer.generation, coalescer.take())
+        };
+        BleScanner::dispatch_batch(buffered);
+        if interval.is_zero() {
+            return;
+        }
+
+        // Stops once the interval changes or the scanner is gone.
+        let coalescer = Arc::downgrade(&self.coalescer);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            let batch = match coalescer.upgrade() {
+                Some(coalescer) => {
+                    let mut coalescer = coalescer.lock().unwrap();
+                    if coalescer.generation != generation {
+                        return;
+                    }
+                    coalescer.take()
+                }
+                None => return,
+            };
+            BleScanner::dispatch_batch(batch);
+        });
+    }
+
+    fn dispatch_batch(batch: Vec<ScanResult>) {
+        if batch.is_empty() {
+            return;
+        }
+
+        // Not registered before |Gatt::initialize|.
+        let dispatcher = get_dispatchers().lock().unwrap().get::<GDScannerCb>();
+        if let Some(dispatcher) = dispatcher {
+            (dispatcher.lock().unwrap().dispatch)(GattScannerCallbacks::OnScanResultsBatch(batch));
+        }
+    }
+
+    /// Filters the results dispatched as |GattScannerCallbacks::OnScanResult|.
+    /// Results are unfiltered until this is called; passing
+    /// |ScanResultFilter::default()| removes the filter again.
+    pub fn set_result_filter(&mut self, filter: ScanResultFilter) {
+        let mut state = self.result_filter.lock().unwrap();
+        state.filter = filter;
+        state.last_reported.clear();
+    }
+
+    /// Whether the controller supports the Microsoft HCI extension's
+    /// advertisement monitors. Without them the |msft_adv_monitor_*| calls
+    /// return |BtStatus::Unsupported| without doing anything.
+    pub fn is_msft_supported(&self) -> bool {
+        GattCapabilities::query().msft
+    }
+
+    /// Adds an advertisement monitor. The handle allocated by the controller
+    /// is returned via |GattScannerInbandCallbacks::MsftAdvMonitorAddCallback|.
+    /// Matching devices are then reported via
+    /// |GattScannerCallbacks::OnMsftAdvMonitorDeviceFound| and
+    /// |GattScannerCallbacks::OnMsftAdvMonitorDeviceLost| once the monitors
+    /// are enabled with |msft_adv_monitor_enable|.
+    pub fn msft_adv_monitor_add(&mut self, monitor: MsftAdvMonitor) -> BtStatus {
+        if !self.is_msft_supported() {
+            return BtStatus::Unsupported;
+        }
+
+        mutcxxcall!(self, MsftAdvMonitorAdd, monitor.into());
+        BtStatus::Success
+    }
+
+    /// Removes a monitor added by |msft_adv_monitor_add|. Completion is
+    /// reported via |GattScannerInbandCallbacks::MsftAdvMonitorRemoveCallback|.
+    pub fn msft_adv_monitor_remove(&mut self, monitor_handle: u8) -> BtStatus {
+        if !self.is_msft_supported() {
+            return BtStatus::Unsupported;
+        }
+
+        mutcxxcall!(self, MsftAdvMonitorRemove, monitor_handle);
+        BtStatus::Success
+    }
+
+    /// Enables or disables all advertisement monitors at once. Completion is
+    /// reported via |GattScannerInbandCallbacks::MsftAdvMonitorEnableCallback|.
+    pub fn msft_adv_monitor_enable(&mut self, enable: bool) -> BtStatus {
+        if !self.is_msft_supported() {
+            return BtStatus::Unsupported;
+        }
+
+        mutcxxcall!(self, MsftAdvMonitorEnable, enable);
+        BtStatus::Success
+    }
+
+    /// Registers a scanner for |app_uuid|. The allocated scanner id and status
+    /// are returned via |GattScannerCallbacks::OnScannerRegistered|.
+    pub fn register_scanner(&mut self, app_uuid: Uuid) {
+        mutcxxcall!(self, RegisterScanner, app_uuid.into());
+    }
+
+    /// Releases a scanner id allocated by |register_scanner|, along with its
+    /// result callback.
+    pub fn unregister_scanner(&mut self, scanner_id: u8) {
+        self.router.lock().unwrap().remove_scanner(scanner_id);
+        mutcxxcall!(self, Unregister, scanner_id);
+    }
+
+    /// Delivers the scan results that belong to |scanner_id| to |callback|,
+    /// so that each component scanning with its own scanner only sees its own
+    /// results. They are still dispatched as |GattScannerCallbacks::OnScanResult|
+    /// too.
+    ///
+    /// A result belongs to the scanner if it meets all the conditions of one
+    /// of the filter indices the scanner set up with |scan_filter_param_setup|
+    /// and |scan_filter_add|. A scanner without filter indices gets every
+    /// result. Results dropped by |set_result_filter| aren't delivered.
+    pub fn set_scan_result_callback(&mut self, scanner_id: u8, callback: ScanResultCallback) {
+        self.router.lock().unwrap().callbacks.insert(scanner_id, callback);
+    }
+
+    /// Stops delivering results to the callback set by
+    /// |set_scan_result_callback|.
+    pub fn remove_scan_result_callback(&mut self, scanner_id: u8) {
+        self.router.lock().unwrap().callbacks.remove(&scanner_id);
+    }
+
+    /// Starts or stops LE scanning. Requesting the state that is already active
+    /// is a no-op. Results are reported via |GattScannerCallbacks::OnScanResult|.
+    ///
+    /// Duplicate filtering is disabled in the controller, so every received
+    /// advertising report is delivered, including repeats from the same device.
+    pub fn scan(&mut self, enable: bool) {
+        if self.is_scanning == enable {
+            return;
+        }
+
+        self.is_scanning = enable;
+        mutcxxcall!(self, Scan, enable);
+    }
+
+    /// Adds, deletes or clears the parameters of a filter index for a scanner.
+    /// Completion is reported via
+    /// |GattScannerInbandCallbacks::FilterParamSetupCallback|.
+    pub fn scan_filter_param_setup(
+        &mut self,
+        scanner_id: u8,
+        action: u8,
+        filter_index: u8,
+        param: GattFilterParam,
+    ) {
+        self.router.lock().unwrap().param_setup(scanner_id, action, filter_index);
+        mutcxxcall!(self, ScanFilterParamSetup, scanner_id, action, filter_index, param);
+    }
+
+    /// Adds filter conditions to a filter index. Completion is reported via
+    /// |GattScannerInbandCallbacks::FilterConfigCallback|.
+    pub fn scan_filter_add(&mut self, filter_index: u8, filters: Vec<ScanFilterCondition>) {
+        self.router.lock().unwrap().add_conditions(filter_index, &filters);
+        let filters: Vec<ApcfCommand> = filters.into_iter().map(|f| f.into()).collect();
+        mutcxxcall!(self, ScanFilterAdd, filter_index, filters);
+    }
+
+    /// Clears all filter conditions of a filter index. Completion is reported
+    /// via |GattScannerInbandCallbacks::FilterConfigCallback|.
+    pub fn scan_filter_clear(&mut self, filter_index: u8) {
+        self.router.lock().unwrap().clear_conditions(filter_index);
+        mutcxxcall!(self, ScanFilterClear, filter_index);
+    }
+
+    /// Enables or disables filtering in the controller. Completion is reported
+    /// via |GattScannerInbandCallbacks::EnableCallback|.
+    pub fn scan_filter_enable(&mut self, enable: bool) {
+        mutcxxcall!(self, ScanFilterEnable, enable);
+    }
+
+    /// Sets the scan interval and window, in units of 0.625ms, and the PHYs
+    /// to scan on. Scanning on several PHYs uses the same interval and window
+    /// on each. Completion is reported via
+    /// |GattScannerCallbacks::OnSetScannerParameterComplete|.
+    ///
+    /// Scanning on the LE Coded PHY, for long range advertisements, requires
+    /// a controller with both extended advertising and the coded PHY; without
+    /// them this returns |BtStatus::Unsupported|.
+    pub fn set_scan_parameters(
+        &mut self,
+        scanner_id: u8,
+        scan_interval: u16,
+        scan_window: u16,
+        scan_phy: ScanPhyMask,
+    ) -> BtStatus {
+        if scan_phy.is_empty() {
+            return BtStatus::InvalidParam;
+        }
+        if scan_phy.contains(ScanPhyMask::LE_CODED) {
+            let capabilities = GattCapabilities::query();
+            if !capabilities.coded_phy || !capabilities.extended_advertising {
+                return BtStatus::Unsupported;
+            }
+        }
+
+        mutcxxcall!(
+            self,
+            SetScanParameters,
+            scanner_id,
+            scan_interval,
+            scan_window,
+            scan_phy.bits()
+        );
+        BtStatus::Success
+    }
+
+    /// Configures controller storage for batch scan results. |full_max| and
+    /// |trunc_max| are the percentages of storage reserved for full and
+    /// truncated reports, and |notify_threshold| is the percentage of storage
+    /// that must fill up before |OnBatchScanThresholdCrossed| is sent.
+    /// Completion is reported via |GattScannerInbandCallbacks::StatusCallback|.
+    pub fn batchscan_config_storage(
+        &mut self,
+        scanner_id: u8,
+        full_max: i32,
+        trunc_max: i32,
+        notify_threshold: i32,
+    ) -> BtStatus {
+        let percent = 0..=100;
+        if !percent.contains(&full_max)
+            || !percent.contains(&trunc_max)
+            || !percent.contains(&notify_threshold)
+        {
+            return BtStatus::InvalidParam;
+        }
+
+        mutcxxcall!(
+            self,
+            BatchscanConfigStorage,
+            scanner_id,
+            full_max,
+            trunc_max,
+            notify_threshold
+        );
+        BtStatus::Success
+    }
+
+    /// Starts batch scanning. Completion is reported via
+    /// |GattScannerInbandCallbacks::StatusCallback| with scanner id 0.
+    pub fn batchscan_enable(
+        &mut self,
+        scan_mode: i32,
+        scan_interval: u16,
+        scan_window: u16,
+        addr_type: i32,
+        discard_rule: i32,
+    ) {
+        mutcxxcall!(
+            self,
+            BatchscanEnable,
+            scan_mode,
+            scan_interval,
+            scan_window,
+            addr_type,
+            discard_rule
+        );
+    }
+
+    /// Stops batch scanning. Completion is reported via
+    /// |GattScannerInbandCallbacks::StatusCallback| with scanner id 0.
+    pub fn batchscan_disable(&mut self) {
+        mutcxxcall!(self, BatchscanDisable);
+    }
+
+    /// Reads out the stored reports. They are delivered via
+    /// |GattScannerCallbacks::OnBatchScanReports|.
+    pub fn batchscan_read_reports(&mut self, scanner_id: u8, scan_mode: i32) {
+        mutcxxcall!(self, BatchscanReadReports, scanner_id, scan_mode);
+    }
+
+    /// Starts syncing to the periodic advertising train |sid| of |address|. The
+    /// result is reported via |GattScannerCallbacks::OnPeriodicSyncStarted|
+    /// and reports then arrive via |GattScannerCallbacks::OnPeriodicSyncReport|
+    /// until |GattScannerCallbacks::OnPeriodicSyncLost|.
+    pub fn start_sync(&mut self, sid: u8, address: RawAddress, skip: u16, timeout: u16) {
+        let addr = ffi::RustRawAddress { address: address.val };
+        mutcxxcall!(self, StartSync, sid, addr, skip, timeout);
+    }
+
+    /// Terminates an established periodic sync.
+    pub fn stop_sync(&mut self, handle: u16) {
+        mutcxxcall!(self, StopSync, handle);
+    }
+
+    /// Cancels a |start_sync| that has not been established yet.
+    pub fn cancel_create_sync(&mut self, sid: u8, address: RawAddress) {
+        let addr = ffi::RustRawAddress { address: address.val };
+        mutcxxcall!(self, CancelCreateSync, sid, addr);
+    }
+
+    /// Transfers the periodic sync |sync_handle| to a connected peer (PAST).
+    /// Completion is reported via
+    /// |GattScannerCallbacks::OnPeriodicSyncTransferred|.
+    pub fn transfer_sync(&mut self, address: RawAddress, service_data: u16, sync_handle: u16) {
+        let addr = ffi::RustRawAddress { address: address.val };
+        mutcxxcall!(self, TransferSync, addr, service_data, sync_handle);
+    }
+
+    /// Transfers the sync info of the local advertising set |adv_handle| to a
+    /// connected peer. Completion is reported via
+    /// |GattScannerCallbacks::OnPeriodicSyncTransferred|.
+    pub fn transfer_set_info(&mut self, address: RawAddress, service_data: u16, adv_handle: u8) {
+        let addr = ffi::RustRawAddress { address: address.val };
+        mutcxxcall!(self, TransferSetInfo, addr, service_data, adv_handle);
+    }
+
+    /// Sets how syncs transferred by |address| are received.
+    pub fn sync_tx_parameters(&mut self, address: RawAddress, mode: u8, skip: u16, timeout: u16) {
+        let addr = ffi::RustRawAddress { address: address.val };
+        mutcxxcall!(self, SyncTxParameters, addr, mode, skip, timeout);
+    }
+}
+
+/// Maximum length of extended advertising (or scan response) data that a
+/// controller can accept for a single advertising set.
+pub const EXT_ADV_MAX_DATA_LEN: usize = 1650;
+
+/// Advertising and scanning features of the controller. Everything is
+/// unsupported until the controller has been read while enabling the stack.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GattCapabilities {
+    pub extended_advertising: bool,
+    pub periodic_advertising: bool,
+    /// Whether the LE 2M PHY is supported, for faster connections.
+    pub le_2m_phy: bool,
+    /// Whether the LE Coded PHY is supported, for long range connections,
+    /// advertising and scanning.
+    pub coded_phy: bool,
+    /// Number of advertising sets that can be enabled at the same time.
+    pub max_advertising_sets: u8,
+    /// Maximum length of advertising, scan response and periodic data of a
+    /// set. Controllers without extended advertising only support 31 bytes.
+    pub max_advertising_data_length: u16,
+    /// Whether the Microsoft HCI extension's advertisement monitors can be
+    /// used. Same as |BleScanner::is_msft_supported|.
+    pub msft: bool,
+}
+
+impl GattCapabilities {
+    fn query() -> Self {
+        let capabilities = ffi::GetGattCapabilities();
+        GattCapabilities {
+            extended_advertising: capabilities.le_extended_advertising_supported,
+            periodic_advertising: capabilities.le_periodic_advertising_supported,
+            le_2m_phy: capabilities.le_2m_phy_supported,
+            coded_phy: capabilities.le_coded_phy_supported,
+            max_advertising_sets: capabilities.max_advertising_sets,
+            max_advertising_data_length: capabilities.max_advertising_data_length,
+            msft: capabilities.msft_supported,
+        }
+    }
+}
+
+/// One advertising set for |BleAdvertiser::start_advertising_sets|. The fields
+/// match the arguments of |BleAdvertiser::start_advertising_set|.
+pub struct AdvertisingSetConfig {
+    pub reg_id: i32,
+    pub params: AdvertiseParameters,
+    pub advertise_data: Vec<u8>,
+    pub scan_response_data: Vec<u8>,
+    pub periodic_params: PeriodicAdvertisingParameters,
+    pub periodic_data: Vec<u8>,
+    pub duration: u16,
+    pub max_ext_adv_events: u8,
+}
+
+pub struct BleAdvertiser {
+    // All advertiser operations go through the cxx shim, which owns the
+    // pointer to |BleAdvertiserInterface|.
+    internal_cxx: cxx::UniquePtr<ffi::BleAdvertiserIntf>,
+
+    // Advertiser ids allocated by the stack. Updated from the advertising
+    // callbacks before they are dispatched.
+    advertisers: Arc<Mutex<HashSet<u8>>>,
+
+    // Registration ids of sets started via |start_advertising_set| that have
+    // not reported |GattAdvCallbacks::OnAdvertisingSetStarted| yet.
+    starting_sets: Arc<Mutex<HashSet<i32>>>,
+}
+
+impl BleAdvertiser {
+    pub(crate) fn new(internal_cxx: cxx::UniquePtr<ffi::BleAdvertiserIntf>) -> Self {
+        BleAdvertiser {
+            internal_cxx,
+            advertisers: Arc::new(Mutex::new(HashSet::new())),
+            starting_sets: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Records advertiser ids confirmed by the in-band callbacks.
+    fn on_inband_callback(advertisers: &Mutex<HashSet<u8>>, cb: &GattAdvInbandCallbacks) {
+        match cb {
+            GattAdvInbandCallbacks::IdStatusCallback(adv_id, status) if *status == 0 => {
+                advertisers.lock().unwrap().insert(*adv_id);
+            }
+            _ => (),
+        }
+    }
+
+    /// Records advertiser ids allocated via |start_advertising_set|.
+    fn on_callback(
+        advertisers: &Mutex<HashSet<u8>>,
+        starting_sets: &Mutex<HashSet<i32>>,
+        cb: &GattAdvCallbacks,
+    ) {
+        match cb {
+            GattAdvCallbacks::OnAdvertisingSetStarted(reg_id, adv_id, _, status) => {
+                starting_sets.lock().unwrap().remove(reg_id);
+                if *status == 0 {
+                    advertisers.lock().unwrap().insert(*adv_id);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Whether |adv_id| is an advertiser currently allocated by the stack.
+    pub fn is_registered(&self, adv_id: u8) -> bool {
+        self.advertisers.lock().unwrap().contains(&adv_id)
+    }
+
+    /// Registers a new advertiser. The allocated advertiser id and status are
+    /// returned via |GattAdvInbandCallbacks::IdStatusCallback|.
+    pub fn register_advertiser(&mut self) {
+        mutcxxcall!(self, RegisterAdvertiser);
+    }
+
+    /// Releases an advertiser id. The id may be handed out again by a later
+    /// registration.
+    pub fn unregister(&mut self, adv_id: u8) -> BtStatus {
+        if !self.advertisers.lock().unwrap().remove(&adv_id) {
+            return BtStatus::InvalidParam;
+        }
+
+        mutcxxcall!(self, Unregister, adv_id);
+        BtStatus::Success
+    }
+
+    /// Reads the current own address (identity or RPA) of an advertising set.
+    /// The result is reported via |GattAdvCallbacks::OnOwnAddressRead|.
+    pub fn get_own_address(&mut self, adv_id: u8) -> BtStatus {
+        if !self.is_registered(adv_id) {
+            return BtStatus::InvalidParam;
+        }
+
+        mutcxxcall!(self, GetOwnAddress, adv_id);
+        BtStatus::Success
+    }
+
+    pub fn set_parameters(&mut self, adv_id: u8, params: AdvertiseParameters) {
+        mutcxxcall!(self, SetParameters, adv_id, params);
+    }
+    pub fn set_data(&mut self, adv_id: u8, set_scan_rsp: bool, data: Vec<u8>) {
+        mutcxxcall!(self, SetData, adv_id, set_scan_rsp, data);
+    }
+
+    /// Updates the advertising data of an existing set. Completion is reported
+    /// via |GattAdvCallbacks::OnAdvertisingDataSet|.
+    pub fn set_advertising_data(&mut self, adv_id: u8, data: &[u8]) -> BtStatus {
+        if data.len() > EXT_ADV_MAX_DATA_LEN {
+            return BtStatus::InvalidParam;
+        }
+
+        self.set_data(adv_id, false, data.to_vec());
+        BtStatus::Success
+    }
+
+    /// Updates the scan response data of an existing set. Completion is
+    /// reported via |GattAdvCallbacks::OnScanResponseDataSet|.
+    pub fn set_scan_response_data(&mut self, adv_id: u8, data: &[u8]) -> BtStatus {
+        if data.len() > EXT_ADV_MAX_DATA_LEN {
+            return BtStatus::InvalidParam;
+        }
+
+        self.set_data(adv_id, true, data.to_vec());
+        BtStatus::Success
+    }
+
+    /// Starts or stops advertising on an existing set without releasing its
+    /// advertiser id. Completion is reported via
+    /// |GattAdvCallbacks::OnAdvertisingEnabled|.
+    pub fn enable(
+        &mut self,
+        adv_id: u8,
+        enable: bool,
+        duration: u16,
+        max_ext_adv_events: u8,
+    ) -> BtStatus {
+        if !self.is_registered(adv_id) {
+            return BtStatus::InvalidParam;
+        }
+
+        mutcxxcall!(self, Enable, adv_id, enable, duration, max_ext_adv_events);
+        BtStatus::Success
+    }
+
+    pub fn start_advertising(
+        &mut self,
+        adv_id: u8,
+        params: AdvertiseParameters,
+        advertise_data: Vec<u8>,
+        scan_response_data: Vec<u8>,
+        timeout_in_sec: i32,
+    ) {
+        mutcxxcall!(
+            self,
+            StartAdvertising,
+            adv_id,
+            params,
+            advertise_data,
+            scan_response_data,
+            timeout_in_sec
+        );
+    }
+    /// Registers and starts a new advertising set in a single step. Completion
+    /// is reported via |GattAdvCallbacks::OnAdvertisingSetStarted| with the
+    /// same |reg_id| so callers can correlate the allocated advertiser id.
+    ///
+    /// Sets the controller can't advertise are not issued: periodic
+    /// advertising without controller support returns |BtStatus::Unsupported|
+    /// and data longer than the controller accepts |BtStatus::InvalidParam|.
+    pub fn start_advertising_set(
+        &mut self,
+        reg_id: i32,
+        params: AdvertiseParameters,
+        advertise_data: Vec<u8>,
+        scan_response_data: Vec<u8>,
+        periodic_params: PeriodicAdvertisingParameters,
+        periodic_data: Vec<u8>,
+        duration: u16,
+        max_ext_adv_events: u8,
+    ) -> BtStatus {
+        let capabilities = GattCapabilities::query();
+        if periodic_params.enable != 0 && !capabilities.periodic_advertising {
+            return BtStatus::Unsupported;
+        }
+        let max_len = usize::from(capabilities.max_advertising_data_length);
+        if [&advertise_data, &scan_response_data, &periodic_data].iter().any(|d| d.len() > max_len)
+        {
+            return BtStatus::InvalidParam;
+        }
+
+        self.starting_sets.lock().unwrap().insert(reg_id);
+        mutcxxcall!(
+            self,
+            StartAdvertisingSet,
+            reg_id,
+            params,
+            advertise_data,
             scan_response_data,
             periodic_params,
             periodic_data,
             duration,
             max_ext_adv_events
         );
+        BtStatus::Success
+    }
+
+    /// Registers and starts every set in |sets| without waiting for earlier
+    /// sets to start. Each set completes through its own
+    /// |GattAdvCallbacks::OnAdvertisingSetStarted|, identified by the |reg_id|
+    /// of its config.
+    ///
+    /// Returns the status of each set, in order. Besides the sets that
+    /// |start_advertising_set| rejects, sets whose |reg_id| is already starting
+    /// are not issued, since their completion could not be told apart from
+    /// the other set's.
+    pub fn start_advertising_sets(&mut self, sets: Vec<AdvertisingSetConfig>) -> Vec<BtStatus> {
+        sets.into_iter()
+            .map(|set| {
+                if self.starting_sets.lock().unwrap().contains(&set.reg_id) {
+                    return BtStatus::InvalidParam;
+                }
+
+                self.start_advertising_set(
+                    set.reg_id,
+                    set.params,
+                    set.advertise_data,
+                    set.scan_response_data,
+                    set.periodic_params,
+                    set.periodic_data,
+                    set.duration,
+                    set.max_ext_adv_events,
+                )
+            })
+            .collect()
+    }
+
+    /// Configures periodic advertising on an existing set. Completion is
+    /// reported via |GattAdvCallbacks::OnPeriodicAdvertisingParametersUpdated|.
+    pub fn set_periodic_advertising_parameters(
+        &mut self,
+        adv_id: u8,
+        params: PeriodicAdvertisingParameters,
+    ) -> BtStatus {
+        if !self.is_registered(adv_id) {
+            return BtStatus::InvalidParam;
+        }
+
+        mutcxxcall!(self, SetPeriodicAdvertisingParameters, adv_id, params);
+        BtStatus::Success
+    }
+
+    /// Updates the periodic advertising data of an existing set. Completion is
+    /// reported via |GattAdvCallbacks::OnPeriodicAdvertisingDataSet|.
+    pub fn set_periodic_advertising_data(&mut self, adv_id: u8, data: &[u8]) -> BtStatus {
+        if !self.is_registered(adv_id) || data.len() > EXT_ADV_MAX_DATA_LEN {
+            return BtStatus::InvalidParam;
+        }
+
+        mutcxxcall!(self, SetPeriodicAdvertisingData, adv_id, data.to_vec());
+        BtStatus::Success
+    }
+
+    /// Starts or stops periodic advertising on an existing set. Completion is
+    /// reported via |GattAdvCallbacks::OnPeriodicAdvertisingEnabled|.
+    ///
+    /// Including the ADI field in AUX_SYNC_IND is not supported by the
+    /// underlying interface yet, so |include_adi| must be false.
+    pub fn set_periodic_advertising_enable(
+        &mut self,
+        adv_id: u8,
+        enable: bool,
+        include_adi: bool,
+    ) -> BtStatus {
+        if include_adi {
+            return BtStatus::Unsupported;
+        }
+        if !self.is_registered(adv_id) {
+            return BtStatus::InvalidParam;
+        }
+
+        mutcxxcall!(self, SetPeriodicAdvertisingEnable, adv_id, enable);
+        BtStatus::Success
+    }
+}
+
+/// Client, server and scanner callbacks in the order they were dispatched,
+/// returned by |Gatt::event_stream|.
+#[derive(Clone, Debug)]
+pub enum GattEvent {
+    Client(GattClientCallbacks),
+    Server(GattServerCallbacks),
+    Scanner(GattScannerCallbacks),
+}
+
+// Receives a copy of every dispatched callback while set.
+type GattEventSender = Arc<Mutex<Option<mpsc::UnboundedSender<GattEvent>>>>;
+
+fn send_event(events: &GattEventSender, event: GattEvent) {
+    let mut events = events.lock().unwrap();
+    if let Some(tx) = events.as_ref() {
+        // Fails once the receiver is dropped.
+        if tx.send(event).is_err() {
+            *events = None;
+        }
+    }
+}
+
+// When the stack last delivered a callback, stamped by every dispatcher
+// registered in |Gatt::initialize|.
+struct CallbackWatchdog {
+    last: Mutex<Instant>,
+}
+
+impl CallbackWatchdog {
+    fn new() -> Self {
+        CallbackWatchdog { last: Mutex::new(Instant::now()) }
+    }
+
+    fn stamp(&self) {
+        *self.last.lock().unwrap() = Instant::now();
+    }
+
+    fn last(&self) -> Instant {
+        *self.last.lock().unwrap()
+    }
+
+    fn is_healthy(&self, max_silence: Duration, now: Instant) -> bool {
+        now.saturating_duration_since(self.last()) <= max_silence
+    }
+}
+
+pub struct Gatt {
+    internal: RawGattWrapper,
+    is_init: bool,
+
+    pub client: GattClient,
+    pub server: GattServer,
+    pub scanner: BleScanner,
+    pub advertiser: BleAdvertiser,
+
+    metrics: Arc<GattMetrics>,
+    events: GattEventSender,
+    watchdog: Arc<CallbackWatchdog>,
+
+    // Keep callback object in memory (underlying code doesn't make copy)
+    callbacks: Option<Box<bindings::btgatt_callbacks_t>>,
+    gatt_client_callbacks: Option<Box<bindings::btgatt_client_callbacks_t>>,
+    gatt_server_callbacks: Option<Box<bindings::btgatt_server_callbacks_t>>,
+    gatt_scanner_callbacks: Option<Box<bindings::btgatt_scanner_callbacks_t>>,
+}
+
+impl Gatt {
+    /// Reads the advertising and scanning features of the controller, to check
+    /// before using the APIs that depend on them.
+    pub fn capabilities(&self) -> GattCapabilities {
+        GattCapabilities::query()
+    }
+
+    pub fn new(intf: &BluetoothInterface) -> Option<Gatt> {
+        let r = intf.get_profile_interface(SupportedProfiles::Gatt);
+
+        if r == std::ptr::null() {
+            return None;
+        }
+
+        let gatt_client_intf = unsafe { ffi::GetGattClientProfile(r as *const u8) };
+        let gatt_server_intf = unsafe { ffi::GetGattServerProfile(r as *const u8) };
+        let gatt_scanner_intf = unsafe { ffi::GetBleScannerIntf(r as *const u8) };
+        let gatt_advertiser_intf = unsafe { ffi::GetBleAdvertiserIntf(r as *const u8) };
+        let metrics = Arc::new(GattMetrics::default());
+
+        Some(Gatt {
+            internal: RawGattWrapper { raw: r as *const btgatt_interface_t },
+            is_init: false,
+            client: GattClient {
+                internal: RawGattClientWrapper {
+                    raw: unsafe {
+                        (*(r as *const btgatt_interface_t)).client
+                            as *const btgatt_client_interface_t
+                    },
+                },
+                internal_cxx: gatt_client_intf,
+                pending: Arc::new(Mutex::new(PendingClientRequests::default())),
+                metrics: metrics.clone(),
+                timeouts: GattTimeouts::default(),
+            },
+            server: GattServer {
+                internal: RawGattServerWrapper {
+                    raw: unsafe {
+                        (*(r as *const btgatt_interface_t)).server
+                            as *const btgatt_server_interface_t
+                    },
+                },
+                internal_cxx: gatt_server_intf,
+                pending: Arc::new(Mutex::new(PendingServerRequests::default())),
+                metrics: metrics.clone(),
+                timeouts: GattServerTimeouts::default(),
+            },
+            scanner: BleScanner::new(r as *const btgatt_interface_t, gatt_scanner_intf),
+            advertiser: BleAdvertiser::new(gatt_advertiser_intf),
+            metrics,
+            events: Arc::new(Mutex::new(None)),
+            watchdog: Arc::new(CallbackWatchdog::new()),
+            callbacks: None,
+            gatt_client_callbacks: None,
+            gatt_server_callbacks: None,
+            gatt_scanner_callbacks: None,
+        })
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.is_init
+    }
+
+    /// Counters of client and server operation outcomes since |new|.
+    pub fn metrics(&self) -> GattMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// When the stack last delivered a callback of any kind, including scan
+    /// results that were filtered out, or when |new| was called if none has
+    /// arrived yet.
+    pub fn last_callback_instant(&self) -> Instant {
+        self.watchdog.last()
+    }
+
+    /// Whether the stack delivered a callback within the last |max_silence|.
+    /// A supervisor can restart the stack once this fails, rather than wait
+    /// for each async operation to time out on its own.
+    ///
+    /// An idle stack has nothing to report either, so |max_silence| should
+    /// exceed the longest expected gap between callbacks, e.g. between scan
+    /// results while scanning.
+    pub fn health_check(&self, max_silence: Duration) -> bool {
+        self.watchdog.is_healthy(max_silence, Instant::now())
+    }
+
+    /// Receives every client, server and scanner callback on a single channel,
+    /// after it has been passed to its dispatcher. Only the last receiver
+    /// returned gets events; calling this again replaces it.
+    pub fn event_stream(&self) -> mpsc::UnboundedReceiver<GattEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        *self.events.lock().unwrap() = Some(tx);
+        rx
+    }
+
+    pub fn initialize(
+        &mut self,
+        gatt_client_callbacks_dispatcher: GattClientCallbacksDispatcher,
+        gatt_server_callbacks_dispatcher: GattServerCallbacksDispatcher,
+        gatt_scanner_callbacks_dispatcher: GattScannerCallbacksDispatcher,
+        gatt_scanner_inband_callbacks_dispatcher: GattScannerInbandCallbacksDispatcher,
+        gatt_adv_inband_callbacks_dispatcher: GattAdvInbandCallbacksDispatcher,
+        gatt_adv_callbacks_dispatcher: GattAdvCallbacksDispatcher,
+    ) -> Result<(), GattInitError> {
+        // Fail before registering anything so that an existing registration
+        // is left intact.
+        {
+            let dispatchers = get_dispatchers();
+            let dispatchers = dispatchers.lock().unwrap();
+            ensure_unregistered::<GattClientCb>(&dispatchers, "GattClientCallbacks")?;
+            ensure_unregistered::<GattServerCb>(&dispatchers, "GattServerCallbacks")?;
+            ensure_unregistered::<GDScannerCb>(&dispatchers, "GattScannerCallbacks")?;
+            ensure_unregistered::<GDScannerInbandCb>(&dispatchers, "GattScannerInbandCallbacks")?;
+            ensure_unregistered::<GDAdvInbandCb>(&dispatchers, "GattAdvInbandCallbacks")?;
+            ensure_unregistered::<GDAdvCb>(&dispatchers, "GattAdvCallbacks")?;
+        }
+
+        // The client resolves its pending async requests before the callback
+        // is dispatched.
+        let pending = self.client.pending.clone();
+        let metrics = self.metrics.clone();
+        let client_raw = RawGattClientWrapper { raw: self.client.internal.raw };
+        let events = self.events.clone();
+        let watchdog = self.watchdog.clone();
+        let client_dispatch = gatt_client_callbacks_dispatcher.dispatch;
+        let gatt_client_callbacks_dispatcher = GattClientCallbacksDispatcher {
+            dispatch: Box::new(move |cb| {
+                watchdog.stamp();
+                #[cfg(feature = "trace-callbacks")]
+                log::debug!("GattClientCallbacks: {:?}", cb);
+                let cb = GattClient::on_callback(&pending, &metrics, cb);
+                GattClient::on_flow_control_callback(&pending, &metrics, &client_raw, &cb);
+                let cb = GattClient::on_rediscover_callback(&pending, &client_raw, cb);
+                (client_dispatch)(cb.clone());
+                send_event(&events, GattEvent::Client(cb));
+            }),
+        };
+
+        // Register dispatcher
+        get_dispatchers()
+            .lock()
+            .unwrap()
+            .set::<GattClientCb>(Arc::new(Mutex::new(gatt_client_callbacks_dispatcher)));
+
+        // The server resolves pending indications and applies connection
+        // policies before the callback is dispatched.
+        let pending = self.server.pending.clone();
+        let metrics = self.metrics.clone();
+        let server_raw = RawGattServerWrapper { raw: self.server.internal.raw };
+        let events = self.events.clone();
+        let watchdog = self.watchdog.clone();
+        let server_dispatch = gatt_server_callbacks_dispatcher.dispatch;
+        let gatt_server_callbacks_dispatcher = GattServerCallbacksDispatcher {
+            dispatch: Box::new(move |cb| {
+                watchdog.stamp();
+                #[cfg(feature = "trace-callbacks")]
+                log::debug!("GattServerCallbacks: {:?}", cb);
+                if GattServer::on_callback(&pending, &metrics, &server_raw, &cb) {
+                    (server_dispatch)(cb.clone());
+                    send_event(&events, GattEvent::Server(cb));
+                }
+            }),
+        };
+
+        get_dispatchers()
+            .lock()
+            .unwrap()
+            .set::<GattServerCb>(Arc::new(Mutex::new(gatt_server_callbacks_dispatcher)));
+
+        // Scan results are filtered, routed to their scanner and coalesced,
+        // and periodic advertising reports reassembled, before they are
+        // dispatched.
+        let result_filter = self.scanner.result_filter.clone();
+        let router = self.scanner.router.clone();
+        let periodic_reports = self.scanner.periodic_reports.clone();
+        let coalescer = self.scanner.coalescer.clone();
+        let events = self.events.clone();
+        let watchdog = self.watchdog.clone();
+        let scanner_dispatch = gatt_scanner_callbacks_dispatcher.dispatch;
+        let gatt_scanner_callbacks_dispatcher = GattScannerCallbacksDispatcher {
+            dispatch: Box::new(move |cb| {
+                watchdog.stamp();
+                #[cfg(feature = "trace-callbacks")]
+                log::debug!("GattScannerCallbacks: {:?}", cb);
+                if !BleScanner::on_callback(&result_filter, &cb) {
+                    return;
+                }
+                BleScanner::on_route_callback(&router, &cb);
+                let cb = match BleScanner::on_coalesce_callback(&coalescer, cb) {
+                    Some(cb) => cb,
+                    None => return,
+                };
+                if let Some(cb) = BleScanner::on_periodic_callback(&periodic_reports, cb) {
+                    (scanner_dispatch)(cb.clone());
+                    send_event(&events, GattEvent::Scanner(cb));
+                }
+            }),
+        };
+
+        get_dispatchers()
+            .lock()
+            .unwrap()
+            .set::<GDScannerCb>(Arc::new(Mutex::new(gatt_scanner_callbacks_dispatcher)));
+
+        let watchdog = self.watchdog.clone();
+        let scanner_inband_dispatch = gatt_scanner_inband_callbacks_dispatcher.dispatch;
+        let gatt_scanner_inband_callbacks_dispatcher = GattScannerInbandCallbacksDispatcher {
+            dispatch: Box::new(move |cb| {
+                watchdog.stamp();
+                (scanner_inband_dispatch)(cb);
+            }),
+        };
+
+        get_dispatchers().lock().unwrap().set::<GDScannerInbandCb>(Arc::new(Mutex::new(
+            gatt_scanner_inband_callbacks_dispatcher,
+        )));
+
+        // The advertiser observes its callbacks before they are dispatched so
+        // that it can keep track of allocated advertiser ids.
+        let advertisers = self.advertiser.advertisers.clone();
+        let watchdog = self.watchdog.clone();
+        let adv_inband_dispatch = gatt_adv_inband_callbacks_dispatcher.dispatch;
+        let gatt_adv_inband_callbacks_dispatcher = GattAdvInbandCallbacksDispatcher {
+            dispatch: Box::new(move |cb| {
+                watchdog.stamp();
+                BleAdvertiser::on_inband_callback(&advertisers, &cb);
+                (adv_inband_dispatch)(cb);
+            }),
+        };
+
+        let advertisers = self.advertiser.advertisers.clone();
+        let starting_sets = self.advertiser.starting_sets.clone();
+        let watchdog = self.watchdog.clone();
+        let adv_dispatch = gatt_adv_callbacks_dispatcher.dispatch;
+        let gatt_adv_callbacks_dispatcher = GattAdvCallbacksDispatcher {
+            dispatch: Box::new(move |cb| {
+                watchdog.stamp();
+                BleAdvertiser::on_callback(&advertisers, &starting_sets, &cb);
+                (adv_dispatch)(cb);
+            }),
+        };
+
+        get_dispatchers()
+            .lock()
+            .unwrap()
+            .set::<GDAdvInbandCb>(Arc::new(Mutex::new(gatt_adv_inband_callbacks_dispatcher)));
+
+        get_dispatchers()
+            .lock()
+            .unwrap()
+            .set::<GDAdvCb>(Arc::new(Mutex::new(gatt_adv_callbacks_dispatcher)));
+
+        let mut gatt_client_callbacks = Box::new(btgatt_client_callbacks_t {
+            register_client_cb: Some(gc_register_client_cb),
+            open_cb: Some(gc_open_cb),
+            close_cb: Some(gc_close_cb),
+            search_complete_cb: Some(gc_search_complete_cb),
+            register_for_notification_cb: Some(gc_register_for_notification_cb),
+            notify_cb: Some(gc_notify_cb),
+            read_characteristic_cb: Some(gc_read_characteristic_cb),
+            write_characteristic_cb: Some(gc_write_characteristic_cb),
+            read_descriptor_cb: Some(gc_read_descriptor_cb),
+            write_descriptor_cb: Some(gc_write_descriptor_cb),
+            execute_write_cb: Some(gc_execute_write_cb),
+            read_remote_rssi_cb: Some(gc_read_remote_rssi_cb),
+            configure_mtu_cb: Some(gc_configure_mtu_cb),
+            congestion_cb: Some(gc_congestion_cb),
+            get_gatt_db_cb: Some(gc_get_gatt_db_cb),
+            phy_updated_cb: Some(gc_phy_updated_cb),
+            conn_updated_cb: Some(gc_conn_updated_cb),
+            service_changed_cb: Some(gc_service_changed_cb),
+            read_multiple_cb: Some(gc_read_multiple_cb),
+            subrate_chg_cb: Some(gc_subrate_chg_cb),
+            mtu_changed_cb: Some(gc_mtu_changed_cb),
+            // These callbacks are never used and will also be removed from btif.
+            // TODO(b/200073464): Remove these.
+            services_removed_cb: None,
+            services_added_cb: None,
+        });
+
+        let mut gatt_server_callbacks = Box::new(btgatt_server_callbacks_t {
+            register_server_cb: Some(gs_register_server_cb),
+            connection_cb: Some(gs_connection_cb),
+            service_added_cb: Some(gs_service_added_cb),
+            service_stopped_cb: Some(gs_service_stopped_cb),
+            service_deleted_cb: Some(gs_service_deleted_cb),
+            request_read_characteristic_cb: Some(gs_request_read_characteristic_cb),
+            request_read_descriptor_cb: Some(gs_request_read_descriptor_cb),
+            request_write_characteristic_cb: Some(gs_request_write_characteristic_cb),
+            request_write_descriptor_cb: Some(gs_request_write_descriptor_cb),
+            request_exec_write_cb: Some(gs_request_exec_write_cb),
+            response_confirmation_cb: Some(gs_response_confirmation_cb),
+            indication_sent_cb: Some(gs_indication_sent_cb),
+            congestion_cb: Some(gs_congestion_cb),
+            mtu_changed_cb: Some(gs_mtu_changed_cb),
+            phy_updated_cb: Some(gs_phy_updated_cb),
+            conn_updated_cb: Some(gs_conn_updated_cb),
+        });
+
+        let mut gatt_scanner_callbacks = Box::new(btgatt_scanner_callbacks_t {
+            scan_result_cb: None,
+            batchscan_reports_cb: None,
+            batchscan_threshold_cb: None,
+            track_adv_event_cb: None,
+        });
+
+        let mut callbacks = Box::new(btgatt_callbacks_t {
+            size: 4 * 8,
+            client: &mut *gatt_client_callbacks,
+            server: &mut *gatt_server_callbacks,
+            scanner: &mut *gatt_scanner_callbacks,
+        });
+
+        let rawcb = &mut *callbacks;
+
+        let init = ccall!(self, init, rawcb);
+        self.is_init = init == 0;
+        self.callbacks = Some(callbacks);
+        self.gatt_client_callbacks = Some(gatt_client_callbacks);
+        self.gatt_server_callbacks = Some(gatt_server_callbacks);
+        self.gatt_scanner_callbacks = Some(gatt_scanner_callbacks);
+
+        if !self.is_init {
+            return Err(GattInitError::BtifInitFailed(init));
+        }
+
+        // Register callbacks for gatt scanner and advertiser
+        if !mutcxxcall!(self.scanner, RegisterCallbacks) {
+            return Err(GattInitError::ScannerRegisterFailed);
+        }
+        mutcxxcall!(self.advertiser, RegisterCallbacks);
+
+        Ok(())
+    }
+}
+
+impl Drop for Gatt {
+    fn drop(&mut self) {
+        // Dispatchers are only registered once |initialize| has run.
+        if self.callbacks.is_none() {
+            return;
+        }
+
+        if self.is_init {
+            ccall!(self, cleanup);
+        }
+
+        let mut dispatchers = get_dispatchers().lock().unwrap();
+        dispatchers.remove::<GattClientCb>();
+        dispatchers.remove::<GattServerCb>();
+        dispatchers.remove::<GDScannerCb>();
+        dispatchers.remove::<GDScannerInbandCb>();
+        dispatchers.remove::<GDAdvInbandCb>();
+        dispatchers.remove::<GDAdvCb>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deref_const_ffi_address;
+    use std::cell::RefCell;
+
+    #[derive(Debug, PartialEq)]
+    enum MockClientCall {
+        Connect(i32, RawAddress, u8, bool, i32, bool, i32),
+        WriteCharacteristic(i32, u16, i32, i32, Vec<u8>, i32),
+        WriteDescriptor(i32, u16, i32, Vec<u8>),
+        RegisterForNotification(i32, RawAddress, u16),
+        DeregisterForNotification(i32, RawAddress, u16),
+        Disconnect(i32, RawAddress, i32),
+        Refresh(RawAddress),
+        SearchService(i32),
+        GetGattDb(i32),
+        ConfigureMtu(i32, i32),
+        ReadCharacteristic(i32, u16, i32, i32),
+        ConnParameterUpdate(RawAddress, i32, i32, i32, i32),
+    }
+
+    thread_local! {
+        // Calls made to the interface returned by |mock_client_interface| on
+        // this thread.
+        static MOCK_CLIENT_CALLS: RefCell<Vec<MockClientCall>> = RefCell::new(vec![]);
+    }
+
+    unsafe extern "C" fn mock_connect(
+        client_if: i32,
+        bd_addr: *const FfiAddress,
+        addr_type: u8,
+        is_direct: bool,
+        transport: i32,
+        opportunistic: bool,
+        initiating_phys: i32,
+    ) -> bindings::bt_status_t {
+        let addr = deref_const_ffi_address!(bd_addr);
+        MOCK_CLIENT_CALLS.with(|calls| {
+            calls.borrow_mut().push(MockClientCall::Connect(
+                client_if,
+                addr,
+                addr_type,
+                is_direct,
+                transport,
+                opportunistic,
+                initiating_phys,
+            ))
+        });
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_disconnect(
+        client_if: i32,
+        bd_addr: *const FfiAddress,
+        conn_id: i32,
+    ) -> bindings::bt_status_t {
+        let addr = deref_const_ffi_address!(bd_addr);
+        MOCK_CLIENT_CALLS.with(|calls| {
+            calls.borrow_mut().push(MockClientCall::Disconnect(client_if, addr, conn_id))
+        });
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_refresh(
+        _client_if: i32,
+        bd_addr: *const FfiAddress,
+    ) -> bindings::bt_status_t {
+        let addr = deref_const_ffi_address!(bd_addr);
+        MOCK_CLIENT_CALLS.with(|calls| calls.borrow_mut().push(MockClientCall::Refresh(addr)));
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_search_service(
+        conn_id: i32,
+        _filter_uuid: *const Uuid,
+    ) -> bindings::bt_status_t {
+        MOCK_CLIENT_CALLS
+            .with(|calls| calls.borrow_mut().push(MockClientCall::SearchService(conn_id)));
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_get_gatt_db(conn_id: i32) -> bindings::bt_status_t {
+        MOCK_CLIENT_CALLS.with(|calls| calls.borrow_mut().push(MockClientCall::GetGattDb(conn_id)));
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_configure_mtu(conn_id: i32, mtu: i32) -> bindings::bt_status_t {
+        MOCK_CLIENT_CALLS
+            .with(|calls| calls.borrow_mut().push(MockClientCall::ConfigureMtu(conn_id, mtu)));
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_read_characteristic(
+        conn_id: i32,
+        handle: u16,
+        auth_req: i32,
+        bearer: i32,
+    ) -> bindings::bt_status_t {
+        MOCK_CLIENT_CALLS.with(|calls| {
+            calls
+                .borrow_mut()
+                .push(MockClientCall::ReadCharacteristic(conn_id, handle, auth_req, bearer))
+        });
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_write_characteristic(
+        conn_id: i32,
+        handle: u16,
+        write_type: i32,
+        auth_req: i32,
+        value: *const u8,
+        length: usize,
+        bearer: i32,
+    ) -> bindings::bt_status_t {
+        let value = ptr_to_vec(value, length);
+        MOCK_CLIENT_CALLS.with(|calls| {
+            calls.borrow_mut().push(MockClientCall::WriteCharacteristic(
+                conn_id, handle, write_type, auth_req, value, bearer,
+            ))
+        });
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_write_descriptor(
+        conn_id: i32,
+        handle: u16,
+        auth_req: i32,
+        value: *const u8,
+        length: usize,
+    ) -> bindings::bt_status_t {
+        let value = ptr_to_vec(value, length);
+        MOCK_CLIENT_CALLS.with(|calls| {
+            calls
+                .borrow_mut()
+                .push(MockClientCall::WriteDescriptor(conn_id, handle, auth_req, value))
+        });
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_register_for_notification(
+        client_if: i32,
+        bd_addr: *const FfiAddress,
+        handle: u16,
+    ) -> bindings::bt_status_t {
+        let addr = deref_const_ffi_address!(bd_addr);
+        MOCK_CLIENT_CALLS.with(|calls| {
+            calls
+                .borrow_mut()
+                .push(MockClientCall::RegisterForNotification(client_if, addr, handle))
+        });
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_deregister_for_notification(
+        client_if: i32,
+        bd_addr: *const FfiAddress,
+        handle: u16,
+    ) -> bindings::bt_status_t {
+        let addr = deref_const_ffi_address!(bd_addr);
+        MOCK_CLIENT_CALLS.with(|calls| {
+            calls
+                .borrow_mut()
+                .push(MockClientCall::DeregisterForNotification(client_if, addr, handle))
+        });
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_conn_parameter_update(
+        bd_addr: *const FfiAddress,
+        min_interval: i32,
+        max_interval: i32,
+        latency: i32,
+        timeout: i32,
+        _min_ce_len: u16,
+        _max_ce_len: u16,
+    ) -> bindings::bt_status_t {
+        let addr = deref_const_ffi_address!(bd_addr);
+        MOCK_CLIENT_CALLS.with(|calls| {
+            calls.borrow_mut().push(MockClientCall::ConnParameterUpdate(
+                addr,
+                min_interval,
+                max_interval,
+                latency,
+                timeout,
+            ))
+        });
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    // A btif client interface that records the calls it implements into
+    // |MOCK_CLIENT_CALLS|. Calls to anything else panic.
+    fn mock_client_interface() -> btgatt_client_interface_t {
+        btgatt_client_interface_t {
+            connect: Some(mock_connect),
+            disconnect: Some(mock_disconnect),
+            refresh: Some(mock_refresh),
+            search_service: Some(mock_search_service),
+            get_gatt_db: Some(mock_get_gatt_db),
+            configure_mtu: Some(mock_configure_mtu),
+            read_characteristic: Some(mock_read_characteristic),
+            write_characteristic: Some(mock_write_characteristic),
+            write_descriptor: Some(mock_write_descriptor),
+            register_for_notification: Some(mock_register_for_notification),
+            deregister_for_notification: Some(mock_deregister_for_notification),
+            conn_parameter_update: Some(mock_conn_parameter_update),
+            ..Default::default()
+        }
+    }
+
+    // A client backed by |intf| rather than the stack. Methods that go
+    // through the cxx interface can't be used.
+    fn mock_client(intf: &btgatt_client_interface_t) -> GattClient {
+        GattClient {
+            internal: RawGattClientWrapper { raw: intf as *const btgatt_client_interface_t },
+            internal_cxx: cxx::UniquePtr::null(),
+            pending: Arc::new(Mutex::new(PendingClientRequests::default())),
+            metrics: Arc::new(GattMetrics::default()),
+            timeouts: GattTimeouts::default(),
+        }
+    }
+
+    fn take_mock_client_calls() -> Vec<MockClientCall> {
+        MOCK_CLIENT_CALLS.with(|calls| calls.borrow_mut().drain(..).collect())
+    }
+
+    #[test]
+    fn client_forwards_connect() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+
+        assert_eq!(client.connect(3, &addr, true, BtTransport::Le, false, 1), BtStatus::Success);
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![MockClientCall::Connect(3, addr, 0, true, i32::from(BtTransport::Le), false, 1)]
+        );
+    }
+
+    #[test]
+    fn initiating_phys_follow_capabilities() {
+        let basic = GattCapabilities::default();
+        let full = GattCapabilities { le_2m_phy: true, coded_phy: true, ..Default::default() };
+        let long_range = InitiatingPhys::LE_1M | InitiatingPhys::LE_CODED;
+
+        assert_eq!(InitiatingPhys::default_for(&basic).bits(), 0x01);
+        assert_eq!(InitiatingPhys::default_for(&full).bits(), 0x03);
+
+        assert_eq!(InitiatingPhys::LE_1M.check(&basic), BtStatus::Success);
+        assert_eq!(long_range.check(&basic), BtStatus::Unsupported);
+        assert_eq!(long_range.check(&full), BtStatus::Success);
+        assert_eq!(InitiatingPhys::LE_CODED.check(&full), BtStatus::Success);
+        assert_eq!(InitiatingPhys::LE_2M.check(&full), BtStatus::InvalidParam);
+        assert_eq!(InitiatingPhys::empty().check(&full), BtStatus::InvalidParam);
+    }
+
+    #[test]
+    fn client_connect_forwards_addr_type() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let addr = RawAddress { val: [0xc1, 2, 3, 4, 5, 6] };
+        let transport = i32::from(BtTransport::Le);
+
+        let status = client.connect_with_addr_type(
+            3,
+            &addr,
+            BleAddressType::Random,
+            true,
+            BtTransport::Le,
+            false,
+            1,
+        );
+        assert_eq!(status, BtStatus::Success);
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![MockClientCall::Connect(3, addr, 1, true, transport, false, 1)]
+        );
+
+        // Anonymous advertisers can't be connected to.
+        let status = client.connect_with_addr_type(
+            3,
+            &addr,
+            BleAddressType::Anonymous,
+            true,
+            BtTransport::Le,
+            false,
+            1,
+        );
+        assert_eq!(status, BtStatus::InvalidParam);
+        assert!(take_mock_client_calls().is_empty());
+    }
+
+    #[test]
+    fn client_connect_async_times_out() {
+        let intf = mock_client_interface();
+        let mut client = mock_client(&intf);
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+
+        // Nothing completes the connect, so the configured default applies.
+        client.set_timeouts(GattTimeouts::default().with_connect(Duration::from_millis(10)));
+        let result =
+            rt.block_on(client.connect_async(3, &addr, true, BtTransport::Le, false, 1, None));
+        assert_eq!(result, Err(GattOpError::Timeout));
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![
+                MockClientCall::Connect(3, addr, 0, true, i32::from(BtTransport::Le), false, 1),
+                MockClientCall::Disconnect(3, addr, 0),
+            ]
+        );
+        assert!(client.pending.lock().unwrap().connects.is_empty());
+
+        // A per-call timeout overrides it.
+        client.set_timeouts(GattTimeouts::default().with_connect(Duration::from_secs(3600)));
+        let result = rt.block_on(client.connect_async(
+            3,
+            &addr,
+            true,
+            BtTransport::Le,
+            false,
+            1,
+            Some(Duration::from_millis(10)),
+        ));
+        assert_eq!(result, Err(GattOpError::Timeout));
+    }
+
+    #[test]
+    fn client_forwards_write_characteristic() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+
+        let status = client.write_characteristic(
+            5,
+            0x2a,
+            GattWriteType::Signed,
+            GattAuthReq::SignedNoMitm,
+            &[1, 2, 3],
+            Some(GattBearer::Enhanced),
+        );
+        assert_eq!(status, BtStatus::Success);
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![MockClientCall::WriteCharacteristic(
+                5,
+                0x2a,
+                GattWriteType::NoResponse as i32,
+                GattAuthReq::SignedNoMitm as i32,
+                vec![1, 2, 3],
+                GattBearer::Enhanced as i32
+            )]
+        );
+
+        // Invalid combinations are rejected before reaching btif.
+        let status = client.write_characteristic(
+            5,
+            0x2a,
+            GattWriteType::Default,
+            GattAuthReq::SignedMitm,
+            &[],
+            None,
+        );
+        assert_eq!(status, BtStatus::InvalidParam);
+        assert!(take_mock_client_calls().is_empty());
+        assert_eq!(client.metrics.snapshot().writes_attempted, 2);
+    }
+
+    #[test]
+    fn client_forwards_read_bearer() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+
+        assert_eq!(client.read_characteristic(5, 0x2a, 0, None), BtStatus::Success);
+        assert_eq!(
+            client.read_characteristic(5, 0x2b, 0, Some(GattBearer::Unenhanced)),
+            BtStatus::Success
+        );
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![
+                MockClientCall::ReadCharacteristic(5, 0x2a, 0, 0),
+                MockClientCall::ReadCharacteristic(5, 0x2b, 0, GattBearer::Unenhanced as i32),
+            ]
+        );
+    }
+
+    #[test]
+    fn manufacturer_data_filter_round_trip() {
+        let condition = ScanFilterCondition::ManufacturerData(
+            0x00e0,
+            0xffff,
+            vec![1, 2, 3],
+            vec![0xff, 0, 0xff],
+        );
+
+        let command: ApcfCommand = condition.clone().into();
+        assert_eq!(command.type_, 5);
+        assert_eq!(command.company, 0x00e0);
+        assert_eq!(command.company_mask, 0xffff);
+        assert_eq!(command.data, vec![1, 2, 3]);
+        assert_eq!(command.data_mask, vec![0xff, 0, 0xff]);
+
+        assert_eq!(ScanFilterCondition::from_apcf(&command), Some(condition));
+    }
+
+    #[test]
+    fn msft_adv_monitor_into_ffi() {
+        let monitor = MsftAdvMonitor {
+            rssi_high_threshold: -60,
+            rssi_low_threshold: -80,
+            rssi_low_timeout: 5,
+            rssi_sampling_period: 1,
+            patterns: vec![MsftAdvMonitorPattern {
+                ad_type: 0x16,
+                start_byte: 2,
+                content: vec![0x6f, 0xfd],
+            }],
+        };
+
+        let converted: ffi::RustMsftAdvMonitor = monitor.into();
+        assert_eq!(converted.rssi_high_threshold, -60);
+        assert_eq!(converted.rssi_low_threshold, -80);
+        assert_eq!(converted.rssi_low_timeout, 5);
+        assert_eq!(converted.rssi_sampling_period, 1);
+        assert_eq!(converted.patterns.len(), 1);
+        assert_eq!(converted.patterns[0].ad_type, 0x16);
+        assert_eq!(converted.patterns[0].start_byte, 2);
+        assert_eq!(converted.patterns[0].content, vec![0x6f, 0xfd]);
+    }
+
+    #[test]
+    fn attr_response_max_len() {
+        let value = vec![0xab; GATT_MAX_ATTR_LEN];
+        let response = GattServer::make_attr_response(0x2a, 0, &value).unwrap();
+        let attr_value = unsafe { response.attr_value };
+        assert_eq!(attr_value.handle, 0x2a);
+        assert_eq!(attr_value.len as usize, GATT_MAX_ATTR_LEN);
+        assert_eq!(&attr_value.value[..GATT_MAX_ATTR_LEN], &value[..]);
+
+        let value = vec![0xab; GATT_MAX_ATTR_LEN + 1];
+        assert!(GattServer::make_attr_response(0x2a, 0, &value).is_none());
+    }
+
+    #[test]
+    fn gatt_db_builder_requires_cccd() {
+        let uuid = Uuid { uu: [1; 16] };
+        let cccd = Uuid { uu: CCCD_UUID };
+        let mut builder = GattDbBuilder::new();
+        builder
+            .add_primary_service(uuid)
+            .unwrap()
+            .add_characteristic(
+                uuid,
+                CharacteristicProperties::READ | CharacteristicProperties::NOTIFY,
+                AttributePermissions::READ,
+            )
+            .unwrap();
+        assert_eq!(
+            builder
+                .add_characteristic(
+                    uuid,
+                    CharacteristicProperties::READ,
+                    AttributePermissions::READ
+                )
+                .err(),
+            Some(GattDbBuilderError::MissingCccd)
+        );
+
+        builder
+            .add_descriptor(cccd, AttributePermissions::READ | AttributePermissions::WRITE)
+            .unwrap();
+        let elements = builder.build().unwrap();
+        assert_eq!(elements.len(), 3);
+        assert_eq!(elements[1].properties, 0x12);
+        assert_eq!(elements[2].permissions, 0x11);
+    }
+
+    #[test]
+    fn client_signed_write_fits_mtu() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let max_len = ATT_DEFAULT_MTU as usize - 3 - GATT_AUTH_SIGN_LEN;
+
+        let value = vec![0; max_len];
+        let status = client.write_characteristic(
+            5,
+            0x2a,
+            GattWriteType::Signed,
+            GattAuthReq::SignedMitm,
+            &value,
+            None,
+        );
+        assert_eq!(status, BtStatus::Success);
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![MockClientCall::WriteCharacteristic(
+                5,
+                0x2a,
+                GattWriteType::NoResponse as i32,
+                GattAuthReq::SignedMitm as i32,
+                value,
+                0
+            )]
+        );
+
+        let value = vec![0; max_len + 1];
+        let status = client.write_characteristic(
+            5,
+            0x2a,
+            GattWriteType::Signed,
+            GattAuthReq::SignedMitm,
+            &value,
+            None,
+        );
+        assert_eq!(status, BtStatus::InvalidParam);
+
+        // A larger MTU leaves room for more.
+        let mtu_changed = GattClientCallbacks::MtuChanged(5, 64);
+        GattClient::on_callback(&client.pending, &client.metrics, mtu_changed);
+        let status = client.write_characteristic(
+            5,
+            0x2a,
+            GattWriteType::Signed,
+            GattAuthReq::SignedMitm,
+            &value,
+            None,
+        );
+        assert_eq!(status, BtStatus::Success);
+        assert_eq!(take_mock_client_calls().len(), 1);
+    }
+
+    #[test]
+    fn client_subscribe_streams_notifications() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let pending = &client.pending;
+        let metrics = &client.metrics;
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let cccd_uuid = Uuid { uu: CCCD_UUID };
+
+        // Nothing is known about a device that isn't connected.
+        assert_eq!(client.subscribe(2, &addr, 0x13).err(), Some(BtStatus::RemoteDeviceDown));
+
+        let connected = GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(pending, metrics, connected);
+        let elements = vec![
+            make_element(GattDbElementType::PrimaryService, 0x10, 1),
+            BtGattDbElement {
+                properties: CharacteristicProperties::NOTIFY.bits(),
+                ..make_element(GattDbElementType::Characteristic, 0x13, 2)
+            },
+            BtGattDbElement {
+                uuid: cccd_uuid,
+                ..make_element(GattDbElementType::Descriptor, 0x14, 0)
+            },
+            make_element(GattDbElementType::Characteristic, 0x16, 3),
+        ];
+        GattClient::on_callback(pending, metrics, GattClientCallbacks::GetGattDb(3, elements, 4));
+
+        // Characteristics without a CCCD can't be subscribed to.
+        assert_eq!(client.subscribe(2, &addr, 0x16).err(), Some(BtStatus::InvalidParam));
+
+        let mut first = client.subscribe(2, &addr, 0x13).unwrap();
+        let second = client.subscribe(2, &addr, 0x13).unwrap();
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![
+                MockClientCall::RegisterForNotification(2, addr, 0x13),
+                MockClientCall::WriteDescriptor(3, 0x14, GattAuthReq::None as i32, vec![1, 0]),
+            ]
+        );
+
+        let notification =
+            GattNotification { addr, handle: 0x13, is_notify: true, value: vec![7, 8] };
+        GattClient::on_callback(
+            pending,
+            metrics,
+            GattClientCallbacks::Notify(3, notification.clone()),
+        );
+        let other = GattNotification { handle: 0x16, ..notification.clone() };
+        GattClient::on_callback(pending, metrics, GattClientCallbacks::Notify(3, other));
+        assert_eq!(first.rx.try_recv().ok(), Some(notification));
+        assert!(first.rx.try_recv().is_err());
+
+        // The CCCD stays enabled until the last stream is dropped.
+        drop(second);
+        assert_eq!(take_mock_client_calls(), vec![]);
+        drop(first);
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![
+                MockClientCall::WriteDescriptor(3, 0x14, GattAuthReq::None as i32, vec![0, 0]),
+                MockClientCall::DeregisterForNotification(2, addr, 0x13),
+            ]
+        );
+    }
+
+    #[test]
+    fn client_cancels_background_connects() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let other = RawAddress { val: [6, 5, 4, 3, 2, 1] };
+
+        client.connect(2, &addr, false, BtTransport::Le, false, 1);
+        client.connect(2, &other, true, BtTransport::Le, false, 1);
+        client.connect_opportunistic(2, &other, BtTransport::Le);
+        assert_eq!(client.background_connects(2), vec![addr]);
+        assert!(!client.has_background_connect(3, &addr));
+
+        // The background connect outlives the connection it established.
+        let connected = GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(&client.pending, &client.metrics, connected);
+        assert!(client.has_background_connect(2, &addr));
+
+        take_mock_client_calls();
+        assert_eq!(client.cancel_connect(2, &addr), BtStatus::Success);
+        assert_eq!(take_mock_client_calls(), vec![MockClientCall::Disconnect(2, addr, 0)]);
+        assert!(!client.has_background_connect(2, &addr));
+        assert_eq!(client.address_for_conn(3), Some(addr));
+    }
+
+    #[test]
+    fn client_rediscovers_changed_database() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let pending = &client.pending;
+        let raw = RawGattClientWrapper { raw: &intf as *const btgatt_client_interface_t };
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+
+        let connected = GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(pending, &client.metrics, connected);
+
+        // Nothing happens unless enabled.
+        GattClient::on_rediscover_callback(pending, &raw, GattClientCallbacks::ServiceChanged(3));
+        assert_eq!(take_mock_client_calls(), vec![]);
+
+        client.set_auto_rediscover(true);
+        GattClient::on_rediscover_callback(pending, &raw, GattClientCallbacks::ServiceChanged(3));
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![MockClientCall::Refresh(addr), MockClientCall::SearchService(3)]
+        );
+
+        let searched = GattClientCallbacks::SearchComplete(3, GattStatus::Success as i32);
+        GattClient::on_rediscover_callback(pending, &raw, searched);
+        assert_eq!(take_mock_client_calls(), vec![MockClientCall::GetGattDb(3)]);
+
+        let elements = vec![make_element(GattDbElementType::PrimaryService, 0x10, 1)];
+        let db = GattClientCallbacks::GetGattDb(3, elements, 1);
+        match GattClient::on_rediscover_callback(pending, &raw, db) {
+            GattClientCallbacks::DatabaseRediscovered(3, elements) => assert_eq!(elements.len(), 1),
+            cb => panic!("Unexpected callback {:?}", cb),
+        }
+
+        // Later reads the client asked for itself are dispatched as usual.
+        let db = GattClientCallbacks::GetGattDb(3, vec![], 0);
+        assert!(matches!(
+            GattClient::on_rediscover_callback(pending, &raw, db),
+            GattClientCallbacks::GetGattDb(3, _, 0)
+        ));
+    }
+
+    #[test]
+    fn server_tracks_service_handles() {
+        let pending = Mutex::new(PendingServerRequests::default());
+        let metrics = GattMetrics::default();
+        let raw = RawGattServerWrapper { raw: std::ptr::null() };
+        let service = |handle| {
+            vec![BtGattDbElement {
+                attribute_handle: handle,
+                ..make_element(GattDbElementType::PrimaryService, handle, 1)
+            }]
+        };
+        let handles = |pending: &Mutex<PendingServerRequests>| {
+            pending.lock().unwrap().service_handles.get(&4).cloned().unwrap_or_default()
+        };
+
+        let success = GattStatus::Success as i32;
+        let error = GattStatus::Error as i32;
+        for (status, handle) in [(success, 0x10), (success, 0x20), (error, 0x30)].iter() {
+            let added = GattServerCallbacks::ServiceAdded(*status, 4, service(*handle), 1);
+            GattServer::on_callback(&pending, &metrics, &raw, &added);
+        }
+        assert_eq!(handles(&pending), [0x10, 0x20].iter().copied().collect());
+
+        // A failed deletion leaves the service in place.
+        let failed = GattServerCallbacks::ServiceDeleted(error, 4, 0x10);
+        GattServer::on_callback(&pending, &metrics, &raw, &failed);
+        let deleted = GattServerCallbacks::ServiceDeleted(success, 4, 0x20);
+        GattServer::on_callback(&pending, &metrics, &raw, &deleted);
+        assert_eq!(handles(&pending), [0x10].iter().copied().collect());
+
+        let deleted = GattServerCallbacks::ServiceDeleted(success, 4, 0x10);
+        GattServer::on_callback(&pending, &metrics, &raw, &deleted);
+        assert!(pending.lock().unwrap().service_handles.is_empty());
+    }
+
+    #[test]
+    fn server_resolves_included_services() {
+        let pending = Mutex::new(PendingServerRequests::default());
+        let metrics = GattMetrics::default();
+        let raw = RawGattServerWrapper { raw: std::ptr::null() };
+        let battery = Uuid { uu: [1; 16] };
+        let device = Uuid { uu: [2; 16] };
+        // Assigns handles from |first| on, as the stack does when adding them.
+        let assign = |mut elements: Vec<BtGattDbElement>, first: u16| {
+            for (i, element) in elements.iter_mut().enumerate() {
+                element.attribute_handle = first + i as u16;
+            }
+            elements
+        };
+
+        let mut builder = GattDbBuilder::new();
+        builder
+            .add_primary_service(battery)
+            .unwrap()
+            .add_characteristic(battery, CharacteristicProperties::READ, AttributePermissions::READ)
+            .unwrap();
+        let added = assign(builder.build().unwrap(), 0x10);
+        let cb = GattServerCallbacks::ServiceAdded(0, 4, added, 2);
+        GattServer::on_callback(&pending, &metrics, &raw, &cb);
+
+        let mut builder = GattDbBuilder::new();
+        builder.add_primary_service(device).unwrap();
+        assert_eq!(
+            builder.add_included_service(0).err(),
+            Some(GattDbBuilderError::InvalidIncludedHandle)
+        );
+        builder
+            .add_included_service(0x10)
+            .unwrap()
+            .add_characteristic(device, CharacteristicProperties::READ, AttributePermissions::READ)
+            .unwrap();
+        let elements = builder.build().unwrap();
+        assert_eq!((elements[1].attribute_handle, elements[1].start_handle), (0x10, 0x10));
+
+        let (tx, mut rx) = oneshot::channel();
+        pending.lock().unwrap().services.insert((4, device.uu), tx);
+        let cb = GattServerCallbacks::ServiceAdded(0, 4, assign(elements, 0x20), 3);
+        GattServer::on_callback(&pending, &metrics, &raw, &cb);
+
+        let db = GattDatabase::from_elements(rx.try_recv().unwrap().unwrap());
+        assert_eq!(
+            db.services[0].included_services,
+            vec![GattIncludedService { uuid: battery, handle: 0x21, start_handle: 0x10 }]
+        );
+        assert_eq!(db.services[0].characteristics[0].value_handle, 0x22);
+    }
+
+    #[test]
+    fn server_tracks_connected_devices() {
+        let server = GattServer {
+            internal: RawGattServerWrapper { raw: std::ptr::null() },
+            internal_cxx: cxx::UniquePtr::null(),
+            pending: Arc::new(Mutex::new(PendingServerRequests::default())),
+            metrics: Arc::new(GattMetrics::default()),
+            timeouts: GattServerTimeouts::default(),
+        };
+        let raw = RawGattServerWrapper { raw: std::ptr::null() };
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let other = RawAddress { val: [6, 5, 4, 3, 2, 1] };
+        let callback = |cb| GattServer::on_callback(&server.pending, &server.metrics, &raw, &cb);
+
+        callback(GattServerCallbacks::Connection(0x104, 4, 1, addr));
+        callback(GattServerCallbacks::Connection(0x204, 4, 1, other));
+        callback(GattServerCallbacks::Connection(0x305, 5, 1, addr));
+        assert_eq!(server.connected_devices(4), vec![(0x104, addr), (0x204, other)]);
+        assert_eq!(server.connected_devices(5), vec![(0x305, addr)]);
+
+        callback(GattServerCallbacks::Connection(0x104, 4, 0, addr));
+        assert_eq!(server.connected_devices(4), vec![(0x204, other)]);
+
+        callback(GattServerCallbacks::Connection(0x204, 4, 0, other));
+        assert!(server.connected_devices(4).is_empty());
+        assert_eq!(server.disconnect_all(4), BtStatus::Success);
+    }
+
+    #[test]
+    fn server_tracks_mtu_per_connection() {
+        let server = GattServer {
+            internal: RawGattServerWrapper { raw: std::ptr::null() },
+            internal_cxx: cxx::UniquePtr::null(),
+            pending: Arc::new(Mutex::new(PendingServerRequests::default())),
+            metrics: Arc::new(GattMetrics::default()),
+            timeouts: GattServerTimeouts::default(),
+        };
+        let raw = RawGattServerWrapper { raw: std::ptr::null() };
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let callback = |cb| GattServer::on_callback(&server.pending, &server.metrics, &raw, &cb);
+
+        assert_eq!(server.mtu_for_conn(0x104), None);
+        callback(GattServerCallbacks::Connection(0x104, 4, 1, addr));
+        assert_eq!(server.mtu_for_conn(0x104), Some(ATT_DEFAULT_MTU as u16));
+        callback(GattServerCallbacks::MtuChanged(0x104, 247));
+        assert_eq!(server.mtu_for_conn(0x104), Some(247));
+
+        callback(GattServerCallbacks::Connection(0x104, 4, 0, addr));
+        assert_eq!(server.mtu_for_conn(0x104), None);
+        assert!(server.pending.lock().unwrap().mtus.is_empty());
+    }
+
+    unsafe extern "C" fn mock_send_response(
+        _conn_id: i32,
+        _trans_id: i32,
+        _status: i32,
+        _response: *const BtGattResponse,
+    ) -> bindings::bt_status_t {
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_add_service(
+        _server_if: i32,
+        _service: *const BtGattDbElement,
+        _service_count: usize,
+    ) -> bindings::bt_status_t {
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    unsafe extern "C" fn mock_send_indication(
+        _server_if: i32,
+        _attribute_handle: i32,
+        _conn_id: i32,
+        _confirm: i32,
+        _value: *const u8,
+        _length: usize,
+    ) -> bindings::bt_status_t {
+        BtStatus::Success as bindings::bt_status_t
+    }
+
+    #[test]
+    fn server_async_operations_time_out() {
+        let intf = btgatt_server_interface_t {
+            add_service: Some(mock_add_service),
+            send_indication: Some(mock_send_indication),
+            ..Default::default()
+        };
+        let mut server = GattServer {
+            internal: RawGattServerWrapper { raw: &intf as *const btgatt_server_interface_t },
+            internal_cxx: cxx::UniquePtr::null(),
+            pending: Arc::new(Mutex::new(PendingServerRequests::default())),
+            metrics: Arc::new(GattMetrics::default()),
+            timeouts: GattServerTimeouts::default(),
+        };
+        server.set_timeouts(
+            GattServerTimeouts::default()
+                .with_service(Duration::from_millis(10))
+                .with_indication(Duration::from_millis(10)),
+        );
+        let mut builder = GattDbBuilder::new();
+        builder.add_primary_service(Uuid::from_u16(0x180f)).unwrap();
+        let service = builder.build().unwrap();
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+
+        let result = rt.block_on(server.add_service_async(4, &service, None));
+        assert!(matches!(result, Err(GattOpError::Timeout)));
+
+        let result = rt.block_on(server.send_indication_confirmed(4, 0x2a, 3, &[1], None));
+        assert!(matches!(result, Err(GattOpError::Timeout)));
+        // The indication stays queued so that a late confirmation isn't
+        // matched to a newer one.
+        assert_eq!(server.pending.lock().unwrap().indications[&3].len(), 1);
+    }
+
+    #[test]
+    fn server_congestion_gates_only_opt_in_sends() {
+        let intf = btgatt_server_interface_t {
+            send_indication: Some(mock_send_indication),
+            ..Default::default()
+        };
+        let server = GattServer {
+            internal: RawGattServerWrapper { raw: &intf as *const btgatt_server_interface_t },
+            internal_cxx: cxx::UniquePtr::null(),
+            pending: Arc::new(Mutex::new(PendingServerRequests::default())),
+            metrics: Arc::new(GattMetrics::default()),
+            timeouts: GattServerTimeouts::default(),
+        };
+        let raw = RawGattServerWrapper { raw: &intf as *const btgatt_server_interface_t };
+        let congestion = GattServerCallbacks::Congestion(3, true);
+        GattServer::on_callback(&server.pending, &server.metrics, &raw, &congestion);
+        assert!(server.is_congested(3));
+        assert!(!server.is_congested(5));
+
+        assert_eq!(server.send_indication(4, 0x2a, 3, 0, &[1]), BtStatus::Success);
+        assert_eq!(server.send_indication_if_not_congested(4, 0x2a, 3, 0, &[1]), BtStatus::Busy);
+        assert_eq!(server.send_indication_if_not_congested(4, 0x2a, 5, 0, &[1]), BtStatus::Success);
+        assert_eq!(
+            server.send_notification_to_all(4, 0x2a, &[1], &[3, 5]),
+            vec![(3, BtStatus::Busy), (5, BtStatus::Success)]
+        );
+    }
+
+    #[test]
+    fn server_validates_read_responses() {
+        let intf = btgatt_server_interface_t {
+            send_response: Some(mock_send_response),
+            ..Default::default()
+        };
+        let server = GattServer {
+            internal: RawGattServerWrapper { raw: &intf as *const btgatt_server_interface_t },
+            internal_cxx: cxx::UniquePtr::null(),
+            pending: Arc::new(Mutex::new(PendingServerRequests::default())),
+            metrics: Arc::new(GattMetrics::default()),
+            timeouts: GattServerTimeouts::default(),
+        };
+        let raw = RawGattServerWrapper { raw: &intf as *const btgatt_server_interface_t };
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let read = |trans_id, offset| {
+            let cb = GattServerCallbacks::RequestReadCharacteristic(
+                3,
+                trans_id,
+                addr,
+                0x2a,
+                offset,
+                offset != 0,
+            );
+            GattServer::on_callback(&server.pending, &server.metrics, &raw, &cb);
+        };
+        let success = GattStatus::Success as i32;
+
+        read(1, 4);
+        // Offset other than the one read at.
+        let response = GattServer::make_attr_response(0x2a, 0, &[1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(server.send_response(3, 1, success, &response), BtStatus::InvalidParam);
+        // Errors go through regardless.
+        let response = GattServer::make_attr_response(0x2a, 4, &[]).unwrap();
+        let invalid_offset = GattStatus::InvalidOffset as i32;
+        assert_eq!(server.send_response(3, 1, invalid_offset, &response), BtStatus::Success);
+
+        // A zero-length value is fine at any offset.
+        read(2, 0);
+        let response = GattServer::make_attr_response(0x2a, 0, &[]).unwrap();
+        assert_eq!(server.send_response(3, 2, success, &response), BtStatus::Success);
+        read(3, 1);
+        let response = GattServer::make_attr_response(0x2a, 1, &[]).unwrap();
+        assert_eq!(server.send_response(3, 3, success, &response), BtStatus::Success);
+
+        // A blob read answered with the remaining bytes of the value, which are
+        // fewer than the offset.
+        read(5, 22);
+        let response = GattServer::make_attr_response(0x2a, 22, &[1, 2]).unwrap();
+        assert_eq!(server.send_response(3, 5, success, &response), BtStatus::Success);
+
+        // Responses to anything other than a read aren't checked.
+        assert_eq!(server.send_response(3, 4, success, &response), BtStatus::Success);
+    }
+
+    #[test]
+    fn client_tracks_connected_addresses() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let pending = &client.pending;
+        let metrics = &client.metrics;
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let other = RawAddress { val: [6, 5, 4, 3, 2, 1] };
+
+        let failed = GattClientCallbacks::Connect(1, GattStatus::Error as i32, 2, addr);
+        GattClient::on_callback(pending, metrics, failed);
+        assert!(!pending.lock().unwrap().is_connected(&addr));
+        assert_eq!(client.address_for_conn(1), None);
+
+        let connected = GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(pending, metrics, connected);
+        assert!(pending.lock().unwrap().is_connected(&addr));
+        assert!(!pending.lock().unwrap().is_connected(&other));
+        assert_eq!(client.address_for_conn(3), Some(addr));
+
+        let disconnected = GattClientCallbacks::Disconnect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(pending, metrics, disconnected);
+        assert!(!pending.lock().unwrap().is_connected(&addr));
+        assert_eq!(client.address_for_conn(3), None);
+    }
+
+    #[test]
+    fn client_refresh_rejects_disconnected_address() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+
+        assert_eq!(client.refresh(2, &addr), BtStatus::RemoteDeviceDown);
+        assert_eq!(
+            rt.block_on(client.refresh_async(2, 3, None)),
+            Err(GattOpError::Status(GattStatus::Error))
+        );
+        assert!(take_mock_client_calls().is_empty());
+
+        let connected = GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(&client.pending, &client.metrics, connected);
+        assert_eq!(client.refresh(2, &addr), BtStatus::Success);
+        assert_eq!(take_mock_client_calls(), vec![MockClientCall::Refresh(addr)]);
+
+        let disconnected = GattClientCallbacks::Disconnect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(&client.pending, &client.metrics, disconnected);
+        assert_eq!(client.refresh(2, &addr), BtStatus::RemoteDeviceDown);
+        assert!(take_mock_client_calls().is_empty());
+    }
+
+    #[test]
+    fn client_refresh_async_resolves_after_search() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+
+        let connected = GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(&client.pending, &client.metrics, connected);
+
+        let (result, _) = rt.block_on(async {
+            tokio::join!(client.refresh_async(2, 3, None), async {
+                tokio::task::yield_now().await;
+                // The search is issued after the refresh, so the stack queues
+                // it behind the rediscovery.
+                assert_eq!(
+                    take_mock_client_calls(),
+                    vec![MockClientCall::Refresh(addr), MockClientCall::SearchService(3)]
+                );
+                let searched = GattClientCallbacks::SearchComplete(3, GattStatus::Success as i32);
+                GattClient::on_callback(&client.pending, &client.metrics, searched);
+            })
+        });
+        assert_eq!(result, Ok(()));
+
+        // A failed rediscovery fails the refresh.
+        let (result, _) = rt.block_on(async {
+            tokio::join!(client.refresh_async(2, 3, None), async {
+                tokio::task::yield_now().await;
+                let searched = GattClientCallbacks::SearchComplete(3, GattStatus::Error as i32);
+                GattClient::on_callback(&client.pending, &client.metrics, searched);
+            })
+        });
+        assert_eq!(result, Err(GattOpError::Status(GattStatus::Error)));
+        take_mock_client_calls();
+    }
+
+    #[test]
+    fn client_configure_mtu_async_resolves_negotiated_mtu() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+
+        // The remote supports less than requested.
+        let (result, _) = rt.block_on(async {
+            tokio::join!(client.configure_mtu_async(3, 517, None), async {
+                tokio::task::yield_now().await;
+                let configured = GattClientCallbacks::ConfigureMtu(3, 0, 247);
+                GattClient::on_callback(&client.pending, &client.metrics, configured);
+            })
+        });
+        assert_eq!(result, Ok(247));
+        assert_eq!(take_mock_client_calls(), vec![MockClientCall::ConfigureMtu(3, 517)]);
+
+        // Nothing is sent once an MTU has been negotiated.
+        assert_eq!(rt.block_on(client.configure_mtu_async(3, 100, None)), Ok(247));
+        let changed = GattClientCallbacks::MtuChanged(4, 185);
+        GattClient::on_callback(&client.pending, &client.metrics, changed);
+        assert_eq!(rt.block_on(client.configure_mtu_async(4, 517, None)), Ok(185));
+        assert!(take_mock_client_calls().is_empty());
+    }
+
+    #[test]
+    fn client_conn_parameter_update_async_resolves_actual_parameters() {
+        let intf = mock_client_interface();
+        let mut client = mock_client(&intf);
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let update = |params, status| {
+            rt.block_on(async {
+                tokio::join!(
+                    client.conn_parameter_update_async(3, 6, 12, 0, 500, 0, 0, None),
+                    async {
+                        tokio::task::yield_now().await;
+                        let cb = GattClientCallbacks::ConnUpdated(3, params, status);
+                        GattClient::on_callback(&client.pending, &client.metrics, cb);
+                    }
+                )
+                .0
+            })
+        };
+
+        // Nothing to update before connecting.
+        let not_connected =
+            rt.block_on(client.conn_parameter_update_async(3, 6, 12, 0, 500, 0, 0, None));
+        assert_eq!(not_connected, Err(GattOpError::Status(GattStatus::Error)));
+        assert!(take_mock_client_calls().is_empty());
+
+        let connected = GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(&client.pending, &client.metrics, connected);
+
+        // The controller settled on the upper end of the requested range.
+        let negotiated = ConnectionParameters::from_raw(12, 0, 500);
+        assert_eq!(update(negotiated, GattStatus::Success), Ok(negotiated));
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![MockClientCall::ConnParameterUpdate(addr, 6, 12, 0, 500)]
+        );
+
+        // The remote rejected it and the previous parameters remain.
+        let unchanged = ConnectionParameters::from_raw(36, 0, 500);
+        assert_eq!(update(unchanged, GattStatus::Error), Ok(unchanged));
+        let none = ConnectionParameters::from_raw(0, 0, 0);
+        assert_eq!(update(none, GattStatus::Error), Err(GattOpError::Status(GattStatus::Error)));
+        assert!(client.pending.lock().unwrap().conn_updates.is_empty());
+
+        // Updates wait for |GattTimeouts::conn_update| rather than |connect|.
+        client.set_timeouts(GattTimeouts::default().with_conn_update(Duration::from_millis(10)));
+        let result = rt.block_on(client.conn_parameter_update_async(3, 6, 12, 0, 500, 0, 0, None));
+        assert_eq!(result, Err(GattOpError::Timeout));
     }
-    pub fn set_periodic_advertising_parameters(
-        &mut self,
-        adv_id: u8,
-        params: PeriodicAdvertisingParameters,
-    ) {
-        mutcxxcall!(self, SetPeriodicAdvertisingParameters, adv_id, params);
+
+    #[test]
+    fn client_read_all_by_uuid_reads_each_match() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let temperature = Uuid::from_u16(0x2a6e);
+        let characteristic = |uuid, value_handle| GattCharacteristic {
+            uuid: Uuid::from_u16(uuid),
+            properties: CharacteristicProperties::READ,
+            value_handle,
+            descriptors: vec![],
+        };
+        let service = |handle, characteristics| GattService {
+            uuid: Uuid::from_u16(0x181a),
+            is_primary: true,
+            handle,
+            end_handle: handle + 0xf,
+            included_services: vec![],
+            characteristics,
+        };
+        let db = GattDatabase {
+            services: vec![
+                service(0x20, vec![characteristic(0x2a6e, 0x22)]),
+                service(0x10, vec![characteristic(0x2a6f, 0x12), characteristic(0x2a6e, 0x14)]),
+            ],
+        };
+
+        let read = || client.read_all_by_uuid(3, &temperature, 0, None);
+        assert_eq!(rt.block_on(read()), Err(GattStatus::Error));
+        client.pending.lock().unwrap().databases.insert(3, db);
+        let missing = client.read_all_by_uuid(3, &Uuid::from_u16(0x2a19), 0, None);
+        assert_eq!(rt.block_on(missing), Err(GattStatus::NotFound));
+
+        let complete = |handle, status: GattStatus, value: Vec<u8>| {
+            let result = GattReadResult { handle, status, value };
+            let cb = GattClientCallbacks::ReadCharacteristic(3, status as i32, result);
+            GattClient::on_callback(&client.pending, &client.metrics, cb);
+        };
+        let (result, _) = rt.block_on(async {
+            tokio::join!(read(), async {
+                tokio::task::yield_now().await;
+                complete(0x14, GattStatus::Success, vec![0x10, 0x09]);
+                complete(0x22, GattStatus::InsufAuthentication, vec![]);
+            })
+        });
+        assert_eq!(
+            result,
+            Ok(UuidReadResults {
+                values: vec![(0x14, vec![0x10, 0x09])],
+                errors: vec![(0x22, GattOpError::Status(GattStatus::InsufAuthentication))],
+            })
+        );
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![
+                MockClientCall::ReadCharacteristic(3, 0x14, 0, 0),
+                MockClientCall::ReadCharacteristic(3, 0x22, 0, 0),
+            ]
+        );
     }
-    pub fn set_periodic_advertising_data(&mut self, adv_id: u8, data: Vec<u8>) {
-        mutcxxcall!(self, SetPeriodicAdvertisingData, adv_id, data);
+
+    #[test]
+    fn preferred_connection_parameters_parse() {
+        let value = [0x18, 0x00, 0x28, 0x00, 0x01, 0x00, 0xf4, 0x01];
+        assert_eq!(
+            PreferredConnectionParameters::parse(&value),
+            Some(PreferredConnectionParameters {
+                min_interval_ms: Some(30.0),
+                max_interval_ms: Some(50.0),
+                latency: 1,
+                supervision_timeout_ms: Some(5000),
+            })
+        );
+
+        let no_preference = [0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0xff, 0xff];
+        let parsed = PreferredConnectionParameters::parse(&no_preference).unwrap();
+        assert_eq!(parsed.min_interval_ms, None);
+        assert_eq!(parsed.max_interval_ms, None);
+        assert_eq!(parsed.supervision_timeout_ms, None);
+        assert!(PreferredConnectionParameters::parse(&value[..7]).is_none());
     }
-    pub fn set_periodic_advertising_enable(&mut self, adv_id: u8, enable: bool) {
-        mutcxxcall!(self, SetPeriodicAdvertisingEnable, adv_id, enable);
+
+    #[test]
+    fn client_reads_peripheral_preferred_connection_parameters() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let gap = |characteristics| GattDatabase {
+            services: vec![GattService {
+                uuid: Uuid::from_u16(GAP_SERVICE_UUID),
+                is_primary: true,
+                handle: 0x01,
+                end_handle: 0x07,
+                included_services: vec![],
+                characteristics,
+            }],
+        };
+        let read = || client.read_peripheral_preferred_connection_parameters(3, None);
+
+        assert_eq!(rt.block_on(read()), Err(GattOpError::Status(GattStatus::Error)));
+        client.pending.lock().unwrap().databases.insert(3, gap(vec![]));
+        assert_eq!(rt.block_on(read()), Err(GattOpError::Status(GattStatus::NotFound)));
+
+        let ppcp = GattCharacteristic {
+            uuid: Uuid::from_u16(PPCP_UUID),
+            properties: CharacteristicProperties::READ,
+            value_handle: 0x07,
+            descriptors: vec![],
+        };
+        client.pending.lock().unwrap().databases.insert(3, gap(vec![ppcp]));
+        let (result, _) = rt.block_on(async {
+            tokio::join!(read(), async {
+                tokio::task::yield_now().await;
+                let value = vec![0x18, 0x00, 0x28, 0x00, 0x00, 0x00, 0xf4, 0x01];
+                let result = GattReadResult { handle: 0x07, status: GattStatus::Success, value };
+                let cb = GattClientCallbacks::ReadCharacteristic(3, 0, result);
+                GattClient::on_callback(&client.pending, &client.metrics, cb);
+            })
+        });
+        assert_eq!(result.map(|params| params.max_interval_ms), Ok(Some(50.0)));
+        assert_eq!(
+            take_mock_client_calls(),
+            vec![MockClientCall::ReadCharacteristic(3, 0x07, 0, 0)]
+        );
     }
-}
 
-pub struct Gatt {
-    internal: RawGattWrapper,
-    is_init: bool,
+    #[test]
+    fn client_cancel_operation_resolves_future() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let complete = |value: Vec<u8>| {
+            let result = GattReadResult { handle: 0x2a, status: GattStatus::Success, value };
+            let cb = GattClientCallbacks::ReadCharacteristic(3, 0, result);
+            GattClient::on_callback(&client.pending, &client.metrics, cb)
+        };
 
-    pub client: GattClient,
-    pub server: GattServer,
-    pub scanner: BleScanner,
-    pub advertiser: BleAdvertiser,
+        let first = client.start_read_characteristic(3, 0x2a, 0, None, None).unwrap();
+        let second = client.start_read_characteristic(3, 0x2a, 0, None, None).unwrap();
+        let with_id = client.read_characteristic_with_id(3, 0x2a, 0, None).unwrap();
+        assert!(!client.cancel_operation(4, first.id()));
+        assert!(client.cancel_operation(3, first.id()));
+        assert!(!client.cancel_operation(3, first.id()));
+        assert!(client.cancel_operation(3, with_id));
+        assert_eq!(rt.block_on(first.wait()), Err(GattOpError::Cancelled));
+
+        // The cancelled read still takes the first completion.
+        assert!(matches!(complete(vec![1]), GattClientCallbacks::ReadCharacteristic(..)));
+        complete(vec![2]);
+        assert_eq!(rt.block_on(second.wait()), Ok(vec![2]));
+        assert!(matches!(complete(vec![3]), GattClientCallbacks::ReadCharacteristic(..)));
+
+        let pending = client.pending.lock().unwrap();
+        assert!(pending.reads.is_empty());
+        assert!(pending.op_waiters.is_empty());
+        assert!(pending.cancelled.is_empty());
+    }
 
-    // Keep callback object in memory (underlying code doesn't make copy)
-    callbacks: Option<Box<bindings::btgatt_callbacks_t>>,
-    gatt_client_callbacks: Option<Box<bindings::btgatt_client_callbacks_t>>,
-    gatt_server_callbacks: Option<Box<bindings::btgatt_server_callbacks_t>>,
-    gatt_scanner_callbacks: Option<Box<bindings::btgatt_scanner_callbacks_t>>,
-}
+    #[test]
+    fn client_queues_no_response_writes_while_congested() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let callback = |cb| {
+            let cb = GattClient::on_callback(&client.pending, &client.metrics, cb);
+            GattClient::on_flow_control_callback(
+                &client.pending,
+                &client.metrics,
+                &client.internal,
+                &cb,
+            );
+        };
+        let sent = |value: u8| MockClientCall::WriteCharacteristic(3, 0x2a, 1, 0, vec![value], 0);
+        callback(GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr));
 
-impl Gatt {
-    pub fn new(intf: &BluetoothInterface) -> Option<Gatt> {
-        let r = intf.get_profile_interface(SupportedProfiles::Gatt);
+        let write = |value: u8| {
+            client.write_characteristic_no_response(3, 0x2a, &[value], GattAuthReq::None)
+        };
+        assert_eq!(write(1), BtStatus::Success);
+        assert_eq!(take_mock_client_calls(), vec![sent(1)]);
+
+        callback(GattClientCallbacks::Congestion(3, true));
+        assert_eq!(write(2), BtStatus::Success);
+        assert_eq!(write(3), BtStatus::Success);
+        assert!(take_mock_client_calls().is_empty());
+        assert_eq!(client.pending_no_response_writes(3), 3);
+
+        callback(GattClientCallbacks::Congestion(3, false));
+        assert_eq!(take_mock_client_calls(), vec![sent(2), sent(3)]);
+        callback(GattClientCallbacks::WriteCharacteristic(3, 0, 0x2a, vec![1]));
+        assert_eq!(client.pending_no_response_writes(3), 2);
+
+        // Too long for a single packet at the default MTU.
+        let value = [0u8; 21];
+        let status = client.write_characteristic_no_response(3, 0x2a, &value, GattAuthReq::None);
+        assert_eq!(status, BtStatus::InvalidParam);
+
+        callback(GattClientCallbacks::Congestion(3, true));
+        assert_eq!(write(4), BtStatus::Success);
+        callback(GattClientCallbacks::Disconnect(3, GattStatus::Success as i32, 2, addr));
+        assert_eq!(client.pending_no_response_writes(3), 0);
+        assert!(take_mock_client_calls().is_empty());
+    }
 
-        if r == std::ptr::null() {
-            return None;
+    #[test]
+    fn client_read_all_bounds_reads_in_flight() {
+        use tokio_stream::StreamExt;
+
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let (pending, metrics) = (&client.pending, &client.metrics);
+        let read = |handle: u16| MockClientCall::ReadCharacteristic(3, handle, 0, 0);
+        let complete = |handle: u16| {
+            let result = GattReadResult { handle, status: GattStatus::Success, value: vec![1] };
+            GattClient::on_callback(
+                pending,
+                metrics,
+                GattClientCallbacks::ReadCharacteristic(3, 0, result),
+            );
+        };
+        GattClient::on_callback(
+            pending,
+            metrics,
+            GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr),
+        );
+
+        let mut stream = client.read_all(3, &[1, 2, 3, 4, 5], GattAuthReq::None);
+        assert_eq!(take_mock_client_calls(), vec![read(1), read(2), read(3)]);
+
+        // Results come back in completion order, each making room for one more.
+        complete(2);
+        assert_eq!(rt.block_on(stream.next()).map(|r| r.handle), Some(2));
+        assert_eq!(take_mock_client_calls(), vec![read(4)]);
+
+        // Nothing new is issued while congested.
+        GattClient::on_callback(pending, metrics, GattClientCallbacks::Congestion(3, true));
+        complete(1);
+        assert_eq!(rt.block_on(stream.next()).map(|r| r.handle), Some(1));
+        assert!(take_mock_client_calls().is_empty());
+
+        GattClient::on_callback(pending, metrics, GattClientCallbacks::Congestion(3, false));
+        complete(3);
+        assert_eq!(rt.block_on(stream.next()).map(|r| r.handle), Some(3));
+        assert_eq!(take_mock_client_calls(), vec![read(5)]);
+
+        // The rest of the batch ends with the connection.
+        GattClient::on_callback(
+            pending,
+            metrics,
+            GattClientCallbacks::Disconnect(3, GattStatus::Success as i32, 2, addr),
+        );
+        assert_eq!(rt.block_on(stream.next()), None);
+    }
+
+    #[test]
+    fn client_tracks_connection_state() {
+        let intf = mock_client_interface();
+        let client = mock_client(&intf);
+        let pending = &client.pending;
+        let metrics = &client.metrics;
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+
+        // Congestion on an unknown connection is ignored.
+        GattClient::on_callback(pending, metrics, GattClientCallbacks::Congestion(3, true));
+        assert_eq!(client.connection_state(3), ConnState::Disconnected);
+
+        let connected = GattClientCallbacks::Connect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(pending, metrics, connected);
+        assert_eq!(client.connection_state(3), ConnState::Connected);
+
+        GattClient::on_callback(pending, metrics, GattClientCallbacks::Congestion(3, true));
+        assert_eq!(client.connection_state(3), ConnState::Congested);
+        GattClient::on_callback(pending, metrics, GattClientCallbacks::Congestion(3, false));
+        assert_eq!(client.connection_state(3), ConnState::Connected);
+
+        GattClient::on_callback(pending, metrics, GattClientCallbacks::Congestion(3, true));
+        let disconnected = GattClientCallbacks::Disconnect(3, GattStatus::Success as i32, 2, addr);
+        GattClient::on_callback(pending, metrics, disconnected);
+        assert_eq!(client.connection_state(3), ConnState::Disconnected);
+    }
+
+    #[test]
+    fn parse_batch_scan_reports_records() {
+        let truncated = [
+            6, 5, 4, 3, 2, 1, 0x01, 0xf6, 0xc4, 0x10, 0x00, // First record
+            1, 2, 3, 4, 5, 6, 0x00, 0x00, 0xb0, 0x20, 0x00, // Second record
+        ];
+        let records = parse_batch_scan_reports(BATCH_SCAN_REPORT_TRUNCATED, 2, &truncated);
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0],
+            BatchScanRecord {
+                addr: RawAddress { val: [1, 2, 3, 4, 5, 6] },
+                addr_type: 1,
+                tx_power: -10,
+                rssi: -60,
+                timestamp: 0x10,
+                adv_data: vec![],
+                scan_response: vec![],
+            }
+        );
+        assert_eq!(records[1].rssi, -80);
+
+        let full = [
+            6, 5, 4, 3, 2, 1, 0x01, 0xf6, 0xc4, 0x10, 0x00, // Fixed fields
+            0x03, 0x02, 0x01, 0x06, // Advertising data
+            0x00, // No scan response
+        ];
+        let records = parse_batch_scan_reports(BATCH_SCAN_REPORT_FULL, 1, &full);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].adv_data, vec![0x02, 0x01, 0x06]);
+        assert!(records[0].scan_response.is_empty());
+    }
+
+    #[test]
+    fn parse_batch_scan_reports_malformed() {
+        let record = [6, 5, 4, 3, 2, 1, 0x01, 0xf6, 0xc4, 0x10, 0x00];
+
+        // More records claimed than present, and a partial record.
+        assert_eq!(parse_batch_scan_reports(BATCH_SCAN_REPORT_TRUNCATED, 5, &record).len(), 1);
+        assert!(parse_batch_scan_reports(BATCH_SCAN_REPORT_TRUNCATED, 1, &record[..10]).is_empty());
+        assert!(parse_batch_scan_reports(BATCH_SCAN_REPORT_TRUNCATED, -1, &record).is_empty());
+        assert!(parse_batch_scan_reports(3, 1, &record).is_empty());
+
+        // Full record whose advertising data runs past the end, or with the
+        // scan response length missing.
+        let mut full = record.to_vec();
+        full.extend_from_slice(&[0x05, 0x01]);
+        assert!(parse_batch_scan_reports(BATCH_SCAN_REPORT_FULL, 1, &full).is_empty());
+        let mut full = record.to_vec();
+        full.extend_from_slice(&[0x01, 0x01]);
+        assert!(parse_batch_scan_reports(BATCH_SCAN_REPORT_FULL, 1, &full).is_empty());
+    }
+
+    #[test]
+    fn scan_result_filter() {
+        let near = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let far = RawAddress { val: [6, 5, 4, 3, 2, 1] };
+        let now = Instant::now();
+
+        let mut state = ScanResultFilterState::default();
+        assert!(state.should_report(&near, -100, now));
+        assert!(state.should_report(&near, -100, now));
+
+        state.filter = ScanResultFilter {
+            min_rssi: Some(-70),
+            dedup_window: Some(Duration::from_secs(1)),
+            address_allowlist: Some(vec![near]),
+        };
+        assert!(!state.should_report(&near, -80, now));
+        assert!(!state.should_report(&far, -50, now));
+        assert!(state.should_report(&near, -50, now));
+        assert!(!state.should_report(&near, -50, now + Duration::from_millis(500)));
+        assert!(state.should_report(&near, -50, now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn scan_results_are_coalesced() {
+        let coalescer = Mutex::new(ScanCoalescer::default());
+        let result = |last: u8, rssi: i8| ScanResult {
+            event_type: AdvertisingEventType(0x13),
+            addr_type: BleAddressType::Public,
+            address: RawAddress { val: [1, 2, 3, 4, 5, last] },
+            primary_phy: 1,
+            secondary_phy: 0,
+            advertising_sid: 0xff,
+            tx_power: 127,
+            rssi,
+            periodic_adv_interval: 0,
+            adv_data: vec![],
+        };
+        let report = |last, rssi| {
+            let cb = GattScannerCallbacks::OnScanResult(result(last, rssi));
+            BleScanner::on_coalesce_callback(&coalescer, cb)
+        };
+
+        // Off by default.
+        assert!(matches!(report(1, -70), Some(GattScannerCallbacks::OnScanResult(_))));
+
+        coalescer.lock().unwrap().interval = Some(Duration::from_millis(500));
+        assert!(report(1, -70).is_none());
+        assert!(report(2, -80).is_none());
+        assert!(report(1, -60).is_none());
+        assert!(report(2, -90).is_none());
+        assert_eq!(coalescer.lock().unwrap().take(), vec![result(1, -60), result(2, -80)]);
+        assert!(coalescer.lock().unwrap().take().is_empty());
+
+        let lost = GattScannerCallbacks::OnPeriodicSyncLost(1);
+        assert!(BleScanner::on_coalesce_callback(&coalescer, lost).is_some());
+    }
+
+    #[test]
+    fn scan_results_are_routed_to_their_scanner() {
+        let router = Mutex::new(ScanResultRouter::default());
+        let received = Arc::new(Mutex::new(vec![]));
+        let callback = |scanner_id: u8| -> ScanResultCallback {
+            let received = received.clone();
+            Arc::new(move |result: ScanResult| {
+                received.lock().unwrap().push((scanner_id, result.address.val[5]))
+            })
+        };
+        let result = |last: u8, adv_data: Vec<u8>| ScanResult {
+            event_type: AdvertisingEventType(0x13),
+            addr_type: BleAddressType::Public,
+            address: RawAddress { val: [1, 2, 3, 4, 5, last] },
+            primary_phy: 1,
+            secondary_phy: 0,
+            advertising_sid: 0xff,
+            tx_power: 127,
+            rssi: -60,
+            periodic_adv_interval: 0,
+            adv_data,
+        };
+        let route = |result| {
+            BleScanner::on_route_callback(&router, &GattScannerCallbacks::OnScanResult(result));
+            std::mem::take(&mut *received.lock().unwrap())
+        };
+
+        {
+            let mut router = router.lock().unwrap();
+            router.callbacks.insert(1, callback(1));
+            router.callbacks.insert(2, callback(2));
+            router.param_setup(1, APCF_ACTION_ADD, 0);
+            router.add_conditions(
+                0,
+                &[ScanFilterCondition::Address(RawAddress { val: [1, 2, 3, 4, 5, 6] }, 0)],
+            );
+            router.param_setup(2, APCF_ACTION_ADD, 1);
+            router.add_conditions(
+                1,
+                &[ScanFilterCondition::ManufacturerData(0x00e0, 0, vec![0xbe], vec![])],
+            );
         }
 
-        let gatt_client_intf = unsafe { ffi::GetGattClientProfile(r as *const u8) };
-        let gatt_scanner_intf = unsafe { ffi::GetBleScannerIntf(r as *const u8) };
-        let gatt_advertiser_intf = unsafe { ffi::GetBleAdvertiserIntf(r as *const u8) };
+        // Manufacturer data of company 0x00e0 starting with 0xbe 0xef.
+        let beacon = vec![5, 0xff, 0xe0, 0x00, 0xbe, 0xef];
+        assert_eq!(route(result(6, vec![])), vec![(1, 6)]);
+        assert_eq!(route(result(7, beacon.clone())), vec![(2, 7)]);
+        assert!(route(result(8, vec![5, 0xff, 0xe0, 0x00, 0xde, 0xad])).is_empty());
+
+        // Clearing its filters hands every result to the scanner.
+        router.lock().unwrap().param_setup(2, APCF_ACTION_CLEAR, 0);
+        let mut routed = route(result(6, vec![]));
+        routed.sort();
+        assert_eq!(routed, vec![(1, 6), (2, 6)]);
+
+        router.lock().unwrap().remove_scanner(1);
+        assert!(router.lock().unwrap().filters.is_empty());
+        assert_eq!(route(result(9, beacon)), vec![(2, 9)]);
+    }
 
-        Some(Gatt {
-            internal: RawGattWrapper { raw: r as *const btgatt_interface_t },
+    #[test]
+    fn periodic_reports_are_reassembled() {
+        let periodic_reports = Mutex::new(PeriodicReportAssembler::default());
+        let fragment = |sync_handle, data_status, data: &[u8]| {
+            GattScannerCallbacks::OnPeriodicSyncReport(PeriodicAdvReport {
+                sync_handle,
+                tx_power: -10,
+                rssi: -60,
+                data_status,
+                data: data.to_vec(),
+            })
+        };
+        let reassembled = |cb| match BleScanner::on_periodic_callback(&periodic_reports, cb) {
+            Some(GattScannerCallbacks::OnPeriodicSyncReport(report)) => Some(report),
+            _ => None,
+        };
+
+        // Trains on different syncs don't mix.
+        assert_eq!(reassembled(fragment(1, AdvertisingDataStatus::Incomplete, &[1, 2])), None);
+        assert_eq!(reassembled(fragment(2, AdvertisingDataStatus::Incomplete, &[9])), None);
+        let report = reassembled(fragment(1, AdvertisingDataStatus::Complete, &[3])).unwrap();
+        assert_eq!(report.data, vec![1, 2, 3]);
+        assert_eq!(report.data_status, AdvertisingDataStatus::Complete);
+
+        // A truncated train is flushed with what was received.
+        let report = reassembled(fragment(2, AdvertisingDataStatus::Truncated, &[])).unwrap();
+        assert_eq!(report.data, vec![9]);
+        assert_eq!(report.data_status, AdvertisingDataStatus::Truncated);
+
+        // Losing the sync drops a partial train.
+        assert_eq!(reassembled(fragment(1, AdvertisingDataStatus::Incomplete, &[4])), None);
+        BleScanner::on_periodic_callback(
+            &periodic_reports,
+            GattScannerCallbacks::OnPeriodicSyncLost(1),
+        );
+        let report = reassembled(fragment(1, AdvertisingDataStatus::Complete, &[5])).unwrap();
+        assert_eq!(report.data, vec![5]);
+    }
+
+    #[test]
+    fn device_type_from_i32() {
+        assert_eq!(DeviceType::try_from(0), Ok(DeviceType::Unknown));
+        assert_eq!(DeviceType::try_from(2), Ok(DeviceType::Le));
+        assert_eq!(DeviceType::try_from(3), Ok(DeviceType::Dual));
+        assert_eq!(DeviceType::try_from(4), Err(4));
+    }
+
+    #[test]
+    fn connection_parameters_from_raw() {
+        let params = ConnectionParameters::from_raw(24, 4, 72);
+        assert_eq!(params.interval_ms, 30.0);
+        assert_eq!(params.latency, 4);
+        assert_eq!(params.supervision_timeout_ms, 720);
+    }
+
+    #[test]
+    fn tracking_event_from_track_info() {
+        let info = |advertiser_state| AdvertisingTrackInfo {
+            scanner_id: 1,
+            filter_index: 2,
+            advertiser_state,
+            advertiser_info_present: 1,
+            advertiser_address: ffi::RustRawAddress { address: [1, 2, 3, 4, 5, 6] },
+            advertiser_address_type: 1,
+            tx_power: 0xf6,
+            rssi: -60,
+            timestamp: 0,
+            adv_packet_len: 3,
+            adv_packet: vec![2, 1, 6],
+            scan_response_len: 0,
+            scan_response: vec![],
+        };
+
+        let found = TrackingEvent::from(info(0x00));
+        assert!(found.found);
+        assert_eq!((found.scanner_id, found.filter_index), (1, 2));
+        assert_eq!(found.address, RawAddress { val: [1, 2, 3, 4, 5, 6] });
+        assert_eq!(found.address_type, BleAddressType::Random);
+        assert_eq!((found.tx_power, found.rssi), (-10, -60));
+        assert_eq!(found.adv_data, vec![2, 1, 6]);
+        assert!(!TrackingEvent::from(info(0x01)).found);
+    }
+
+    #[test]
+    fn callback_watchdog_reports_silence() {
+        let watchdog = CallbackWatchdog::new();
+        let start = watchdog.last();
+        let max_silence = Duration::from_secs(10);
+
+        assert!(watchdog.is_healthy(max_silence, start + Duration::from_secs(10)));
+        assert!(!watchdog.is_healthy(max_silence, start + Duration::from_secs(11)));
+
+        watchdog.stamp();
+        assert!(watchdog.last() >= start);
+        assert!(watchdog.is_healthy(max_silence, watchdog.last() + Duration::from_secs(5)));
+    }
+
+    unsafe extern "C" fn mock_failing_init(
+        _callbacks: *const bindings::btgatt_callbacks_t,
+    ) -> bindings::bt_status_t {
+        BtStatus::Fail as bindings::bt_status_t
+    }
+
+    // A |Gatt| backed by |intf| and |client_intf| rather than the stack.
+    fn mock_gatt(intf: &btgatt_interface_t, client_intf: &btgatt_client_interface_t) -> Gatt {
+        let raw = intf as *const btgatt_interface_t;
+        Gatt {
+            internal: RawGattWrapper { raw },
             is_init: false,
-            client: GattClient {
-                internal: RawGattClientWrapper {
-                    raw: unsafe {
-                        (*(r as *const btgatt_interface_t)).client
-                            as *const btgatt_client_interface_t
-                    },
-                },
-                internal_cxx: gatt_client_intf,
-            },
+            client: mock_client(client_intf),
             server: GattServer {
-                internal: RawGattServerWrapper {
-                    raw: unsafe {
-                        (*(r as *const btgatt_interface_t)).server
-                            as *const btgatt_server_interface_t
-                    },
-                },
+                internal: RawGattServerWrapper { raw: std::ptr::null() },
+                internal_cxx: cxx::UniquePtr::null(),
+                pending: Arc::new(Mutex::new(PendingServerRequests::default())),
+                metrics: Arc::new(GattMetrics::default()),
+                timeouts: GattServerTimeouts::default(),
             },
-            scanner: BleScanner::new(r as *const btgatt_interface_t, gatt_scanner_intf),
-            advertiser: BleAdvertiser::new(r as *const btgatt_interface_t, gatt_advertiser_intf),
+            scanner: BleScanner::new(raw, cxx::UniquePtr::null()),
+            advertiser: BleAdvertiser::new(cxx::UniquePtr::null()),
+            metrics: Arc::new(GattMetrics::default()),
+            events: Arc::new(Mutex::new(None)),
+            watchdog: Arc::new(CallbackWatchdog::new()),
             callbacks: None,
             gatt_client_callbacks: None,
             gatt_server_callbacks: None,
             gatt_scanner_callbacks: None,
-        })
+        }
     }
 
-    pub fn is_initialized(&self) -> bool {
-        self.is_init
+    fn initialize_mock_gatt(
+        gatt: &mut Gatt,
+        client_cbs: &Arc<Mutex<Vec<GattClientCallbacks>>>,
+    ) -> Result<(), GattInitError> {
+        let client_cbs = client_cbs.clone();
+        gatt.initialize(
+            GattClientCallbacksDispatcher {
+                dispatch: Box::new(move |cb| client_cbs.lock().unwrap().push(cb)),
+            },
+            GattServerCallbacksDispatcher { dispatch: Box::new(|_| {}) },
+            GattScannerCallbacksDispatcher { dispatch: Box::new(|_| {}) },
+            GattScannerInbandCallbacksDispatcher { dispatch: Box::new(|_| {}) },
+            GattAdvInbandCallbacksDispatcher { dispatch: Box::new(|_| {}) },
+            GattAdvCallbacksDispatcher { dispatch: Box::new(|_| {}) },
+        )
     }
 
-    pub fn initialize(
-        &mut self,
-        gatt_client_callbacks_dispatcher: GattClientCallbacksDispatcher,
-        gatt_server_callbacks_dispatcher: GattServerCallbacksDispatcher,
-        gatt_scanner_callbacks_dispatcher: GattScannerCallbacksDispatcher,
-    ) -> bool {
-        // Register dispatcher
-        if get_dispatchers()
+    #[test]
+    fn gatt_can_be_reinitialized_after_drop() {
+        // btif init fails, which leaves the dispatchers registered without
+        // needing the scanner and advertiser shims.
+        let intf = btgatt_interface_t { init: Some(mock_failing_init), ..Default::default() };
+        let client_intf = mock_client_interface();
+        let client_cbs = Arc::new(Mutex::new(Vec::new()));
+
+        let mut gatt = mock_gatt(&intf, &client_intf);
+        assert!(matches!(
+            initialize_mock_gatt(&mut gatt, &client_cbs),
+            Err(GattInitError::BtifInitFailed(_))
+        ));
+        gc_service_changed_cb(1);
+
+        let mut other = mock_gatt(&intf, &client_intf);
+        assert_eq!(
+            initialize_mock_gatt(&mut other, &client_cbs),
+            Err(GattInitError::DispatcherAlreadyRegistered("GattClientCallbacks"))
+        );
+        drop(other);
+
+        // A callback arriving once the dispatchers are gone is dropped rather
+        // than panicking across the FFI boundary.
+        drop(gatt);
+        gc_service_changed_cb(2);
+
+        let mut gatt = mock_gatt(&intf, &client_intf);
+        assert!(matches!(
+            initialize_mock_gatt(&mut gatt, &client_cbs),
+            Err(GattInitError::BtifInitFailed(_))
+        ));
+        gc_service_changed_cb(3);
+        drop(gatt);
+
+        let received: Vec<i32> = client_cbs
             .lock()
             .unwrap()
-            .set::<GattClientCb>(Arc::new(Mutex::new(gatt_client_callbacks_dispatcher)))
-        {
-            panic!("Tried to set dispatcher for GattClientCallbacks but it already existed");
-        }
+            .iter()
+            .filter_map(|cb| match cb {
+                GattClientCallbacks::ServiceChanged(conn_id) => Some(*conn_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(received, vec![1, 3]);
+    }
 
-        if get_dispatchers()
-            .lock()
-            .unwrap()
-            .set::<GattServerCb>(Arc::new(Mutex::new(gatt_server_callbacks_dispatcher)))
-        {
-            panic!("Tried to set dispatcher for GattServerCallbacks but it already existed");
-        }
+    #[test]
+    fn gatt_events_are_forwarded_until_dropped() {
+        let events: GattEventSender = Arc::new(Mutex::new(None));
+        // Nothing is buffered without a receiver.
+        send_event(&events, GattEvent::Client(GattClientCallbacks::ServiceChanged(1)));
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        *events.lock().unwrap() = Some(tx);
+        send_event(&events, GattEvent::Server(GattServerCallbacks::MtuChanged(2, 185)));
+        send_event(&events, GattEvent::Scanner(GattScannerCallbacks::OnPeriodicSyncLost(3)));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(GattEvent::Server(GattServerCallbacks::MtuChanged(2, 185)))
+        ));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(GattEvent::Scanner(GattScannerCallbacks::OnPeriodicSyncLost(3)))
+        ));
+        assert!(rx.try_recv().is_err());
+
+        drop(rx);
+        send_event(&events, GattEvent::Client(GattClientCallbacks::ServiceChanged(1)));
+        assert!(events.lock().unwrap().is_none());
+    }
 
-        if get_dispatchers()
-            .lock()
-            .unwrap()
-            .set::<GDScannerCb>(Arc::new(Mutex::new(gatt_scanner_callbacks_dispatcher)))
-        {
-            panic!("Tried to set dispatcher for GattScannerCallbacks but it already existed");
-        }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn scan_result_serializes_data_as_bytes() {
+        let result = ScanResult {
+            event_type: AdvertisingEventType(0x13),
+            addr_type: BleAddressType::Random,
+            address: RawAddress { val: [1, 2, 3, 4, 5, 6] },
+            primary_phy: 1,
+            secondary_phy: 0,
+            advertising_sid: 0xff,
+            tx_power: 127,
+            rssi: -60,
+            periodic_adv_interval: 0,
+            // Manufacturer data that isn't valid UTF-8.
+            adv_data: vec![0x05, 0xff, 0xe0, 0x00, 0xc3, 0x28],
+        };
 
-        let mut gatt_client_callbacks = Box::new(btgatt_client_callbacks_t {
-            register_client_cb: Some(gc_register_client_cb),
-            open_cb: Some(gc_open_cb),
-            close_cb: Some(gc_close_cb),
-            search_complete_cb: Some(gc_search_complete_cb),
-            register_for_notification_cb: Some(gc_register_for_notification_cb),
-            notify_cb: Some(gc_notify_cb),
-            read_characteristic_cb: Some(gc_read_characteristic_cb),
-            write_characteristic_cb: Some(gc_write_characteristic_cb),
-            read_descriptor_cb: Some(gc_read_descriptor_cb),
-            write_descriptor_cb: Some(gc_write_descriptor_cb),
-            execute_write_cb: Some(gc_execute_write_cb),
-            read_remote_rssi_cb: Some(gc_read_remote_rssi_cb),
-            configure_mtu_cb: Some(gc_configure_mtu_cb),
-            congestion_cb: Some(gc_congestion_cb),
-            get_gatt_db_cb: Some(gc_get_gatt_db_cb),
-            phy_updated_cb: Some(gc_phy_updated_cb),
-            conn_updated_cb: Some(gc_conn_updated_cb),
-            service_changed_cb: Some(gc_service_changed_cb),
-            // These callbacks are never used and will also be removed from btif.
-            // TODO(b/200073464): Remove these.
-            services_removed_cb: None,
-            services_added_cb: None,
-        });
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["adv_data"], serde_json::json!([0x05, 0xff, 0xe0, 0x00, 0xc3, 0x28]));
+        assert_eq!(json["address"]["val"], serde_json::json!([1, 2, 3, 4, 5, 6]));
+        let parsed: ScanResult = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, result);
 
-        let mut gatt_server_callbacks = Box::new(btgatt_server_callbacks_t {
-            register_server_cb: Some(gs_register_server_cb),
-            connection_cb: Some(gs_connection_cb),
-            service_added_cb: Some(gs_service_added_cb),
-            service_stopped_cb: Some(gs_service_stopped_cb),
-            service_deleted_cb: Some(gs_service_deleted_cb),
-            request_read_characteristic_cb: Some(gs_request_read_characteristic_cb),
-            request_read_descriptor_cb: Some(gs_request_read_descriptor_cb),
-            request_write_characteristic_cb: Some(gs_request_write_characteristic_cb),
-            request_write_descriptor_cb: Some(gs_request_write_descriptor_cb),
-            request_exec_write_cb: Some(gs_request_exec_write_cb),
-            response_confirmation_cb: Some(gs_response_confirmation_cb),
-            indication_sent_cb: Some(gs_indication_sent_cb),
-            congestion_cb: Some(gs_congestion_cb),
-            mtu_changed_cb: Some(gs_mtu_changed_cb),
-            phy_updated_cb: Some(gs_phy_updated_cb),
-            conn_updated_cb: Some(gs_conn_updated_cb),
-        });
+        let field = AdvDataField::ManufacturerData { company_id: 0x00e0, data: vec![0xc3, 0x28] };
+        let json = serde_json::to_string(&field).unwrap();
+        assert_eq!(serde_json::from_str::<AdvDataField>(&json).unwrap(), field);
+    }
 
-        let mut gatt_scanner_callbacks = Box::new(btgatt_scanner_callbacks_t {
-            scan_result_cb: None,
-            batchscan_reports_cb: None,
-            batchscan_threshold_cb: None,
-            track_adv_event_cb: None,
-        });
+    #[test]
+    fn uuid_round_trips_through_ffi() {
+        // A uuid with no symmetry, so any swap shows up.
+        let uuid = Uuid::from_be_bytes([
+            0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x0f, 0xed, 0xcb, 0xa9, 0x87, 0x65,
+            0x43, 0x21,
+        ]);
+
+        // Registration hands the shim a |RustUuid| and gets the same one back in
+        // |gdscan_on_scanner_registered|.
+        let registered = ffi::RustUuid::from(uuid);
+        assert_eq!(registered.uu, uuid.to_be_bytes());
+        assert_eq!(Uuid::from(registered), uuid);
+    }
 
-        let mut callbacks = Box::new(btgatt_callbacks_t {
-            size: 4 * 8,
-            client: &mut *gatt_client_callbacks,
-            server: &mut *gatt_server_callbacks,
-            scanner: &mut *gatt_scanner_callbacks,
-        });
+    #[test]
+    fn ble_address_type_from_u8() {
+        assert_eq!(BleAddressType::from(0x00), BleAddressType::Public);
+        assert_eq!(BleAddressType::from(0x03), BleAddressType::RandomIdentity);
+        assert_eq!(BleAddressType::from(0xFF), BleAddressType::Anonymous);
+        assert!(BleAddressType::from(0x02).is_identity());
+        assert!(!BleAddressType::from(0x01).is_identity());
+
+        // Undefined values are kept rather than rejected.
+        assert_eq!(BleAddressType::from(0x04), BleAddressType::Unknown(0x04));
+        assert_eq!(u8::from(BleAddressType::Unknown(0x04)), 0x04);
+        assert_eq!(u8::from(BleAddressType::Anonymous), 0xFF);
+    }
 
-        let rawcb = &mut *callbacks;
+    #[test]
+    fn estimate_distance_model() {
+        // At 1m the RSSI is the advertised power less the free-space loss.
+        assert!((estimate_distance(-41, 0) - 1.0).abs() < 0.001);
+        assert!((estimate_distance(-61, 0) - 10.0).abs() < 0.01);
+        assert!((estimate_distance_with_exponent(-61, 0, 4.0) - 3.162).abs() < 0.01);
+        assert_eq!(estimate_distance_with_exponent(-61, 0, 0.0), estimate_distance(-61, 0));
+
+        // Readings stronger than the advertised power and very weak ones
+        // are clamped.
+        assert_eq!(estimate_distance(10, 0), 0.1);
+        assert_eq!(estimate_distance(-127, 20), 100.0);
+    }
 
-        let init = ccall!(self, init, rawcb);
-        self.is_init = init == 0;
-        self.callbacks = Some(callbacks);
-        self.gatt_client_callbacks = Some(gatt_client_callbacks);
-        self.gatt_server_callbacks = Some(gatt_server_callbacks);
-        self.gatt_scanner_callbacks = Some(gatt_scanner_callbacks);
+    #[test]
+    fn advertising_event_type_bits() {
+        // ADV_IND as reported for a legacy advertisement.
+        let adv_ind = AdvertisingEventType(0x0013);
+        assert!(adv_ind.is_connectable());
+        assert!(adv_ind.is_scannable());
+        assert!(!adv_ind.is_directed());
+        assert!(!adv_ind.is_scan_response());
+        assert!(adv_ind.is_legacy());
+        assert_eq!(adv_ind.data_status(), AdvertisingDataStatus::Complete);
+
+        let extended = AdvertisingEventType(0x0021);
+        assert!(extended.is_connectable());
+        assert!(!extended.is_legacy());
+        assert_eq!(extended.data_status(), AdvertisingDataStatus::Incomplete);
+        assert_eq!(AdvertisingEventType(0x0040).data_status(), AdvertisingDataStatus::Truncated);
+        assert_eq!(AdvertisingEventType(0x0060).data_status(), AdvertisingDataStatus::Reserved);
+    }
 
-        // Register callbacks for gatt scanner and advertiser
-        mutcxxcall!(self.scanner, RegisterCallbacks);
-        mutcxxcall!(self.advertiser, RegisterCallbacks);
+    #[test]
+    fn parse_adv_data_fields() {
+        let data = [
+            0x02, 0x01, 0x06, // Flags
+            0x05, 0x03, 0x0f, 0x18, 0x0a, 0x18, // Complete 16-bit uuids
+            0x04, 0x08, b'a', b'b', b'c', // Shortened local name
+            0x02, 0x0a, 0xf4, // Tx power
+            0x05, 0xff, 0xe0, 0x00, 0x01, 0x02, // Manufacturer data
+            0x04, 0x16, 0x0f, 0x18, 0x64, // Service data
+            0x03, 0xaa, 0x01, 0x02, // Unknown type
+            0x00, 0x02, 0x01, 0x06, // Padding ends the data
+        ];
+
+        assert_eq!(
+            parse_adv_data(&data),
+            vec![
+                AdvDataField::Flags(0x06),
+                AdvDataField::ServiceUuids16 { complete: true, uuids: vec![0x180f, 0x180a] },
+                AdvDataField::LocalName { complete: false, name: "abc".to_string() },
+                AdvDataField::TxPower(-12),
+                AdvDataField::ManufacturerData { company_id: 0x00e0, data: vec![0x01, 0x02] },
+                AdvDataField::ServiceData16 { uuid: 0x180f, data: vec![0x64] },
+                AdvDataField::Other { ad_type: 0xaa, data: vec![0x01, 0x02] },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_adv_data_malformed() {
+        assert!(parse_adv_data(&[]).is_empty());
+
+        // The second field claims more bytes than are left.
+        let truncated = [0x02, 0x01, 0x06, 0x09, 0x09, b'a'];
+        assert_eq!(parse_adv_data(&truncated), vec![AdvDataField::Flags(0x06)]);
+
+        // Manufacturer data too short for a company id.
+        let short = [0x02, 0xff, 0x01];
+        assert_eq!(
+            parse_adv_data(&short),
+            vec![AdvDataField::Other { ad_type: 0xff, data: vec![0x01] }]
+        );
+    }
+
+    #[test]
+    fn prepared_write_buffer_reassembles() {
+        let mut buffer = PreparedWriteBuffer::new();
+        buffer.prepare(1, 0x10, 0, &[1, 2, 3]);
+        buffer.prepare(1, 0x20, 0, &[9]);
+        buffer.prepare(1, 0x10, 3, &[4, 5]);
+        buffer.prepare(2, 0x10, 0, &[7]);
+
+        assert_eq!(
+            buffer.execute(1, ExecuteWriteMode::Execute),
+            Ok(vec![
+                CompletedWrite { handle: 0x10, value: vec![1, 2, 3, 4, 5] },
+                CompletedWrite { handle: 0x20, value: vec![9] },
+            ])
+        );
+        assert_eq!(buffer.execute(1, ExecuteWriteMode::Execute), Ok(vec![]));
+
+        // Cancelled writes are dropped.
+        assert_eq!(buffer.execute(2, ExecuteWriteMode::Cancel), Ok(vec![]));
+        assert_eq!(buffer.execute(2, ExecuteWriteMode::Execute), Ok(vec![]));
+
+        // A gap in the prepared data.
+        buffer.prepare(1, 0x10, 0, &[1]);
+        buffer.prepare(1, 0x10, 2, &[3]);
+        assert_eq!(buffer.execute(1, ExecuteWriteMode::Execute), Err(GattStatus::InvalidOffset));
+    }
+
+    #[test]
+    fn prepared_write_buffer_discards_on_cancel() {
+        let mut buffer = PreparedWriteBuffer::new();
+        let addr = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let prepare = |offset, value: Vec<u8>| {
+            let len = value.len();
+            GattServerCallbacks::RequestWriteCharacteristic(
+                1, 7, addr, 0x10, offset, false, true, value, len,
+            )
+        };
+        let exec_write = |exec_write| {
+            GattServerCallbacks::RequestExecWrite(1, 8, addr, ExecuteWriteMode::from(exec_write))
+        };
 
-        return self.is_init;
+        assert_eq!(ExecuteWriteMode::from(0), ExecuteWriteMode::Cancel);
+        assert_eq!(ExecuteWriteMode::from(1), ExecuteWriteMode::Execute);
+
+        assert_eq!(buffer.on_callback(&prepare(0, vec![1, 2])), None);
+        assert_eq!(buffer.on_callback(&prepare(2, vec![3])), None);
+        assert_eq!(buffer.on_callback(&exec_write(0)), Some(Ok(vec![])));
+
+        // Nothing from before the cancel is left to execute.
+        assert_eq!(buffer.on_callback(&prepare(0, vec![4])), None);
+        assert_eq!(
+            buffer.on_callback(&exec_write(1)),
+            Some(Ok(vec![CompletedWrite { handle: 0x10, value: vec![4] }]))
+        );
+    }
+
+    #[test]
+    fn connection_policy_allows() {
+        let a = RawAddress { val: [1, 2, 3, 4, 5, 6] };
+        let b = RawAddress { val: [6, 5, 4, 3, 2, 1] };
+
+        assert!(ConnectionPolicy::AcceptAll.allows(&a));
+        assert!(ConnectionPolicy::Allowlist(vec![a]).allows(&a));
+        assert!(!ConnectionPolicy::Allowlist(vec![a]).allows(&b));
+        assert!(!ConnectionPolicy::Denylist(vec![a]).allows(&a));
+        assert!(ConnectionPolicy::Denylist(vec![a]).allows(&b));
+    }
+
+    fn make_element(element_type: GattDbElementType, handle: u16, uuid: u8) -> BtGattDbElement {
+        BtGattDbElement {
+            type_: element_type as u32,
+            attribute_handle: handle,
+            uuid: Uuid { uu: [uuid; 16] },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gatt_database_finds_descriptors() {
+        let elements = vec![
+            make_element(GattDbElementType::PrimaryService, 0x10, 1),
+            make_element(GattDbElementType::Characteristic, 0x12, 2),
+            make_element(GattDbElementType::Descriptor, 0x13, 3),
+            make_element(GattDbElementType::PrimaryService, 0x20, 4),
+            make_element(GattDbElementType::Characteristic, 0x22, 2),
+            make_element(GattDbElementType::Descriptor, 0x23, 3),
+        ];
+        let db = GattDatabase::from_elements(elements);
+        let uuid = |n| Uuid { uu: [n; 16] };
+
+        assert_eq!(db.characteristic(0x22).map(|c| c.uuid.uu), Some(uuid(2).uu));
+        assert!(db.characteristic(0x23).is_none());
+
+        // The same characteristic and descriptor uuids in another service.
+        assert_eq!(db.descriptor(&uuid(4), &uuid(2), &uuid(3)).map(|d| d.handle), Some(0x23));
+        assert_eq!(db.descriptor(&uuid(1), &uuid(2), &uuid(3)).map(|d| d.handle), Some(0x13));
+        assert!(db.descriptor(&uuid(1), &uuid(2), &uuid(5)).is_none());
+    }
+
+    #[test]
+    fn gatt_database_from_elements() {
+        let elements = vec![
+            // Orphan descriptor before any service is dropped.
+            make_element(GattDbElementType::Descriptor, 0x01, 0),
+            BtGattDbElement {
+                start_handle: 0x10,
+                end_handle: 0x1f,
+                ..make_element(GattDbElementType::PrimaryService, 0, 1)
+            },
+            BtGattDbElement {
+                start_handle: 0x20,
+                ..make_element(GattDbElementType::IncludedService, 0x11, 2)
+            },
+            make_element(GattDbElementType::Characteristic, 0x13, 3),
+            make_element(GattDbElementType::Descriptor, 0x14, 4),
+            // Secondary service without an end handle.
+            BtGattDbElement {
+                start_handle: 0x20,
+                ..make_element(GattDbElementType::SecondaryService, 0, 2)
+            },
+            make_element(GattDbElementType::Characteristic, 0x22, 5),
+            make_element(GattDbElementType::Descriptor, 0x23, 6),
+        ];
+
+        let db = GattDatabase::from_elements(elements);
+        assert_eq!(db.services.len(), 2);
+
+        let service = &db.services[0];
+        assert!(service.is_primary);
+        assert_eq!((service.handle, service.end_handle), (0x10, 0x1f));
+        assert_eq!(service.included_services.len(), 1);
+        assert_eq!(service.included_services[0].start_handle, 0x20);
+        assert_eq!(service.characteristics.len(), 1);
+        assert_eq!(service.characteristics[0].value_handle, 0x13);
+        assert_eq!(service.characteristics[0].descriptors[0].handle, 0x14);
+
+        let service = &db.services[1];
+        assert!(!service.is_primary);
+        assert_eq!((service.handle, service.end_handle), (0x20, 0x23));
+        assert_eq!(service.characteristics[0].descriptors.len(), 1);
     }
 }
@@ -147,8 +147,11 @@ impl Hfp {
     }
 
     pub fn initialize(&mut self, callbacks: HfpCallbacksDispatcher) -> bool {
-        if get_dispatchers().lock().unwrap().set::<HfpCb>(Arc::new(Mutex::new(callbacks))) {
-            panic!("Tried to set dispatcher for HFP callbacks while it already exists");
+        if let Err(e) =
+            get_dispatchers().lock().unwrap().try_set::<HfpCb>(Arc::new(Mutex::new(callbacks)))
+        {
+            log::error!("Failed to initialize HFP: {:?}", e);
+            return false;
         }
         self.internal.pin_mut().init();
         true
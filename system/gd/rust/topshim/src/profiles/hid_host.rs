@@ -177,8 +177,11 @@ impl HidHost {
 
     pub fn initialize(&mut self, callbacks: HHCallbacksDispatcher) -> bool {
         // Register dispatcher
-        if get_dispatchers().lock().unwrap().set::<HHCb>(Arc::new(Mutex::new(callbacks))) {
-            panic!("Tried to set dispatcher for HHCallbacks but it already existed");
+        if let Err(e) =
+            get_dispatchers().lock().unwrap().try_set::<HHCb>(Arc::new(Mutex::new(callbacks)))
+        {
+            log::error!("Failed to initialize HidHost: {:?}", e);
+            return false;
         }
 
         let mut callbacks = Box::new(bindings::bthh_callbacks_t {
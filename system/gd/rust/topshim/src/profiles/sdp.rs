@@ -373,8 +373,11 @@ impl Sdp {
     }
 
     pub fn initialize(&mut self, callbacks: SdpCallbacksDispatcher) -> bool {
-        if get_dispatchers().lock().unwrap().set::<SdpCb>(Arc::new(Mutex::new(callbacks))) {
-            panic!("Tried to set dispatcher for SdpCallbacks but it already existed");
+        if let Err(e) =
+            get_dispatchers().lock().unwrap().try_set::<SdpCb>(Arc::new(Mutex::new(callbacks)))
+        {
+            log::error!("Failed to initialize SDP: {:?}", e);
+            return false;
         }
 
         let mut callbacks = Box::new(bindings::btsdp_callbacks_t {
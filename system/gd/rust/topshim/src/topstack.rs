@@ -58,6 +58,11 @@ lazy_static! {
 ///  ```
 pub type InstanceBox = Box<dyn Any + Send + Sync>;
 
+/// Returned by [`DispatchContainer::try_set`] when a dispatcher of the same
+/// type is already set. Holds the name of that type.
+#[derive(Debug, PartialEq)]
+pub struct DispatcherAlreadySet(pub &'static str);
+
 /// Manage enum dispatches for emulating callbacks.
 ///
 /// Libbluetooth is highly callback based but our Rust code prefers using
@@ -99,9 +104,103 @@ impl DispatchContainer {
     pub fn set<T: 'static + Clone + Send + Sync>(&mut self, obj: T) -> bool {
         self.instances.insert(TypeId::of::<T>(), Box::new(obj)).is_some()
     }
+
+    /// Set dispatcher for an enum specialization unless one is already set.
+    ///
+    /// # Returns
+    ///
+    /// Err with the existing dispatcher left in place if one was already set.
+    pub fn try_set<T: 'static + Clone + Send + Sync>(
+        &mut self,
+        obj: T,
+    ) -> Result<(), DispatcherAlreadySet> {
+        let typeid = TypeId::of::<T>();
+        if self.instances.contains_key(&typeid) {
+            return Err(DispatcherAlreadySet(std::any::type_name::<T>()));
+        }
+
+        self.instances.insert(typeid, Box::new(obj));
+        Ok(())
+    }
+
+    /// Get the dispatcher for an enum specialization, setting the one returned
+    /// by `init` first if none is set.
+    pub fn get_or_init<T: 'static + Clone + Send + Sync>(&mut self, init: impl FnOnce() -> T) -> T {
+        self.instances
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(init()))
+            .downcast_ref::<T>()
+            .unwrap()
+            .clone()
+    }
+
+    /// Remove the dispatcher for an enum specialization.
+    ///
+    /// # Returns
+    ///
+    /// True if a dispatcher was registered.
+    pub fn remove<T: 'static + Clone + Send + Sync>(&mut self) -> bool {
+        self.instances.remove(&TypeId::of::<T>()).is_some()
+    }
 }
 
 /// Take a clone of the static dispatcher container.
 pub fn get_dispatchers() -> Arc<Mutex<DispatchContainer>> {
     CB_DISPATCHER.clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_container() -> DispatchContainer {
+        DispatchContainer { instances: HashMap::new() }
+    }
+
+    #[test]
+    fn try_set_keeps_existing_dispatcher() {
+        let mut container = new_container();
+
+        assert_eq!(container.try_set::<u32>(1), Ok(()));
+        assert_eq!(
+            container.try_set::<u32>(2),
+            Err(DispatcherAlreadySet(std::any::type_name::<u32>()))
+        );
+        assert_eq!(container.get::<u32>(), Some(1));
+    }
+
+    #[test]
+    fn get_or_init_only_initializes_once() {
+        let mut container = new_container();
+        let mut inits = 0;
+
+        assert_eq!(
+            container.get_or_init::<u32>(|| {
+                inits += 1;
+                1
+            }),
+            1
+        );
+        assert_eq!(
+            container.get_or_init::<u32>(|| {
+                inits += 1;
+                2
+            }),
+            1
+        );
+        assert_eq!(inits, 1);
+    }
+
+    #[test]
+    fn remove_allows_setting_again() {
+        let mut container = new_container();
+
+        assert!(!container.remove::<u32>());
+        assert_eq!(container.try_set::<u32>(1), Ok(()));
+        assert!(container.remove::<u32>());
+        assert!(!container.remove::<u32>());
+        assert_eq!(container.get::<u32>(), None);
+        assert_eq!(container.try_set::<u32>(2), Ok(()));
+        assert_eq!(container.get::<u32>(), Some(2));
+    }
+}
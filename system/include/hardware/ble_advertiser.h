@@ -69,6 +69,12 @@ class AdvertisingCallbacks {
                                             uint8_t status) = 0;
   virtual void OnOwnAddressRead(uint8_t advertiser_id, uint8_t address_type,
                                 RawAddress address) = 0;
+  /** Called after OnAdvertisingEnabled(advertiser_id, false, status) when the
+   * controller stopped a set because it reached its duration (status 0x3C) or
+   * maximum number of extended advertising events (status 0x43). */
+  virtual void OnAdvertisingSetTerminated(
+      uint8_t advertiser_id, uint8_t status,
+      uint8_t num_completed_extended_adv_events) {}
 };
 
 class BleAdvertiserInterface {
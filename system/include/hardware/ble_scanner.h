@@ -107,6 +107,10 @@ class ScanningCallbacks {
   virtual void OnPeriodicSyncLost(uint16_t sync_handle) = 0;
   virtual void OnPeriodicSyncTransferred(int pa_source, uint8_t status,
                                          RawAddress address) = 0;
+  virtual void OnMsftAdvMonitorDeviceFound(uint8_t monitor_handle,
+                                           RawAddress address) = 0;
+  virtual void OnMsftAdvMonitorDeviceLost(uint8_t monitor_handle,
+                                          RawAddress address) = 0;
 };
 
 class BleScannerInterface {
@@ -129,6 +133,15 @@ class BleScannerInterface {
       base::Callback<void(uint8_t /* filt_type */, uint8_t /* avbl_space */,
                           uint8_t /* action */, uint8_t /* btm_status */)>;
 
+  using MsftAdvMonitorAddCallback =
+      base::Callback<void(uint8_t /* monitor_handle */, uint8_t /* status */)>;
+
+  using MsftAdvMonitorRemoveCallback =
+      base::Callback<void(uint8_t /* status */)>;
+
+  using MsftAdvMonitorEnableCallback =
+      base::Callback<void(uint8_t /* status */)>;
+
   /** Registers a scanner with the stack */
   virtual void RegisterScanner(const bluetooth::Uuid& app_uuid,
                                RegisterCallback) = 0;
@@ -155,9 +168,11 @@ class BleScannerInterface {
   /** Enable / disable scan filter feature*/
   virtual void ScanFilterEnable(bool enable, EnableCallback cb) = 0;
 
-  /** Sets the LE scan interval and window in units of N*0.625 msec */
+  /** Sets the LE scan interval and window in units of N*0.625 msec, and the
+   * PHYs to scan on as a mask (bit 0: LE 1M, bit 2: LE Coded) */
   virtual void SetScanParameters(int scanner_id, int scan_interval,
-                                 int scan_window, Callback cb) = 0;
+                                 int scan_window, int scan_phy,
+                                 Callback cb) = 0;
 
   /* Configure the batchscan storage */
   virtual void BatchscanConfigStorage(int client_if, int batch_scan_full_max,
@@ -190,6 +205,23 @@ class BleScannerInterface {
                                uint8_t adv_handle, int pa_source) = 0;
   virtual void SyncTxParameters(RawAddress addr, uint8_t mode, uint16_t skip,
                                 uint16_t timeout, int reg_id) = 0;
+
+  /** Whether the controller supports the advertisement monitors of the
+   * Microsoft HCI extension */
+  virtual bool IsMsftSupported() = 0;
+
+  /** Add an MSFT advertisement monitor. Devices matching it are reported via
+   * OnMsftAdvMonitorDeviceFound and OnMsftAdvMonitorDeviceLost */
+  virtual void MsftAdvMonitorAdd(MsftAdvMonitor monitor,
+                                 MsftAdvMonitorAddCallback cb) = 0;
+
+  /** Remove an MSFT advertisement monitor */
+  virtual void MsftAdvMonitorRemove(uint8_t monitor_handle,
+                                    MsftAdvMonitorRemoveCallback cb) = 0;
+
+  /** Enable / disable all MSFT advertisement monitors */
+  virtual void MsftAdvMonitorEnable(bool enable,
+                                    MsftAdvMonitorEnableCallback cb) = 0;
 };
 
 #endif /* ANDROID_INCLUDE_BLE_SCANNER_H */
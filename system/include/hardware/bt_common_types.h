@@ -107,4 +107,20 @@ struct ApcfCommand {
   std::array<uint8_t, 16> irk;  // 128 bit/16 octet IRK
 };
 
+// Advertising data pattern of an MSFT advertisement monitor
+struct MsftAdvMonitorPattern {
+  uint8_t ad_type;
+  uint8_t start_byte;  // Offset of the pattern within the AD structure data
+  std::vector<uint8_t> pattern;
+};
+
+// Advertisement monitor of the Microsoft HCI extension
+struct MsftAdvMonitor {
+  int8_t rssi_threshold_high;
+  int8_t rssi_threshold_low;
+  uint8_t rssi_threshold_low_time_interval;  // In seconds
+  uint8_t rssi_sampling_period;              // In units of 100ms
+  std::vector<MsftAdvMonitorPattern> patterns;
+};
+
 #endif /* ANDROID_INCLUDE_BT_COMMON_TYPES_H */
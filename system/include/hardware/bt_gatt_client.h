@@ -128,6 +128,10 @@ typedef void (*notify_callback)(int conn_id,
 typedef void (*read_characteristic_callback)(int conn_id, int status,
                                              btgatt_read_params_t* p_data);
 
+/** Reports result of a GATT read multiple operation */
+typedef void (*read_multiple_callback)(int conn_id, int status,
+                                       const uint8_t* value, uint16_t len);
+
 /** GATT write characteristic operation callback */
 typedef void (*write_characteristic_callback)(int conn_id, int status,
                                               uint16_t handle, uint16_t len,
@@ -183,6 +187,15 @@ typedef void (*conn_updated_callback)(int conn_id, uint16_t interval,
                                       uint16_t latency, uint16_t timeout,
                                       uint8_t status);
 
+/** Callback invoked when the subrate parameters for a given connection
+ * changes */
+typedef void (*subrate_change_callback)(int conn_id, uint16_t subrate_factor,
+                                        uint16_t latency, uint16_t cont_num,
+                                        uint16_t timeout, uint8_t status);
+
+/** Callback invoked when the remote device exchanges the MTU */
+typedef void (*client_mtu_changed_callback)(int conn_id, int mtu);
+
 /** Callback when services are changed */
 typedef void (*service_changed_callback)(int conn_id);
 
@@ -207,6 +220,9 @@ typedef struct {
   phy_updated_callback phy_updated_cb;
   conn_updated_callback conn_updated_cb;
   service_changed_callback service_changed_cb;
+  read_multiple_callback read_multiple_cb;
+  subrate_change_callback subrate_chg_cb;
+  client_mtu_changed_callback mtu_changed_cb;
 } btgatt_client_callbacks_t;
 
 /** Represents the standard BT-GATT client interface. */
@@ -219,10 +235,12 @@ typedef struct {
   /** Unregister a client application from the stack */
   bt_status_t (*unregister_client)(int client_if);
 
-  /** Create a connection to a remote LE or dual-mode device */
+  /** Create a connection to a remote LE or dual-mode device. With a public
+   * |addr_type| the type stored for |bd_addr| is used, if any; other types
+   * are used as given. */
   bt_status_t (*connect)(int client_if, const RawAddress& bd_addr,
-                         bool is_direct, int transport, bool opportunistic,
-                         int initiating_phys);
+                         uint8_t addr_type, bool is_direct, int transport,
+                         bool opportunistic, int initiating_phys);
 
   /** Disconnect a remote device or cancel a pending connection */
   bt_status_t (*disconnect)(int client_if, const RawAddress& bd_addr,
@@ -244,9 +262,13 @@ typedef struct {
   void (*btif_gattc_discover_service_by_uuid)(int conn_id,
                                               const bluetooth::Uuid& uuid);
 
-  /** Read a characteristic on a remote device */
+  /**
+   * Read a characteristic on a remote device. |bearer| is a tGATT_BEARER
+   * value: 0 picks the bearer from the client's EATT support, 1 prefers an
+   * enhanced bearer and 2 uses the unenhanced one.
+   */
   bt_status_t (*read_characteristic)(int conn_id, uint16_t handle,
-                                     int auth_req);
+                                     int auth_req, int bearer);
 
   /** Read a characteristic on a remote device */
   bt_status_t (*read_using_characteristic_uuid)(int conn_id,
@@ -255,10 +277,14 @@ typedef struct {
                                                 uint16_t e_handle,
                                                 int auth_req);
 
-  /** Write a remote characteristic */
+  /**
+   * Write a remote characteristic. |bearer| is chosen as for
+   * |read_characteristic|.
+   */
   bt_status_t (*write_characteristic)(int conn_id, uint16_t handle,
                                       int write_type, int auth_req,
-                                      const uint8_t* value, size_t length);
+                                      const uint8_t* value, size_t length,
+                                      int bearer);
 
   /** Read the descriptor for a given characteristic */
   bt_status_t (*read_descriptor)(int conn_id, uint16_t handle, int auth_req);
@@ -312,6 +338,16 @@ typedef struct {
   /** Get gatt db content */
   bt_status_t (*get_gatt_db)(int conn_id);
 
+  /** Read multiple characteristic values in a single request */
+  bt_status_t (*read_multiple)(int conn_id, const uint16_t* handles,
+                               size_t num_handles, int auth_req);
+
+  /** Request LE connection subrating. The result is reported through
+   * subrate_chg_cb, also when the controller rejects the request. */
+  bt_status_t (*subrate_request)(const RawAddress& bd_addr, int subrate_min,
+                                 int subrate_max, int max_latency,
+                                 int cont_num, int sup_timeout);
+
 } btgatt_client_interface_t;
 
 __END_DECLS
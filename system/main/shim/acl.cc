@@ -772,6 +772,23 @@ class LeShimAclConnection
     connection_->UpdateLocalAddress(address_with_type);
   }
 
+  void OnLeSubrateChange(hci::ErrorCode hci_status, uint16_t subrate_factor,
+                         uint16_t peripheral_latency,
+                         uint16_t continuation_number,
+                         uint16_t supervision_timeout) override {
+    TRY_POSTING_ON_MAIN(interface_.on_le_subrate_change,
+                        ToLegacyHciErrorCode(hci_status), handle_,
+                        subrate_factor, peripheral_latency,
+                        continuation_number, supervision_timeout);
+  }
+
+  void LeSubrateRequest(uint16_t subrate_min, uint16_t subrate_max,
+                        uint16_t max_latency, uint16_t cont_num,
+                        uint16_t sup_tout) {
+    connection_->LeSubrateRequest(subrate_min, subrate_max, max_latency,
+                                  cont_num, sup_tout);
+  }
+
   void OnDisconnection(hci::ErrorCode reason) {
     Disconnect();
     on_disconnect_(handle_, reason);
@@ -939,6 +956,20 @@ struct shim::legacy::Acl::impl {
     }
   }
 
+  void LeSubrateRequest(HciHandle handle, uint16_t subrate_min,
+                        uint16_t subrate_max, uint16_t max_latency,
+                        uint16_t cont_num, uint16_t sup_tout) {
+    auto connection = handle_to_le_connection_map_.find(handle);
+    if (connection == handle_to_le_connection_map_.end()) {
+      LOG_WARN("Unable to request subrate for unknown le connection "
+               "handle:0x%04x",
+               handle);
+      return;
+    }
+    connection->second->LeSubrateRequest(subrate_min, subrate_max, max_latency,
+                                         cont_num, sup_tout);
+  }
+
   void disconnect_le(uint16_t handle, tHCI_STATUS reason, std::string comment) {
     auto connection = handle_to_le_connection_map_.find(handle);
     if (connection != handle_to_le_connection_map_.end()) {
@@ -1643,6 +1674,15 @@ bool shim::legacy::Acl::SniffSubrating(uint16_t hci_handle,
   return false;
 }
 
+void shim::legacy::Acl::LeSubrateRequest(uint16_t hci_handle,
+                                         uint16_t subrate_min,
+                                         uint16_t subrate_max,
+                                         uint16_t max_latency,
+                                         uint16_t cont_num, uint16_t sup_tout) {
+  handler_->CallOn(pimpl_.get(), &Acl::impl::LeSubrateRequest, hci_handle,
+                   subrate_min, subrate_max, max_latency, cont_num, sup_tout);
+}
+
 void shim::legacy::Acl::DumpConnectionHistory(int fd) const {
   pimpl_->DumpConnectionHistory(fd);
 }
@@ -35,6 +35,9 @@ void ACL_Disconnect(uint16_t handle, bool is_classic, tHCI_STATUS reason,
                     std::string comment);
 void ACL_WriteData(uint16_t handle, BT_HDR* p_buf);
 void ACL_ConfigureLePrivacy(bool is_le_privacy_enabled);
+void ACL_LeSubrateRequest(uint16_t hci_handle, uint16_t subrate_min,
+                          uint16_t subrate_max, uint16_t max_latency,
+                          uint16_t cont_num, uint16_t sup_tout);
 void ACL_Shutdown();
 void ACL_IgnoreAllLeConnections();
 
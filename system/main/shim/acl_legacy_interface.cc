@@ -83,6 +83,7 @@ const acl_interface_t GetAclInterface() {
       .link.le.on_read_remote_version_information_complete =
           btm_read_remote_version_complete,
       .link.le.on_phy_update = gatt_notify_phy_updated,
+      .link.le.on_le_subrate_change = acl_ble_subrate_change_event,
   };
   return acl_interface;
 }
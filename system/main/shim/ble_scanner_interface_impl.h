@@ -53,7 +53,7 @@ class BleScannerInterfaceImpl : public ::BleScannerInterface,
   void ScanFilterClear(int filter_index, FilterConfigCallback cb) override;
   void ScanFilterEnable(bool enable, EnableCallback cb) override;
   void SetScanParameters(int scanner_id, int scan_interval, int scan_window,
-                         Callback cb) override;
+                         int scan_phy, Callback cb) override;
   void BatchscanConfigStorage(int client_if, int batch_scan_full_max,
                               int batch_scan_trunc_max,
                               int batch_scan_notify_threshold,
@@ -72,6 +72,13 @@ class BleScannerInterfaceImpl : public ::BleScannerInterface,
                        uint8_t adv_handle, int pa_source) override;
   void SyncTxParameters(RawAddress addr, uint8_t mode, uint16_t skip,
                         uint16_t timeout, int reg_id) override;
+  bool IsMsftSupported() override;
+  void MsftAdvMonitorAdd(MsftAdvMonitor monitor,
+                         MsftAdvMonitorAddCallback cb) override;
+  void MsftAdvMonitorRemove(uint8_t monitor_handle,
+                            MsftAdvMonitorRemoveCallback cb) override;
+  void MsftAdvMonitorEnable(bool enable,
+                            MsftAdvMonitorEnableCallback cb) override;
 
   // bluetooth::hci::ScanningCallback
   void RegisterCallbacks(ScanningCallbacks* callbacks);
@@ -118,6 +125,13 @@ class BleScannerInterfaceImpl : public ::BleScannerInterface,
       ApcfCommand apcf_command);
   void handle_remote_properties(RawAddress bd_addr, tBLE_ADDR_TYPE addr_type,
                                 std::vector<uint8_t> advertising_data);
+  void OnMsftAdvMonitorAdd(MsftAdvMonitorAddCallback cb,
+                           uint8_t monitor_handle,
+                           bluetooth::hci::ErrorCode status);
+  void OnMsftAdvMonitorStatus(Callback cb, bluetooth::hci::ErrorCode status);
+  void OnMsftAdvMonitorDevice(uint8_t monitor_handle,
+                              bluetooth::hci::AddressWithType address,
+                              bool found);
 
   class AddressCache {
    public:
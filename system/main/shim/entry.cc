@@ -19,6 +19,7 @@
 #include "gd/hci/hci_layer.h"
 #include "gd/hci/le_advertising_manager.h"
 #include "gd/hci/le_scanning_manager.h"
+#include "gd/hci/msft.h"
 #include "gd/hci/vendor_specific_event_manager.h"
 #include "gd/metrics/counter_metrics.h"
 #include "gd/neighbor/connectability.h"
@@ -109,6 +110,12 @@ hci::LeScanningManager* GetScanning() {
       ->GetInstance<hci::LeScanningManager>();
 }
 
+hci::MsftExtensionManager* GetMsftExtensionManager() {
+  return Stack::GetInstance()
+      ->GetStackManager()
+      ->GetInstance<hci::MsftExtensionManager>();
+}
+
 security::SecurityModule* GetSecurityModule() {
   return Stack::GetInstance()
       ->GetStackManager()
@@ -51,6 +51,7 @@ class HciLayer;
 class AclManager;
 class LeAdvertisingManager;
 class LeScanningManager;
+class MsftExtensionManager;
 class VendorSpecificEventManager;
 }
 
@@ -92,6 +93,7 @@ l2cap::le::L2capLeModule* GetL2capLeModule();
 neighbor::NameModule* GetName();
 neighbor::PageModule* GetPage();
 hci::LeScanningManager* GetScanning();
+hci::MsftExtensionManager* GetMsftExtensionManager();
 bluetooth::security::SecurityModule* GetSecurityModule();
 storage::StorageModule* GetStorage();
 hci::AclManager* GetAclManager();
@@ -378,6 +378,16 @@ class BleAdvertiserInterfaceImpl : public BleAdvertiserInterface,
                                 advertiser_id, address_type, raw_address));
   }
 
+  void OnAdvertisingSetTerminated(
+      uint8_t advertiser_id, uint8_t status,
+      uint8_t num_completed_extended_adv_events) override {
+    do_in_jni_thread(
+        FROM_HERE,
+        base::Bind(&AdvertisingCallbacks::OnAdvertisingSetTerminated,
+                   base::Unretained(advertising_callbacks_), advertiser_id,
+                   status, num_completed_extended_adv_events));
+  }
+
   AdvertisingCallbacks* advertising_callbacks_;
 
  private:
@@ -30,6 +30,7 @@
 #include "btif/include/btif_common.h"
 #include "hci/address.h"
 #include "hci/le_scanning_manager.h"
+#include "hci/msft.h"
 #include "include/hardware/ble_scanner.h"
 #include "main/shim/ble_scanner_interface_impl.h"
 #include "main/shim/dumpsys.h"
@@ -100,6 +101,14 @@ class DefaultScanningCallback : public ::ScanningCallbacks {
                                  RawAddress address) override {
     LogUnused();
   };
+  void OnMsftAdvMonitorDeviceFound(uint8_t monitor_handle,
+                                   RawAddress address) override {
+    LogUnused();
+  };
+  void OnMsftAdvMonitorDeviceLost(uint8_t monitor_handle,
+                                  RawAddress address) override {
+    LogUnused();
+  };
 
  private:
   static void LogUnused() {
@@ -133,6 +142,9 @@ using bluetooth::shim::BleScannerInterfaceImpl;
 void BleScannerInterfaceImpl::Init() {
   LOG_INFO("init BleScannerInterfaceImpl");
   bluetooth::shim::GetScanning()->RegisterScanningCallback(this);
+  bluetooth::shim::GetMsftExtensionManager()->RegisterDeviceCallback(
+      bluetooth::shim::GetGdShimHandler()->BindOn(
+          this, &BleScannerInterfaceImpl::OnMsftAdvMonitorDevice));
 }
 
 /** Registers a scanner with the stack */
@@ -246,7 +258,8 @@ void BleScannerInterfaceImpl::ScanFilterEnable(bool enable, EnableCallback cb) {
   /** Sets the LE scan interval and window in units of N*0.625 msec */
 void BleScannerInterfaceImpl::SetScanParameters(int scanner_id,
                                                 int scan_interval,
-                                                int scan_window, Callback cb) {
+                                                int scan_window, int scan_phy,
+                                                Callback cb) {
   LOG(INFO) << __func__ << " in shim layer";
   tBTM_BLE_INQ_CB* p_cb = &btm_cb.ble_ctr_cb.inq_var;
   if (BTM_BLE_ISVALID_PARAM(scan_interval, BTM_BLE_SCAN_INT_MIN,
@@ -260,8 +273,8 @@ void BleScannerInterfaceImpl::SetScanParameters(int scanner_id,
 
   // use active scan
   auto scan_type = static_cast<bluetooth::hci::LeScanType>(0x01);
-  bluetooth::shim::GetScanning()->SetScanParameters(scanner_id, scan_type,
-                                                    scan_interval, scan_window);
+  bluetooth::shim::GetScanning()->SetScanParameters(
+      scanner_id, scan_type, scan_interval, scan_window, scan_phy);
 }
 
 /* Configure the batchscan storage */
@@ -305,6 +318,52 @@ void BleScannerInterfaceImpl::BatchscanReadReports(int client_if,
                                                       batch_scan_mode);
 }
 
+bool BleScannerInterfaceImpl::IsMsftSupported() {
+  return bluetooth::shim::GetMsftExtensionManager()->SupportsMsftExtensions();
+}
+
+/* Adds an advertisement monitor offloaded to the controller */
+void BleScannerInterfaceImpl::MsftAdvMonitorAdd(MsftAdvMonitor monitor,
+                                                MsftAdvMonitorAddCallback cb) {
+  LOG(INFO) << __func__ << " in shim layer";
+  bluetooth::hci::MsftAdvMonitor gd_monitor = {
+      .rssi_threshold_high = monitor.rssi_threshold_high,
+      .rssi_threshold_low = monitor.rssi_threshold_low,
+      .rssi_threshold_low_time_interval =
+          monitor.rssi_threshold_low_time_interval,
+      .rssi_sampling_period = monitor.rssi_sampling_period,
+  };
+  for (const auto& pattern : monitor.patterns) {
+    gd_monitor.patterns.push_back({
+        .ad_type = pattern.ad_type,
+        .start_byte = pattern.start_byte,
+        .pattern = pattern.pattern,
+    });
+  }
+  bluetooth::shim::GetMsftExtensionManager()->MsftAdvMonitorAdd(
+      gd_monitor, bluetooth::shim::GetGdShimHandler()->BindOnceOn(
+                      this, &BleScannerInterfaceImpl::OnMsftAdvMonitorAdd, cb));
+}
+
+/* Removes an advertisement monitor */
+void BleScannerInterfaceImpl::MsftAdvMonitorRemove(
+    uint8_t monitor_handle, MsftAdvMonitorRemoveCallback cb) {
+  LOG(INFO) << __func__ << " in shim layer";
+  bluetooth::shim::GetMsftExtensionManager()->MsftAdvMonitorRemove(
+      monitor_handle,
+      bluetooth::shim::GetGdShimHandler()->BindOnceOn(
+          this, &BleScannerInterfaceImpl::OnMsftAdvMonitorStatus, cb));
+}
+
+/* Enables or disables all the advertisement monitors */
+void BleScannerInterfaceImpl::MsftAdvMonitorEnable(
+    bool enable, MsftAdvMonitorEnableCallback cb) {
+  LOG(INFO) << __func__ << " in shim layer";
+  bluetooth::shim::GetMsftExtensionManager()->MsftAdvMonitorEnable(
+      enable, bluetooth::shim::GetGdShimHandler()->BindOnceOn(
+                  this, &BleScannerInterfaceImpl::OnMsftAdvMonitorStatus, cb));
+}
+
 extern bool btm_random_pseudo_to_identity_addr(
     RawAddress* random_pseudo, tBLE_ADDR_TYPE* p_identity_addr_type);
 
@@ -543,6 +602,35 @@ void BleScannerInterfaceImpl::OnPeriodicSyncTransferred(
                                   pa_source, status, ToRawAddress(address)));
 }
 
+void BleScannerInterfaceImpl::OnMsftAdvMonitorAdd(
+    MsftAdvMonitorAddCallback cb, uint8_t monitor_handle,
+    bluetooth::hci::ErrorCode status) {
+  do_in_jni_thread(FROM_HERE, base::Bind(cb, monitor_handle,
+                                         static_cast<uint8_t>(status)));
+}
+
+void BleScannerInterfaceImpl::OnMsftAdvMonitorStatus(
+    Callback cb, bluetooth::hci::ErrorCode status) {
+  do_in_jni_thread(FROM_HERE, base::Bind(cb, static_cast<uint8_t>(status)));
+}
+
+void BleScannerInterfaceImpl::OnMsftAdvMonitorDevice(
+    uint8_t monitor_handle, bluetooth::hci::AddressWithType address,
+    bool found) {
+  RawAddress raw_address = ToRawAddress(address.GetAddress());
+  tBLE_ADDR_TYPE ble_addr_type =
+      to_ble_addr_type((uint8_t)address.GetAddressType());
+  if (ble_addr_type != BLE_ADDR_ANONYMOUS) {
+    btm_ble_process_adv_addr(raw_address, &ble_addr_type);
+  }
+
+  auto callback = found ? &ScanningCallbacks::OnMsftAdvMonitorDeviceFound
+                        : &ScanningCallbacks::OnMsftAdvMonitorDeviceLost;
+  do_in_jni_thread(
+      FROM_HERE, base::BindOnce(callback, base::Unretained(scanning_callbacks_),
+                                monitor_handle, raw_address));
+}
+
 void BleScannerInterfaceImpl::OnTimeout() {}
 void BleScannerInterfaceImpl::OnFilterEnable(bluetooth::hci::Enable enable,
                                              uint8_t status) {}
@@ -614,6 +614,10 @@ class TestScanningCallbacks : public ::ScanningCallbacks {
   void OnPeriodicSyncLost(uint16_t sync_handle) override{};
   void OnPeriodicSyncTransferred(int pa_source, uint8_t status,
                                  RawAddress address) override{};
+  void OnMsftAdvMonitorDeviceFound(uint8_t monitor_handle,
+                                   RawAddress address) override{};
+  void OnMsftAdvMonitorDeviceLost(uint8_t monitor_handle,
+                                  RawAddress address) override{};
 };
 
 TEST_F(MainShimTest, DISABLED_BleScannerInterfaceImpl_OnScanResult) {
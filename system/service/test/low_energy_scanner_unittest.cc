@@ -53,8 +53,9 @@ class MockScannerHandler : public BleScannerInterface {
                     FilterParamSetupCallback cb));
   MOCK_METHOD2(ScanFilterClear, void(int filt_index, FilterConfigCallback cb));
   MOCK_METHOD2(ScanFilterEnable, void(bool enable, EnableCallback cb));
-  MOCK_METHOD4(SetScanParameters, void(int scanner_id, int scan_interval,
-                                       int scan_window, Callback cb));
+  MOCK_METHOD5(SetScanParameters,
+               void(int scanner_id, int scan_interval, int scan_window,
+                    int scan_phy, Callback cb));
 
   MOCK_METHOD5(BatchscanConfigStorage,
                void(int client_if, int batch_scan_full_max,
@@ -83,6 +84,17 @@ class MockScannerHandler : public BleScannerInterface {
   MOCK_METHOD5(SyncTxParameters,
                void(RawAddress, uint8_t, uint16_t, uint16_t, int));
 
+  MOCK_METHOD0(IsMsftSupported, bool());
+
+  MOCK_METHOD2(MsftAdvMonitorAdd,
+               void(MsftAdvMonitor, MsftAdvMonitorAddCallback));
+
+  MOCK_METHOD2(MsftAdvMonitorRemove,
+               void(uint8_t, MsftAdvMonitorRemoveCallback));
+
+  MOCK_METHOD2(MsftAdvMonitorEnable,
+               void(bool, MsftAdvMonitorEnableCallback));
+
   void ScanFilterAdd(int filter_index, std::vector<ApcfCommand> filters,
                      FilterConfigCallback cb) override{};
 
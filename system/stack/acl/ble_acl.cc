@@ -179,6 +179,27 @@ void acl_ble_update_event_received(tHCI_STATUS status, uint16_t handle,
                           timeout, status);
 }
 
+void gatt_notify_subrate_change(const RawAddress& remote,
+                                uint16_t subrate_factor, uint16_t latency,
+                                uint16_t cont_num, uint16_t timeout,
+                                tHCI_STATUS status);
+void acl_ble_subrate_change_event(tHCI_STATUS status, uint16_t handle,
+                                  uint16_t subrate_factor, uint16_t latency,
+                                  uint16_t cont_num, uint16_t timeout) {
+  LOG_DEBUG(
+      "Subrate change event received handle:0x%04x subrate_factor:%hu "
+      "latency:%hu cont_num:%hu timeout:%hu status:%s",
+      handle, subrate_factor, latency, cont_num, timeout,
+      hci_status_code_text(status).c_str());
+
+  tBTM_SEC_DEV_REC* p_dev_rec = btm_find_dev_by_handle(handle);
+
+  if (!p_dev_rec) return;
+
+  gatt_notify_subrate_change(p_dev_rec->ble.pseudo_addr, subrate_factor,
+                             latency, cont_num, timeout, status);
+}
+
 void acl_ble_data_length_change_event(uint16_t handle, uint16_t max_tx_octets,
                                       uint16_t max_tx_time,
                                       uint16_t max_rx_octets,
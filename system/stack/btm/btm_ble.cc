@@ -28,6 +28,7 @@
 #include <cstdint>
 
 #include "device/include/controller.h"
+#include "main/shim/acl_api.h"
 #include "main/shim/btm_api.h"
 #include "main/shim/l2c_api.h"
 #include "main/shim/shim.h"
@@ -832,6 +833,20 @@ void BTM_BleSetPhy(const RawAddress& bd_addr, uint8_t tx_phys, uint8_t rx_phys,
                             base::Bind(doNothing));
 }
 
+void BTM_BleSubrateRequest(const RawAddress& bd_addr, uint16_t subrate_min,
+                           uint16_t subrate_max, uint16_t max_latency,
+                           uint16_t cont_num, uint16_t sup_tout) {
+  if (!BTM_IsAclConnectionUp(bd_addr, BT_TRANSPORT_LE)) {
+    LOG_INFO(
+        "Unable to request subrate because no le acl is connected to device");
+    return;
+  }
+
+  uint16_t handle = BTM_GetHCIConnHandle(bd_addr, BT_TRANSPORT_LE);
+  bluetooth::shim::ACL_LeSubrateRequest(handle, subrate_min, subrate_max,
+                                        max_latency, cont_num, sup_tout);
+}
+
 /*******************************************************************************
  *
  * Function         btm_ble_determine_security_act
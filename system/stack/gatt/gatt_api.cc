@@ -800,12 +800,13 @@ tGATT_STATUS GATTC_Discover(uint16_t conn_id, tGATT_DISC_TYPE disc_type,
  * Parameters       conn_id: connection identifier.
  *                  type    - attribute read type.
  *                  p_read  - read operation parameters.
+ *                  bearer  - bearer to send the request on.
  *
  * Returns          GATT_SUCCESS if command started successfully.
  *
  ******************************************************************************/
 tGATT_STATUS GATTC_Read(uint16_t conn_id, tGATT_READ_TYPE type,
-                        tGATT_READ_PARAM* p_read) {
+                        tGATT_READ_PARAM* p_read, tGATT_BEARER bearer) {
   tGATT_IF gatt_if = GATT_GET_GATT_IF(conn_id);
   uint8_t tcb_idx = GATT_GET_TCB_IDX(conn_id);
   tGATT_TCB* p_tcb = gatt_get_tcb_by_idx(tcb_idx);
@@ -827,6 +828,7 @@ tGATT_STATUS GATTC_Read(uint16_t conn_id, tGATT_READ_TYPE type,
 
   tGATT_CLCB* p_clcb = gatt_clcb_alloc(conn_id);
   if (!p_clcb) return GATT_NO_RESOURCES;
+  gatt_clcb_set_bearer(p_clcb, bearer);
 
   p_clcb->operation = GATTC_OPTYPE_READ;
   p_clcb->op_subtype = type;
@@ -909,12 +911,13 @@ tGATT_STATUS GATTC_Read(uint16_t conn_id, tGATT_READ_TYPE type,
  * Parameters       conn_id: connection identifier.
  *                  type    - attribute write type.
  *                  p_write  - write operation parameters.
+ *                  bearer  - bearer to send the request on.
  *
  * Returns          GATT_SUCCESS if command started successfully.
  *
  ******************************************************************************/
 tGATT_STATUS GATTC_Write(uint16_t conn_id, tGATT_WRITE_TYPE type,
-                         tGATT_VALUE* p_write) {
+                         tGATT_VALUE* p_write, tGATT_BEARER bearer) {
   tGATT_IF gatt_if = GATT_GET_GATT_IF(conn_id);
   uint8_t tcb_idx = GATT_GET_TCB_IDX(conn_id);
   tGATT_TCB* p_tcb = gatt_get_tcb_by_idx(tcb_idx);
@@ -930,6 +933,7 @@ tGATT_STATUS GATTC_Write(uint16_t conn_id, tGATT_WRITE_TYPE type,
 
   tGATT_CLCB* p_clcb = gatt_clcb_alloc(conn_id);
   if (!p_clcb) return GATT_NO_RESOURCES;
+  gatt_clcb_set_bearer(p_clcb, bearer);
 
   p_clcb->operation = GATTC_OPTYPE_WRITE;
   p_clcb->op_subtype = type;
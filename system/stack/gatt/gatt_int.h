@@ -584,6 +584,7 @@ extern bool gatt_tcb_is_cid_busy(tGATT_TCB& tcb, uint16_t cid);
 extern tGATT_REG* gatt_get_regcb(tGATT_IF gatt_if);
 extern bool gatt_is_clcb_allocated(uint16_t conn_id);
 extern tGATT_CLCB* gatt_clcb_alloc(uint16_t conn_id);
+extern void gatt_clcb_set_bearer(tGATT_CLCB* p_clcb, tGATT_BEARER bearer);
 
 extern bool gatt_tcb_get_cid_available_for_indication(
     tGATT_TCB* p_tcb, bool eatt_support, uint16_t** indicate_handle_p,
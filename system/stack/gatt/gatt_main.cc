@@ -588,6 +588,25 @@ void gatt_notify_conn_update(const RawAddress& remote, uint16_t interval,
   }
 }
 
+void gatt_notify_subrate_change(const RawAddress& remote,
+                                uint16_t subrate_factor, uint16_t latency,
+                                uint16_t cont_num, uint16_t timeout,
+                                tHCI_STATUS status) {
+  tGATT_TCB* p_tcb = gatt_find_tcb_by_addr(remote, BT_TRANSPORT_LE);
+
+  if (!p_tcb) return;
+
+  for (int i = 0; i < GATT_MAX_APPS; i++) {
+    tGATT_REG* p_reg = &gatt_cb.cl_rcb[i];
+    if (p_reg->in_use && p_reg->app_cb.p_subrate_chg_cb) {
+      uint16_t conn_id = GATT_CREATE_CONN_ID(p_tcb->tcb_idx, p_reg->gatt_if);
+      (*p_reg->app_cb.p_subrate_chg_cb)(p_reg->gatt_if, conn_id, subrate_factor,
+                                        latency, cont_num, timeout,
+                                        static_cast<tGATT_STATUS>(status));
+    }
+  }
+}
+
 /** This function is called when GATT fixed channel is congested or uncongested
  */
 static void gatt_le_cong_cback(const RawAddress& remote_bda, bool congested) {
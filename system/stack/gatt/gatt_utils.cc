@@ -1004,6 +1004,24 @@ tGATT_CLCB* gatt_clcb_alloc(uint16_t conn_id) {
   return p_clcb;
 }
 
+/*******************************************************************************
+ *
+ * Function         gatt_clcb_set_bearer
+ *
+ * Description      The function picks the channel of a newly allocated
+ *                  connection link control block according to |bearer|
+ *                  instead of the eatt support of the client registration.
+ *
+ * Returns          void
+ *
+ ******************************************************************************/
+void gatt_clcb_set_bearer(tGATT_CLCB* p_clcb, tGATT_BEARER bearer) {
+  if (bearer == GATT_BEARER_AUTO) return;
+
+  p_clcb->cid = gatt_tcb_get_att_cid(*p_clcb->p_tcb,
+                                     bearer == GATT_BEARER_ENHANCED);
+}
+
 /*******************************************************************************
  *
  * Function         gatt_tcb_get_cid_available_for_indication
@@ -46,3 +46,6 @@ void acl_ble_data_length_change_event(uint16_t handle, uint16_t max_tx_octets,
                                       uint16_t max_tx_time,
                                       uint16_t max_rx_octets,
                                       uint16_t max_rx_time);
+void acl_ble_subrate_change_event(tHCI_STATUS status, uint16_t handle,
+                                  uint16_t subrate_factor, uint16_t latency,
+                                  uint16_t cont_num, uint16_t timeout);
@@ -652,6 +652,20 @@ extern void BTM_BleReadPhy(
 extern void BTM_BleSetPhy(const RawAddress& bd_addr, uint8_t tx_phys,
                           uint8_t rx_phys, uint16_t phy_options);
 
+/*******************************************************************************
+ *
+ * Function         BTM_BleSubrateRequest
+ *
+ * Description      To request LE connection subrating for specified LE
+ *                  connection. The result is reported through the GATT
+ *                  subrate change callback.
+ *
+ ******************************************************************************/
+extern void BTM_BleSubrateRequest(const RawAddress& bd_addr,
+                                  uint16_t subrate_min, uint16_t subrate_max,
+                                  uint16_t max_latency, uint16_t cont_num,
+                                  uint16_t sup_tout);
+
 extern void btm_ble_multi_adv_cleanup(void);
 
 /*******************************************************************************
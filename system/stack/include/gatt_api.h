@@ -597,6 +597,16 @@ typedef enum : uint8_t {
   GATTC_OPTYPE_INDICATION = 7,
 } tGATTC_OPTYPE;
 
+/* Bearer a client read or write is sent on
+*/
+typedef enum : uint8_t {
+  /* Enhanced if the client registered with EATT support */
+  GATT_BEARER_AUTO = 0,
+  /* Enhanced if one is available, unenhanced otherwise */
+  GATT_BEARER_ENHANCED = 1,
+  GATT_BEARER_UNENHANCED = 2,
+} tGATT_BEARER;
+
 /* characteristic declaration
 */
 typedef struct {
@@ -689,6 +699,12 @@ typedef void(tGATT_CONN_UPDATE_CB)(tGATT_IF gatt_if, uint16_t conn_id,
                                    uint16_t interval, uint16_t latency,
                                    uint16_t timeout, tGATT_STATUS status);
 
+/* Define a callback function when LE connection subrating changes */
+typedef void(tGATT_SUBRATE_CHG_CB)(tGATT_IF gatt_if, uint16_t conn_id,
+                                   uint16_t subrate_factor, uint16_t latency,
+                                   uint16_t cont_num, uint16_t timeout,
+                                   tGATT_STATUS status);
+
 /* Define the structure that applications use to register with
  * GATT. This structure includes callback functions. All functions
  * MUST be provided.
@@ -703,6 +719,7 @@ typedef struct {
   tGATT_CONGESTION_CBACK* p_congestion_cb{nullptr};
   tGATT_PHY_UPDATE_CB* p_phy_update_cb{nullptr};
   tGATT_CONN_UPDATE_CB* p_conn_update_cb{nullptr};
+  tGATT_SUBRATE_CHG_CB* p_subrate_chg_cb{nullptr};
 } tGATT_CBACK;
 
 /*****************  Start Handle Management Definitions   *********************/
@@ -937,12 +954,14 @@ extern tGATT_STATUS GATTC_Discover(uint16_t conn_id, tGATT_DISC_TYPE disc_type,
  * Parameters       conn_id: connection identifier.
  *                  type    - attribute read type.
  *                  p_read  - read operation parameters.
+ *                  bearer  - bearer to send the request on.
  *
  * Returns          GATT_SUCCESS if command started successfully.
  *
  ******************************************************************************/
 extern tGATT_STATUS GATTC_Read(uint16_t conn_id, tGATT_READ_TYPE type,
-                               tGATT_READ_PARAM* p_read);
+                               tGATT_READ_PARAM* p_read,
+                               tGATT_BEARER bearer = GATT_BEARER_AUTO);
 
 /*******************************************************************************
  *
@@ -954,12 +973,14 @@ extern tGATT_STATUS GATTC_Read(uint16_t conn_id, tGATT_READ_TYPE type,
  * Parameters       conn_id: connection identifier.
  *                  type    - attribute write type.
  *                  p_write  - write operation parameters.
+ *                  bearer  - bearer to send the request on.
  *
  * Returns          GATT_SUCCESS if command started successfully.
  *
  ******************************************************************************/
 extern tGATT_STATUS GATTC_Write(uint16_t conn_id, tGATT_WRITE_TYPE type,
-                                tGATT_VALUE* p_write);
+                                tGATT_VALUE* p_write,
+                                tGATT_BEARER bearer = GATT_BEARER_AUTO);
 
 /*******************************************************************************
  *
@@ -132,11 +132,13 @@ void BTA_GATTC_ReadCharDescr(uint16_t conn_id, uint16_t handle,
 }
 void BTA_GATTC_ReadCharacteristic(uint16_t conn_id, uint16_t handle,
                                   tGATT_AUTH_REQ auth_req,
-                                  GATT_READ_OP_CB callback, void* cb_data) {
+                                  GATT_READ_OP_CB callback, void* cb_data,
+                                  tGATT_BEARER bearer) {
   mock_function_count_map[__func__]++;
 }
 void BTA_GATTC_ReadMultiple(uint16_t conn_id, tBTA_GATTC_MULTI* p_read_multi,
-                            tGATT_AUTH_REQ auth_req) {
+                            tGATT_AUTH_REQ auth_req,
+                            GATT_READ_MULTI_OP_CB callback, void* cb_data) {
   mock_function_count_map[__func__]++;
 }
 void BTA_GATTC_ReadUsingCharUuid(uint16_t conn_id, const bluetooth::Uuid& uuid,
@@ -165,6 +167,7 @@ void BTA_GATTC_WriteCharValue(uint16_t conn_id, uint16_t handle,
                               tGATT_WRITE_TYPE write_type,
                               std::vector<uint8_t> value,
                               tGATT_AUTH_REQ auth_req,
-                              GATT_WRITE_OP_CB callback, void* cb_data) {
+                              GATT_WRITE_OP_CB callback, void* cb_data,
+                              tGATT_BEARER bearer) {
   mock_function_count_map[__func__]++;
 }
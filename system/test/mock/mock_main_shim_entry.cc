@@ -61,6 +61,7 @@ hci::LeAdvertisingManager* GetAdvertising() {
 hci::LeScanningManager* GetScanning() {
   return hci::testing::mock_le_scanning_manager_;
 }
+hci::MsftExtensionManager* GetMsftExtensionManager() { return nullptr; }
 hci::VendorSpecificEventManager* GetVendorSpecificEventManager() {
   return nullptr;
 }
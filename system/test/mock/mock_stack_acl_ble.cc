@@ -76,6 +76,11 @@ void acl_ble_update_event_received(tHCI_STATUS status, uint16_t handle,
   mock_function_count_map[__func__]++;
 }
 
+void acl_ble_subrate_change_event(tHCI_STATUS status, uint16_t handle,
+                                  uint16_t subrate_factor, uint16_t latency,
+                                  uint16_t cont_num, uint16_t timeout) {
+  mock_function_count_map[__func__]++;
+}
 void acl_ble_data_length_change_event(uint16_t handle, uint16_t max_tx_octets,
                                       uint16_t max_tx_time,
                                       uint16_t max_rx_octets,
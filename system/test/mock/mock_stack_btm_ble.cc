@@ -169,6 +169,11 @@ void BTM_BleSetPhy(const RawAddress& bd_addr, uint8_t tx_phys, uint8_t rx_phys,
                    uint16_t phy_options) {
   mock_function_count_map[__func__]++;
 }
+void BTM_BleSubrateRequest(const RawAddress& bd_addr, uint16_t subrate_min,
+                           uint16_t subrate_max, uint16_t max_latency,
+                           uint16_t cont_num, uint16_t sup_tout) {
+  mock_function_count_map[__func__]++;
+}
 void BTM_BleSetPrefConnParams(const RawAddress& bd_addr, uint16_t min_conn_int,
                               uint16_t max_conn_int,
                               uint16_t peripheral_latency,
@@ -116,18 +116,19 @@ tGATT_STATUS GATTC_ExecuteWrite(uint16_t conn_id, bool is_execute) {
   return test::mock::stack_gatt_api::GATTC_ExecuteWrite(conn_id, is_execute);
 }
 tGATT_STATUS GATTC_Read(uint16_t conn_id, tGATT_READ_TYPE type,
-                        tGATT_READ_PARAM* p_read) {
+                        tGATT_READ_PARAM* p_read, tGATT_BEARER bearer) {
   mock_function_count_map[__func__]++;
-  return test::mock::stack_gatt_api::GATTC_Read(conn_id, type, p_read);
+  return test::mock::stack_gatt_api::GATTC_Read(conn_id, type, p_read, bearer);
 }
 tGATT_STATUS GATTC_SendHandleValueConfirm(uint16_t conn_id, uint16_t cid) {
   mock_function_count_map[__func__]++;
   return test::mock::stack_gatt_api::GATTC_SendHandleValueConfirm(conn_id, cid);
 }
 tGATT_STATUS GATTC_Write(uint16_t conn_id, tGATT_WRITE_TYPE type,
-                         tGATT_VALUE* p_write) {
+                         tGATT_VALUE* p_write, tGATT_BEARER bearer) {
   mock_function_count_map[__func__]++;
-  return test::mock::stack_gatt_api::GATTC_Write(conn_id, type, p_write);
+  return test::mock::stack_gatt_api::GATTC_Write(conn_id, type, p_write,
+                                                 bearer);
 }
 tGATT_STATUS GATTS_AddService(tGATT_IF gatt_if, btgatt_db_element_t* service,
                               int count) {
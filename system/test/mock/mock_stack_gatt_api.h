@@ -109,17 +109,18 @@ struct GATTC_ExecuteWrite {
 extern struct GATTC_ExecuteWrite GATTC_ExecuteWrite;
 
 // Name: GATTC_Read
-// Params: uint16_t conn_id, tGATT_READ_TYPE type, tGATT_READ_PARAM* p_read
+// Params: uint16_t conn_id, tGATT_READ_TYPE type, tGATT_READ_PARAM* p_read,
+// tGATT_BEARER bearer
 // Return: tGATT_STATUS
 struct GATTC_Read {
   static tGATT_STATUS return_value;
   std::function<tGATT_STATUS(uint16_t conn_id, tGATT_READ_TYPE type,
-                             tGATT_READ_PARAM* p_read)>
-      body{[](uint16_t conn_id, tGATT_READ_TYPE type,
-              tGATT_READ_PARAM* p_read) { return return_value; }};
+                             tGATT_READ_PARAM* p_read, tGATT_BEARER bearer)>
+      body{[](uint16_t conn_id, tGATT_READ_TYPE type, tGATT_READ_PARAM* p_read,
+              tGATT_BEARER bearer) { return return_value; }};
   tGATT_STATUS operator()(uint16_t conn_id, tGATT_READ_TYPE type,
-                          tGATT_READ_PARAM* p_read) {
-    return body(conn_id, type, p_read);
+                          tGATT_READ_PARAM* p_read, tGATT_BEARER bearer) {
+    return body(conn_id, type, p_read, bearer);
   };
 };
 extern struct GATTC_Read GATTC_Read;
@@ -138,18 +139,18 @@ struct GATTC_SendHandleValueConfirm {
 extern struct GATTC_SendHandleValueConfirm GATTC_SendHandleValueConfirm;
 
 // Name: GATTC_Write
-// Params: uint16_t conn_id, tGATT_WRITE_TYPE type, tGATT_VALUE* p_write
+// Params: uint16_t conn_id, tGATT_WRITE_TYPE type, tGATT_VALUE* p_write,
+// tGATT_BEARER bearer
 // Return: tGATT_STATUS
 struct GATTC_Write {
   static tGATT_STATUS return_value;
   std::function<tGATT_STATUS(uint16_t conn_id, tGATT_WRITE_TYPE type,
-                             tGATT_VALUE* p_write)>
-      body{[](uint16_t conn_id, tGATT_WRITE_TYPE type, tGATT_VALUE* p_write) {
-        return return_value;
-      }};
+                             tGATT_VALUE* p_write, tGATT_BEARER bearer)>
+      body{[](uint16_t conn_id, tGATT_WRITE_TYPE type, tGATT_VALUE* p_write,
+              tGATT_BEARER bearer) { return return_value; }};
   tGATT_STATUS operator()(uint16_t conn_id, tGATT_WRITE_TYPE type,
-                          tGATT_VALUE* p_write) {
-    return body(conn_id, type, p_write);
+                          tGATT_VALUE* p_write, tGATT_BEARER bearer) {
+    return body(conn_id, type, p_write, bearer);
   };
 };
 extern struct GATTC_Write GATTC_Write;
@@ -94,6 +94,12 @@ void gatt_notify_conn_update(const RawAddress& remote, uint16_t interval,
                              tHCI_STATUS status) {
   mock_function_count_map[__func__]++;
 }
+void gatt_notify_subrate_change(const RawAddress& remote,
+                                uint16_t subrate_factor, uint16_t latency,
+                                uint16_t cont_num, uint16_t timeout,
+                                tHCI_STATUS status) {
+  mock_function_count_map[__func__]++;
+}
 void gatt_notify_phy_updated(tGATT_STATUS status, uint16_t handle,
                              uint8_t tx_phy, uint8_t rx_phy) {
   mock_function_count_map[__func__]++;